@@ -1,5 +1,683 @@
 //! Built-in commands that must be handled by the shell itself
 //! (not external processes)
+//!
+//! These commands change the executor's own state, so they receive
+//! `&mut Executor` instead of running as plain functions.
 
-// Placeholder for future built-in command implementations
-// like job control, aliases, etc.
+use crate::executor::{CommandError, CommandResult, Executor, FileOp};
+use crate::parser::{Command, Quoting, Word};
+use crate::utils::{expand_tilde, interrupted, normalize_digits, shape_if_arabic};
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Execute a builtin that needs access to the executor's state
+///
+/// Returns `None` if `name` is not a shell-state builtin.
+pub fn execute_shell_builtin(executor: &mut Executor, name: &str, args: &[&str]) -> Option<CommandResult> {
+    match super::find_builtin(name)?.handler {
+        super::Handler::Shell(run) => Some(run(executor, args)),
+        super::Handler::Plain(_) | super::Handler::Options(_) => None,
+    }
+}
+
+/// Print the executor's working directory (`اين` / pwd)
+pub(super) fn cmd_pwd(executor: &mut Executor, _args: &[&str]) -> CommandResult {
+    CommandResult::Success(format!("{}\n", executor.cwd().display()))
+}
+
+/// Change the executor's working directory (`انتقل` / cd)
+///
+/// With no argument, goes to the home directory.
+pub(super) fn cmd_cd(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    let path = if args.is_empty() {
+        match dirs::home_dir() {
+            Some(home) => home,
+            None => {
+                return CommandError::failed(("لا يمكن إيجاد مجلد المنزل", "Cannot find home directory")).into();
+            }
+        }
+    } else {
+        expand_tilde(args[0])
+    };
+
+    match executor.set_cwd(&path) {
+        Ok(()) => CommandResult::None,
+        Err(e) => CommandError::file(FileOp::ChangeDir, path.display(), e).into(),
+    }
+}
+
+/// Toggle timestamped output (`سجل تشغيل` / `log on`)
+pub(super) fn cmd_log(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    match args {
+        [] => {
+            let state = if executor.timestamps {
+                "مفعّل / on"
+            } else {
+                "معطّل / off"
+            };
+            CommandResult::Success(format!("السجل: {} / Log: {}\n", state, state))
+        }
+        ["تشغيل" | "on"] => {
+            executor.timestamps = true;
+            CommandResult::None
+        }
+        ["ايقاف" | "إيقاف" | "off"] => {
+            executor.timestamps = false;
+            CommandResult::None
+        }
+        _ => CommandError::usage(
+            ("خيار غير صالح", "Invalid option"),
+            ("سجل [تشغيل|ايقاف]", "log [on|off]"),
+        ).into(),
+    }
+}
+
+/// Allow or forbid `>` onto existing files (`امسح_فوق ايقاف` / `clobber off`)
+///
+/// `off` sets noclobber: `>` then fails on an existing file, while `>|`
+/// always overwrites.
+pub(super) fn cmd_clobber(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    match args {
+        [] => {
+            let state = if executor.noclobber {
+                "معطّل / off"
+            } else {
+                "مفعّل / on"
+            };
+            CommandResult::Success(format!("الكتابة فوق الملفات: {} / Clobber: {}\n", state, state))
+        }
+        ["تشغيل" | "on"] => {
+            executor.noclobber = false;
+            CommandResult::None
+        }
+        ["ايقاف" | "إيقاف" | "off"] => {
+            executor.noclobber = true;
+            CommandResult::None
+        }
+        _ => CommandError::usage(
+            ("خيار غير صالح", "Invalid option"),
+            ("امسح_فوق [تشغيل|ايقاف]", "clobber [on|off]"),
+        ).into(),
+    }
+}
+
+/// Turn shell options on and off (`اضبط` / set)
+///
+/// `-e` (errexit) stops a sequence at its first failing command, `-x`
+/// (xtrace) prints each command to stderr before it runs, and `-d`
+/// (dry run) has `احذف`, `انسخ`, `انقل`, `صلاحيات` and `مالك` print what they
+/// would do instead of doing it; `+e`, `+x` and `+d` turn them off. Flags
+/// combine (`-ex`). With no arguments, shows all three.
+pub(super) fn cmd_set(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    let usage = || {
+        CommandError::usage(
+            ("خيار غير صالح", "Invalid option"),
+            ("اضبط [-e|+e] [-x|+x] [-d|+d]", "set [-e|+e] [-x|+x] [-d|+d]"),
+        ).into()
+    };
+
+    if args.is_empty() {
+        let state = |on| if on { ("مفعّل", "on") } else { ("معطّل", "off") };
+        let (errexit_ar, errexit) = state(executor.errexit);
+        let (xtrace_ar, xtrace) = state(executor.xtrace);
+        let (dry_run_ar, dry_run) = state(executor.dry_run);
+        return CommandResult::Success(format!(
+            "الخروج عند الخطأ: {} / errexit: {}\nتتبع الأوامر: {} / xtrace: {}\nتشغيل تجريبي: {} / dry-run: {}\n",
+            errexit_ar, errexit, xtrace_ar, xtrace, dry_run_ar, dry_run
+        ));
+    }
+
+    // Check every flag before changing anything
+    let mut changes = Vec::new();
+    for arg in args {
+        let (on, flags) = match (arg.strip_prefix('-'), arg.strip_prefix('+')) {
+            (Some(flags), _) => (true, flags),
+            (_, Some(flags)) => (false, flags),
+            _ => return usage(),
+        };
+        if flags.is_empty() || !flags.chars().all(|c| matches!(c, 'e' | 'x' | 'd')) {
+            return usage();
+        }
+        changes.extend(flags.chars().map(|flag| (flag, on)));
+    }
+
+    for (flag, on) in changes {
+        match flag {
+            'e' => executor.errexit = on,
+            'x' => executor.xtrace = on,
+            _ => executor.dry_run = on,
+        }
+    }
+    CommandResult::None
+}
+
+/// Register words tab completion offers for a command (`اكمل` / complete)
+///
+/// `اكمل cmd w...` sets the words offered for `cmd`'s arguments, `-r cmd`
+/// removes them and `اكمل cmd` shows them. With no arguments, lists every
+/// command that has some.
+pub(super) fn cmd_complete(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    let Ok(mut table) = executor.completions.write() else {
+        return CommandError::Silent.into();
+    };
+    let line = |name: &str, words: &[String]| format!("{}: {}\n", name, words.join(" "));
+
+    match args {
+        [] => {
+            let mut names: Vec<&String> = table.keys().collect();
+            names.sort();
+            let output: String = names.into_iter().map(|name| line(name, &table[name])).collect();
+            if output.is_empty() {
+                CommandResult::None
+            } else {
+                CommandResult::Success(output)
+            }
+        }
+        ["-r", names @ ..] if !names.is_empty() => {
+            for name in names {
+                table.remove(*name);
+            }
+            CommandResult::None
+        }
+        [name] if !name.starts_with('-') => match table.get(*name) {
+            Some(words) => CommandResult::Success(line(name, words)),
+            None => CommandError::failed((
+                format!("لا توجد إكمالات لـ'{}'", name),
+                format!("No completions for '{}'", name),
+            )).into(),
+        },
+        [name, words @ ..] if !name.starts_with('-') => {
+            let mut unique: Vec<String> = Vec::new();
+            for word in words {
+                if !unique.iter().any(|w| w == word) {
+                    unique.push(word.to_string());
+                }
+            }
+            table.insert(name.to_string(), unique);
+            CommandResult::None
+        }
+        _ => CommandError::usage(
+            ("يرجى تحديد أمر", "Please specify a command"),
+            ("اكمل [-r] <أمر> [كلمة...]", "complete [-r] <command> [word...]"),
+        ).into(),
+    }
+}
+
+/// Wipe the command history (`امسح_ذاكرة` / clear-history)
+///
+/// Asks for confirmation on stderr first unless given `-f`. The history
+/// file is emptied here; the REPL sees `history_cleared` and drops the
+/// entries it holds in memory, so they aren't saved back on exit.
+pub(super) fn cmd_clear_history(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    match args {
+        ["-f"] => {}
+        [] => {
+            eprint!("{}", shape_if_arabic("مسح سجل الأوامر نهائياً؟ [ن/y] / Clear command history permanently? [y/N] "));
+            let _ = io::stderr().flush();
+            let mut answer = String::new();
+            if io::stdin().read_line(&mut answer).is_err() || !is_yes(&answer) {
+                return CommandResult::Success("لم يُمسح السجل / History kept\n".to_string());
+            }
+        }
+        _ => {
+            return CommandError::usage(
+                ("خيار غير صالح", "Invalid option"),
+                ("امسح_ذاكرة [-f]", "clear-history [-f]"),
+            ).into();
+        }
+    }
+
+    // Nothing to empty if no history was ever saved
+    let file = &executor.history_file;
+    match std::fs::OpenOptions::new().write(true).truncate(true).open(file) {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return CommandError::file(FileOp::Truncate, file.display(), e).into(),
+    }
+    executor.history_cleared = true;
+    CommandResult::Success("تم مسح سجل الأوامر / Command history cleared\n".to_string())
+}
+
+/// Whether a confirmation answer means yes (`ن`, `نعم`, `y`, `yes`)
+fn is_yes(answer: &str) -> bool {
+    let answer = answer.trim();
+    ["ن", "نعم", "y", "yes"].iter().any(|yes| answer.eq_ignore_ascii_case(yes))
+}
+
+/// Show where external commands resolve on `PATH` (`واجهة` / which)
+///
+/// Uses the executor's command cache, so it reports exactly what running
+/// the command would use. Fails if any name isn't found.
+pub(super) fn cmd_which(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    if args.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد اسم أمر", "Please specify a command name"),
+            ("واجهة <أمر...>", "which <command...>"),
+        ).into();
+    }
+
+    let mut output = String::new();
+    for name in args {
+        match executor.command_cache.resolve(name) {
+            Some(path) => output.push_str(&format!("{}\n", path.display())),
+            None => {
+                return CommandError::failed((
+                    format!("الأمر '{}' غير موجود في PATH", name),
+                    format!("Command '{}' not found in PATH", name),
+                )).into();
+            }
+        }
+    }
+    CommandResult::Success(output)
+}
+
+/// Run a command and report how long it took (`وقت` / time)
+///
+/// Prints wall-clock time, and on Unix user and system CPU time, to stderr
+/// so piped output stays clean. The command's result and exit code are
+/// passed through unchanged.
+pub(super) fn cmd_time(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    let Some((name, rest)) = args.split_first() else {
+        return CommandError::usage(
+            ("يرجى تحديد أمر", "Please specify a command"),
+            ("وقت <أمر> [وسائط...]", "time <command> [args...]"),
+        ).into();
+    };
+
+    let cpu_before = cpu_times();
+    let start = Instant::now();
+    let result = executor.execute(literal_command(name, rest));
+    let real = start.elapsed();
+
+    let mut report = format!("الوقت الحقيقي / real   {}", format_seconds(real));
+    if let (Some((user_before, sys_before)), Some((user, sys))) = (cpu_before, cpu_times()) {
+        report.push_str(&format!("\nالمستخدم / user       {}", format_seconds(user.saturating_sub(user_before))));
+        report.push_str(&format!("\nالنظام / sys          {}", format_seconds(sys.saturating_sub(sys_before))));
+    }
+    executor.print_error(&report);
+    result
+}
+
+/// Run a command `-n N` times and summarize how long it took (`قياس` / bench)
+///
+/// The command is given quoted, like `قياس 'ls | wc -l'`, and parsed once,
+/// so a whole pipeline or sequence is timed; separate words are joined
+/// first. Only the last run's output is shown; min, max and mean
+/// wall-clock times go to stderr. A failing run stops the benchmark and
+/// its error and exit code are passed through; Ctrl+C between runs stops
+/// it with a notice.
+pub(super) fn cmd_bench(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    let usage = |ar: &str, en: &str| -> CommandResult {
+        CommandError::usage((ar, en), ("قياس [-n عدد] '<أمر>'", "bench [-n COUNT] '<command>'")).into()
+    };
+
+    let (count, args) = match args {
+        ["-n", count, rest @ ..] => (*count, rest),
+        [flag, rest @ ..] if flag.len() > 2 && flag.starts_with("-n") => (&flag[2..], rest),
+        ["-n"] => return usage("الخيار -n يتطلب عدداً", "-n requires a count"),
+        _ => ("10", args),
+    };
+    let runs = match normalize_digits(count).parse::<u32>() {
+        Ok(runs) if runs > 0 => runs,
+        _ => return usage(&format!("عدد غير صالح '{}'", count), &format!("Invalid count '{}'", count)),
+    };
+    if args.is_empty() {
+        return usage("يرجى تحديد أمر", "Please specify a command");
+    }
+    let command = match Executor::parse(&args.join(" ")) {
+        Ok(command) => command,
+        Err(e) => return usage(&e.message.ar, &e.message.en),
+    };
+
+    let mut times = Vec::new();
+    let mut result = CommandResult::None;
+    for _ in 0..runs {
+        if interrupted() {
+            return executor.interrupt();
+        }
+        let start = Instant::now();
+        result = executor.execute(command.clone());
+        times.push(start.elapsed());
+        if !result.is_success() || executor.last_exit_code != 0 {
+            return result;
+        }
+    }
+
+    let total: Duration = times.iter().sum();
+    let report = format!(
+        "التشغيلات / runs    {}\nالأدنى / min        {}\nالأقصى / max        {}\nالمتوسط / mean      {}",
+        runs,
+        format_seconds(times.iter().min().copied().unwrap_or_default()),
+        format_seconds(times.iter().max().copied().unwrap_or_default()),
+        format_seconds(total / runs),
+    );
+    executor.print_error(&report);
+    result
+}
+
+/// A simple command from arguments that were expanded already, so they are
+/// passed on literally
+fn literal_command(name: &str, args: &[&str]) -> Command {
+    Command::Simple {
+        name: name.to_string(),
+        args: args.iter().map(|arg| Word::new(arg.to_string(), Quoting::Single)).collect(),
+        redirects: Vec::new(),
+    }
+}
+
+fn format_seconds(duration: Duration) -> String {
+    format!("{:.3}s", duration.as_secs_f64())
+}
+
+/// User and system CPU time used so far by the shell and its finished children
+#[cfg(unix)]
+fn cpu_times() -> Option<(Duration, Duration)> {
+    use nix::sys::resource::{getrusage, UsageWho};
+    use nix::sys::time::TimeVal;
+
+    let to_duration = |tv: TimeVal| Duration::new(tv.tv_sec() as u64, tv.tv_usec() as u32 * 1000);
+    let own = getrusage(UsageWho::RUSAGE_SELF).ok()?;
+    let children = getrusage(UsageWho::RUSAGE_CHILDREN).ok()?;
+    Some((
+        to_duration(own.user_time()) + to_duration(children.user_time()),
+        to_duration(own.system_time()) + to_duration(children.system_time()),
+    ))
+}
+
+#[cfg(not(unix))]
+fn cpu_times() -> Option<(Duration, Duration)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_on_off_arabic() {
+        let mut executor = Executor::default();
+        assert_eq!(execute_shell_builtin(&mut executor, "سجل", &["تشغيل"]), Some(CommandResult::None));
+        assert!(executor.timestamps);
+        assert_eq!(execute_shell_builtin(&mut executor, "سجل", &["ايقاف"]), Some(CommandResult::None));
+        assert!(!executor.timestamps);
+    }
+
+    #[test]
+    fn test_log_on_off_english() {
+        let mut executor = Executor::default();
+        execute_shell_builtin(&mut executor, "log", &["on"]);
+        assert!(executor.timestamps);
+        assert!(executor.stamp_line("x").starts_with('['));
+        execute_shell_builtin(&mut executor, "log", &["off"]);
+        assert_eq!(executor.stamp_line("x"), "x");
+    }
+
+    #[test]
+    fn test_log_invalid_option() {
+        let mut executor = Executor::default();
+        let result = execute_shell_builtin(&mut executor, "log", &["maybe"]);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Usage")));
+    }
+
+    #[test]
+    fn test_time_passes_result_through() {
+        let mut executor = Executor::default();
+        for name in ["وقت", "time"] {
+            let result = execute_shell_builtin(&mut executor, name, &["اطبع", "مرحبا", "*"]);
+            assert_eq!(result, Some(CommandResult::Success("مرحبا *\n".to_string())));
+        }
+        assert!(!cmd_time(&mut executor, &[]).is_success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_time_propagates_exit_code() {
+        let mut executor = Executor::default();
+        let result = cmd_time(&mut executor, &["sh", "-c", "exit 3"]);
+        assert!(!result.is_success());
+        assert_eq!(executor.last_exit_code, 3);
+        assert!(cpu_times().is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bench_runs_command_count_times() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("runs.log");
+        let script = format!("echo run >> '{}'; echo done", log.display());
+
+        let mut executor = Executor::default();
+        for (name, count) in [("قياس", "3"), ("bench", "٢")] {
+            std::fs::write(&log, "").unwrap();
+            let result = execute_shell_builtin(&mut executor, name, &["-n", count, "sh", "-c", &format!("\"{}\"", script)]);
+            assert_eq!(result, Some(CommandResult::Success("done\n".to_string())));
+            let runs = std::fs::read_to_string(&log).unwrap().lines().count();
+            assert_eq!(runs.to_string(), normalize_digits(count));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bench_stops_at_first_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("runs.log");
+        let script = format!("echo run >> '{}'; exit 3", log.display());
+
+        let mut executor = Executor::default();
+        assert!(!cmd_bench(&mut executor, &["-n5", &format!("sh -c \"{}\"", script)]).is_success());
+        assert_eq!(executor.last_exit_code, 3);
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "run\n");
+    }
+
+    #[test]
+    fn test_bench_times_the_whole_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("runs.log");
+
+        let mut executor = Executor::default();
+        let line = format!("اطبع run >> {} ; لكل x في أ ب ; نفذ اطبع $x ; انتهى | رأس -n 1", log.display());
+        assert_eq!(cmd_bench(&mut executor, &["-n", "2", &line]), CommandResult::Success("أ\n".to_string()));
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "run\nrun\n");
+    }
+
+    #[test]
+    fn test_bench_usage() {
+        let mut executor = Executor::default();
+        for args in [&[][..], &["-n"], &["-n", "0", "اطبع"], &["-n", "كثير", "اطبع"], &["-n", "3"], &["اطبع |"]] {
+            let result = cmd_bench(&mut executor, args);
+            assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("Usage: bench")), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_clobber_toggle() {
+        let mut executor = Executor::default();
+        assert_eq!(execute_shell_builtin(&mut executor, "امسح_فوق", &["ايقاف"]), Some(CommandResult::None));
+        assert!(executor.noclobber);
+        let state = execute_shell_builtin(&mut executor, "clobber", &[]);
+        assert!(matches!(state, Some(CommandResult::Success(msg)) if msg.contains("off")));
+        assert_eq!(execute_shell_builtin(&mut executor, "clobber", &["on"]), Some(CommandResult::None));
+        assert!(!executor.noclobber);
+    }
+
+    #[test]
+    fn test_set_toggles_options() {
+        let mut executor = Executor::default();
+        assert_eq!(execute_shell_builtin(&mut executor, "اضبط", &["-ex"]), Some(CommandResult::None));
+        assert!(executor.errexit && executor.xtrace);
+        assert_eq!(execute_shell_builtin(&mut executor, "set", &["+x"]), Some(CommandResult::None));
+        assert!(executor.errexit && !executor.xtrace);
+        let state = execute_shell_builtin(&mut executor, "set", &[]);
+        assert!(matches!(state, Some(CommandResult::Success(msg)) if msg.contains("errexit: on") && msg.contains("xtrace: off")));
+
+        // A bad flag leaves every option as it was
+        for args in [&["-e", "-q"][..], &["e"], &["-"]] {
+            assert!(!cmd_set(&mut executor, args).is_success());
+        }
+        assert!(executor.errexit);
+    }
+
+    #[test]
+    fn test_complete_registers_and_removes_words() {
+        let mut executor = Executor::default();
+        assert_eq!(
+            execute_shell_builtin(&mut executor, "اكمل", &["mycmd", "--foo", "--bar", "--foo"]),
+            Some(CommandResult::None)
+        );
+        let registered = executor.completions.read().unwrap().get("mycmd").cloned();
+        assert_eq!(registered, Some(vec!["--foo".to_string(), "--bar".to_string()]));
+        assert_eq!(
+            execute_shell_builtin(&mut executor, "complete", &[]),
+            Some(CommandResult::Success("mycmd: --foo --bar\n".to_string()))
+        );
+
+        assert_eq!(execute_shell_builtin(&mut executor, "complete", &["-r", "mycmd"]), Some(CommandResult::None));
+        assert!(executor.completions.read().unwrap().is_empty());
+        assert!(!cmd_complete(&mut executor, &["mycmd"]).is_success());
+        assert!(!cmd_complete(&mut executor, &["-r"]).is_success());
+    }
+
+    #[test]
+    fn test_clear_history_empties_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = Executor::default();
+        executor.history_file = dir.path().join("history");
+        std::fs::write(&executor.history_file, "اطبع سر\n").unwrap();
+
+        let result = execute_shell_builtin(&mut executor, "امسح_ذاكرة", &["-f"]);
+        assert_eq!(result, Some(CommandResult::Success("تم مسح سجل الأوامر / Command history cleared\n".to_string())));
+        assert_eq!(std::fs::read_to_string(&executor.history_file).unwrap(), "");
+        assert!(executor.history_cleared);
+
+        // A missing file is already clear
+        executor.history_file = dir.path().join("missing");
+        assert!(execute_shell_builtin(&mut executor, "clear-history", &["-f"]).unwrap().is_success());
+        assert!(!executor.history_file.exists());
+        assert!(!cmd_clear_history(&mut executor, &["-x"]).is_success());
+    }
+
+    #[test]
+    fn test_confirmation_answers() {
+        for answer in ["ن\n", "نعم", "y", "YES\n"] {
+            assert!(is_yes(answer), "{}", answer);
+        }
+        for answer in ["", "\n", "لا", "n", "yep"] {
+            assert!(!is_yes(answer), "{}", answer);
+        }
+    }
+
+    #[test]
+    fn test_set_dry_run_reaches_file_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("keep.txt");
+        std::fs::write(&file, "").unwrap();
+
+        let mut executor = Executor::default();
+        assert_eq!(execute_shell_builtin(&mut executor, "اضبط", &["-d"]), Some(CommandResult::None));
+        assert!(executor.dry_run);
+        let result = executor.execute_str(&format!("احذف {}", file.display())).unwrap();
+        assert!(matches!(result, CommandResult::Success(msg) if msg.contains("would remove")));
+        assert!(file.exists());
+
+        execute_shell_builtin(&mut executor, "set", &["+d"]);
+        executor.execute_str(&format!("rm {}", file.display())).unwrap();
+        assert!(!file.exists());
+    }
+
+    /// `cd` only changes the executor, never the process cwd
+    #[test]
+    fn test_executors_hold_separate_cwds() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let process_cwd = std::env::current_dir().unwrap();
+
+        let mut a = Executor::default();
+        let mut b = Executor::default();
+        assert_eq!(execute_shell_builtin(&mut a, "انتقل", &[&first.path().to_string_lossy()]), Some(CommandResult::None));
+        assert_eq!(execute_shell_builtin(&mut b, "cd", &[&second.path().to_string_lossy()]), Some(CommandResult::None));
+
+        let pwd = |executor: &mut Executor, name| match execute_shell_builtin(executor, name, &[]) {
+            Some(CommandResult::Success(output)) => std::path::PathBuf::from(output.trim_end()),
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(pwd(&mut a, "اين"), first.path());
+        assert_eq!(pwd(&mut b, "pwd"), second.path());
+        assert_eq!(std::env::current_dir().unwrap(), process_cwd);
+    }
+
+    #[test]
+    fn test_cd_relative_and_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("file"), "").unwrap();
+
+        let mut executor = Executor::default();
+        executor.set_cwd(dir.path()).unwrap();
+        assert_eq!(execute_shell_builtin(&mut executor, "cd", &["sub"]), Some(CommandResult::None));
+        assert_eq!(executor.cwd(), dir.path().join("sub"));
+
+        for target in ["missing", "../file"] {
+            let result = execute_shell_builtin(&mut executor, "cd", &[target]);
+            assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Cannot change")));
+        }
+        assert!(executor.cwd().ends_with("sub"));
+    }
+
+    #[test]
+    fn test_cd_collapses_dot_dot_and_trailing_slashes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+
+        let mut executor = Executor::default();
+        executor.set_cwd(dir.path()).unwrap();
+        execute_shell_builtin(&mut executor, "cd", &["a/b/"]);
+        assert_eq!(executor.cwd(), dir.path().join("a/b"));
+        execute_shell_builtin(&mut executor, "انتقل", &[".././/b/../"]);
+        assert_eq!(executor.cwd(), dir.path().join("a"));
+        assert_eq!(executor.variables.get("PWD").map(String::as_str), Some(&*dir.path().join("a").to_string_lossy()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cd_keeps_logical_path_through_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("real/sub")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real"), dir.path().join("link")).unwrap();
+
+        let mut executor = Executor::default();
+        executor.set_cwd(dir.path().join("link/sub")).unwrap();
+        assert_eq!(execute_shell_builtin(&mut executor, "cd", &[".."]), Some(CommandResult::None));
+        // Physically this is `real`, but `..` returns through the link
+        assert_eq!(executor.cwd(), dir.path().join("link"));
+    }
+
+    #[test]
+    fn test_not_a_shell_builtin() {
+        let mut executor = Executor::default();
+        assert_eq!(execute_shell_builtin(&mut executor, "اطبع", &[]), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_which_arabic_and_english() {
+        let mut executor = Executor::default();
+        for name in ["واجهة", "which"] {
+            match execute_shell_builtin(&mut executor, name, &["sh"]) {
+                Some(CommandResult::Success(output)) => {
+                    let path = std::path::Path::new(output.trim_end());
+                    assert!(path.is_absolute() && path.ends_with("sh"));
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_which_missing_command() {
+        let mut executor = Executor::default();
+        let result = execute_shell_builtin(&mut executor, "which", &["ocean-no-such-command"]);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("not found")));
+        assert!(matches!(execute_shell_builtin(&mut executor, "واجهة", &[]), Some(CommandResult::Error(_))));
+    }
+}
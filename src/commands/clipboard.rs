@@ -0,0 +1,109 @@
+//! Clipboard commands
+//! `نسخ_حافظة` (clipcopy) and `الصق_حافظة` (clippaste), built with the
+//! `clipboard` feature
+
+use crate::executor::{CommandError, CommandResult};
+
+/// The process keeps one clipboard handle: on X11 the copied text is only
+/// served while the handle that set it is alive
+#[cfg(feature = "clipboard")]
+static CLIPBOARD: std::sync::Mutex<Option<arboard::Clipboard>> = std::sync::Mutex::new(None);
+
+/// Run `action` with the system clipboard, opening it on first use
+#[cfg(feature = "clipboard")]
+fn with_clipboard<T>(action: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>) -> Result<T, String> {
+    let mut slot = CLIPBOARD.lock().map_err(|e| e.to_string())?;
+    if slot.is_none() {
+        *slot = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+    }
+    slot.as_mut().map(action).unwrap_or(Err(arboard::Error::ClipboardNotSupported)).map_err(|e| e.to_string())
+}
+
+/// The error for a system without a usable clipboard (no display, say)
+#[cfg(feature = "clipboard")]
+fn not_available(reason: &str) -> CommandResult {
+    CommandError::failed((
+        format!("الحافظة غير متاحة - {}", reason),
+        format!("Clipboard not available - {}", reason),
+    )).into()
+}
+
+/// Copy piped input, or the arguments, to the clipboard (`نسخ_حافظة` / clipcopy)
+///
+/// `اين | نسخ_حافظة` copies the path without its trailing newline.
+pub fn cmd_clipcopy(args: &[&str], input: Option<&str>) -> CommandResult {
+    let text = match input {
+        Some(input) => input.trim_end_matches('\n').to_string(),
+        None if !args.is_empty() => args.join(" "),
+        None => {
+            return CommandError::usage(
+                ("يرجى تمرير نص بالأنبوب أو كوسائط", "Please pipe text in or pass it as arguments"),
+                ("<أمر> | نسخ_حافظة  أو  نسخ_حافظة <نص>", "<command> | clipcopy  or  clipcopy <text>"),
+            ).into();
+        }
+    };
+    copy_text(text)
+}
+
+#[cfg(feature = "clipboard")]
+fn copy_text(text: String) -> CommandResult {
+    match with_clipboard(|clipboard| clipboard.set_text(text)) {
+        Ok(()) => CommandResult::None,
+        Err(reason) => not_available(&reason),
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn not_built_in() -> CommandResult {
+    CommandError::failed((
+        "الحافظة غير مضمنة في هذا البناء",
+        "clipboard not built in (enable the `clipboard` feature)",
+    )).into()
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_text(_text: String) -> CommandResult {
+    not_built_in()
+}
+
+/// Print the clipboard's text (`الصق_حافظة` / clippaste)
+#[cfg(feature = "clipboard")]
+pub fn cmd_clippaste(_args: &[&str]) -> CommandResult {
+    match with_clipboard(|clipboard| clipboard.get_text()) {
+        Ok(text) if text.is_empty() || text.ends_with('\n') => CommandResult::Success(text),
+        Ok(text) => CommandResult::Success(format!("{}\n", text)),
+        Err(reason) => not_available(&reason),
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn cmd_clippaste(_args: &[&str]) -> CommandResult {
+    not_built_in()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::execute_builtin;
+
+    #[test]
+    fn test_clipcopy_needs_text() {
+        for name in ["نسخ_حافظة", "clipcopy"] {
+            let result = execute_builtin(name, &[], None).unwrap();
+            assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("Usage: <command> | clipcopy")));
+        }
+    }
+
+    /// Without a display there is no clipboard, and both commands say so
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn test_clipboard_unavailable_without_display() {
+        if std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            return;
+        }
+        for (name, input) in [("نسخ_حافظة", Some("ocean")), ("الصق_حافظة", None)] {
+            let result = execute_builtin(name, &[], input).unwrap();
+            assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("Clipboard not available")));
+        }
+    }
+}
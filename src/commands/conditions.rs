@@ -0,0 +1,138 @@
+//! Condition commands
+//! `اختبار` (test) for use with `اذا`/if and `&&`/`||`
+
+use super::BuiltinOptions;
+use crate::executor::{CommandError, CommandResult};
+
+use std::fs;
+
+/// Evaluate a condition (`اختبار` / test)
+///
+/// Succeeds silently when the condition holds and fails with an empty error
+/// otherwise, so it can drive `اذا` without printing anything. Supports:
+/// - `-e`/`-f`/`-d`/`-s <path>` - exists / file / directory / non-empty file
+/// - `-z`/`-n <text>` - empty / non-empty text
+/// - `<a> = <b>`, `<a> != <b>` - text comparison
+/// - `<a> -eq|-ne|-lt|-le|-gt|-ge <b>` - integer comparison
+/// - `! <condition>` - negation
+pub fn cmd_test(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    let (negate, args) = match args.split_first() {
+        Some((&"!", rest)) => (true, rest),
+        _ => (false, args),
+    };
+
+    match evaluate(args, options) {
+        Ok(holds) if holds != negate => CommandResult::None,
+        Ok(_) => CommandError::Silent.into(),
+        Err(error) => error.into(),
+    }
+}
+
+fn evaluate(args: &[&str], options: &BuiltinOptions) -> Result<bool, CommandError> {
+    match args {
+        [] => Ok(false),
+        [text] => Ok(!text.is_empty()),
+        [op, operand] => unary(op, operand, options),
+        [left, op, right] => binary(left, op, right),
+        _ => Err(usage()),
+    }
+}
+
+fn unary(op: &str, operand: &str, options: &BuiltinOptions) -> Result<bool, CommandError> {
+    let meta = || fs::metadata(options.path(operand));
+    match op {
+        "-e" => Ok(meta().is_ok()),
+        "-f" => Ok(meta().map(|m| m.is_file()).unwrap_or(false)),
+        "-d" => Ok(meta().map(|m| m.is_dir()).unwrap_or(false)),
+        "-s" => Ok(meta().map(|m| m.len() > 0).unwrap_or(false)),
+        "-z" => Ok(operand.is_empty()),
+        "-n" => Ok(!operand.is_empty()),
+        _ => Err(unknown_operator(op)),
+    }
+}
+
+fn binary(left: &str, op: &str, right: &str) -> Result<bool, CommandError> {
+    match op {
+        "=" | "==" => return Ok(left == right),
+        "!=" => return Ok(left != right),
+        _ => {}
+    }
+
+    let number = |text: &str| {
+        text.parse::<i64>().map_err(|_| {
+            CommandError::failed((format!("'{}' ليس عدداً صحيحاً", text), format!("'{}' is not an integer", text)))
+        })
+    };
+    let (a, b) = (number(left)?, number(right)?);
+    match op {
+        "-eq" => Ok(a == b),
+        "-ne" => Ok(a != b),
+        "-lt" => Ok(a < b),
+        "-le" => Ok(a <= b),
+        "-gt" => Ok(a > b),
+        "-ge" => Ok(a >= b),
+        _ => Err(unknown_operator(op)),
+    }
+}
+
+const USAGE: (&str, &str) = ("اختبار [!] <شرط>", "test [!] <condition>");
+
+fn unknown_operator(op: &str) -> CommandError {
+    let problem = (format!("عامل غير معروف '{}'", op), format!("Unknown operator '{}'", op));
+    CommandError::usage(problem, USAGE)
+}
+
+fn usage() -> CommandError {
+    CommandError::usage(("شرط غير صالح", "Invalid condition"), USAGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::execute_builtin;
+
+    fn holds(name: &str, args: &[&str]) -> bool {
+        execute_builtin(name, args, None).unwrap().is_success()
+    }
+
+    #[test]
+    fn test_file_checks_arabic_and_english() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("x.txt");
+        fs::write(&file, "data").unwrap();
+        let file = file.to_string_lossy().to_string();
+        let dir_path = dir.path().to_string_lossy().to_string();
+
+        for name in ["اختبار", "test"] {
+            assert!(holds(name, &["-f", &file]));
+            assert!(holds(name, &["-s", &file]));
+            assert!(!holds(name, &["-d", &file]));
+            assert!(holds(name, &["-d", &dir_path]));
+            assert!(!holds(name, &["-e", "/nonexistent/ocean-file"]));
+        }
+    }
+
+    #[test]
+    fn test_string_and_integer_comparison() {
+        assert!(holds("test", &["أ", "=", "أ"]));
+        assert!(holds("test", &["أ", "!=", "ب"]));
+        assert!(holds("test", &["-z", ""]));
+        assert!(holds("test", &["3", "-gt", "2"]));
+        assert!(!holds("test", &["3", "-le", "2"]));
+        assert!(holds("test", &["!", "3", "-le", "2"]));
+        assert!(!holds("test", &[]));
+    }
+
+    #[test]
+    fn test_false_condition_is_silent() {
+        assert_eq!(execute_builtin("اختبار", &["-z", "x"], None), Some(CommandResult::Error(CommandError::Silent)));
+    }
+
+    #[test]
+    fn test_invalid_condition() {
+        let result = execute_builtin("test", &["a", "-eq", "1"], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("not an integer")));
+        let result = execute_builtin("test", &["-q", "x"], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Usage")));
+    }
+}
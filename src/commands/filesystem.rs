@@ -1,5 +1,1060 @@
 //! Filesystem-related commands
-//! Advanced file operations will be implemented here
+//! Path resolution and advanced file operations
 
-// Placeholder for future filesystem operations
-// like find, grep, permissions, etc.
+use super::BuiltinOptions;
+use crate::executor::{CommandError, CommandResult, FileOp};
+use crate::utils::{active_locale, format_size, format_timestamp, Message};
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+/// Print canonical absolute paths (`مسار` / realpath)
+///
+/// Symlinks and `.`/`..` are resolved. With `-m`, missing path components
+/// are allowed and normalized textually.
+pub fn cmd_realpath(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    let allow_missing = args.iter().any(|a| *a == "-m" || *a == "-ن");
+    let paths: Vec<&str> = args
+        .iter()
+        .copied()
+        .filter(|a| *a != "-m" && *a != "-ن")
+        .collect();
+
+    if paths.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد مسار", "Please specify a path"),
+            ("مسار [-m] <مسار...>", "realpath [-m] <path...>"),
+        ).into();
+    }
+
+    let mut output = String::new();
+    for path in paths {
+        let expanded = options.path(path);
+        let resolved = if allow_missing {
+            canonicalize_missing(&expanded)
+        } else {
+            fs::canonicalize(&expanded)
+        };
+
+        match resolved {
+            Ok(canonical) => output.push_str(&format!("{}\n", canonical.display())),
+            Err(e) => {
+                return CommandError::file(FileOp::Resolve, path, e).into();
+            }
+        }
+    }
+
+    CommandResult::Success(output)
+}
+
+/// Canonicalize a path whose trailing components may not exist
+///
+/// Existing prefixes are resolved through the filesystem (following
+/// symlinks); the remainder is normalized textually.
+fn canonicalize_missing(path: &Path) -> io::Result<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir()?.join(path)
+    };
+
+    let mut resolved = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => resolved.push(component),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => {
+                resolved.push(name);
+                if let Ok(canonical) = fs::canonicalize(&resolved) {
+                    resolved = canonical;
+                }
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Print the final path component (`اسم_الملف` / basename)
+///
+/// A pure string operation: the filesystem is never consulted. An optional
+/// second argument is stripped from the end of the name.
+pub fn cmd_basename(args: &[&str]) -> CommandResult {
+    if args.is_empty() || args.len() > 2 {
+        return CommandError::usage(
+            ("يرجى تحديد مسار", "Please specify a path"),
+            ("اسم_الملف <مسار> [لاحقة]", "basename <path> [suffix]"),
+        ).into();
+    }
+
+    let mut name = base_name(args[0]);
+    if let Some(suffix) = args.get(1) {
+        if let Some(stripped) = name.strip_suffix(suffix) {
+            if !stripped.is_empty() {
+                name = stripped;
+            }
+        }
+    }
+
+    CommandResult::Success(format!("{}\n", name))
+}
+
+/// Print the parent directory of each path (`اسم_المجلد` / dirname)
+///
+/// A pure string operation: the filesystem is never consulted.
+pub fn cmd_dirname(args: &[&str]) -> CommandResult {
+    if args.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد مسار", "Please specify a path"),
+            ("اسم_المجلد <مسار...>", "dirname <path...>"),
+        ).into();
+    }
+
+    let output: String = args
+        .iter()
+        .map(|path| format!("{}\n", dir_name(path)))
+        .collect();
+    CommandResult::Success(output)
+}
+
+/// POSIX basename of a path string
+fn base_name(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        // Either empty input or only slashes ("/", "//")
+        return if path.is_empty() { "" } else { "/" };
+    }
+    trimmed.rsplit('/').next().unwrap_or(trimmed)
+}
+
+/// POSIX dirname of a path string
+fn dir_name(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return if path.is_empty() { "." } else { "/" };
+    }
+
+    match trimmed.rfind('/') {
+        None => ".",
+        Some(index) => {
+            let parent = trimmed[..index].trim_end_matches('/');
+            if parent.is_empty() { "/" } else { parent }
+        }
+    }
+}
+
+/// Report disk usage of files and directories (`حجم` / du)
+///
+/// Prints one total per argument (default `.`); directory totals sum all
+/// files beneath them. Symlinks are counted by their own size, not followed.
+/// Unreadable entries are skipped with a warning on stderr.
+pub fn cmd_du(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    let human = args.iter().any(|a| *a == "-h" || *a == "--human-readable");
+    let mut paths: Vec<&str> = args
+        .iter()
+        .copied()
+        .filter(|a| *a != "-h" && *a != "--human-readable")
+        .collect();
+    if paths.is_empty() {
+        paths.push(".");
+    }
+
+    let mut output = String::new();
+    for path in paths {
+        let expanded = options.path(path);
+        if let Err(e) = fs::symlink_metadata(&expanded) {
+            return CommandError::file(FileOp::Access, path, e).into();
+        }
+        output.push_str(&format!("{}\t{}\n", format_size(disk_usage(&expanded, options), human), path));
+    }
+
+    CommandResult::Success(output)
+}
+
+/// Sum the sizes of all files under `path`, warning about unreadable entries
+fn disk_usage(path: &Path, options: &BuiltinOptions) -> u64 {
+    let mut total = 0;
+    for entry in walkdir::WalkDir::new(path) {
+        match entry.and_then(|e| e.metadata().map(|m| (e, m))) {
+            Ok((_, meta)) if !meta.is_dir() => total += meta.len(),
+            Ok(_) => {}
+            Err(e) => {
+                let location = e
+                    .path()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| path.display().to_string());
+                let message = (format!("تخطي '{}' - {}", location, e), format!("Skipping '{}' - {}", location, e));
+                warn(message, options);
+            }
+        }
+    }
+    total
+}
+
+/// Report filesystem usage for a path (`مساحة` / df)
+///
+/// Defaults to the current directory. Each figure is printed on its own
+/// line so Arabic labels render correctly in RTL terminals.
+#[cfg(unix)]
+pub fn cmd_df(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    use nix::sys::statvfs::statvfs;
+
+    let path = args.first().copied().unwrap_or(".");
+    let expanded = options.path(path);
+
+    let stat = match statvfs(&expanded) {
+        Ok(stat) => stat,
+        Err(e) => {
+            return CommandError::failed((
+                format!("لا يمكن قراءة نظام الملفات لـ '{}' - {}", path, e),
+                format!("Cannot read filesystem of '{}' - {}", path, e),
+            )).into();
+        }
+    };
+
+    // Field widths differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    let (total, free, available) = {
+        let fragment = stat.fragment_size() as u64;
+        (
+            stat.blocks() as u64 * fragment,
+            stat.blocks_free() as u64 * fragment,
+            stat.blocks_available() as u64 * fragment,
+        )
+    };
+    let used = total.saturating_sub(free);
+    let percent = (used * 100).checked_div(total).unwrap_or(0);
+
+    CommandResult::Success(format!(
+        "المسار / Path: {}\nالكلي / Total: {}\nالمستخدم / Used: {} ({}%)\nالمتاح / Available: {}\n",
+        path,
+        format_size(total, true),
+        format_size(used, true),
+        percent,
+        format_size(available, true)
+    ))
+}
+
+#[cfg(not(unix))]
+pub fn cmd_df(_args: &[&str], _options: &BuiltinOptions) -> CommandResult {
+    CommandError::failed(("أمر مساحة غير مدعوم على هذا النظام", "df not supported on this platform")).into()
+}
+
+/// Show file metadata (`احصاء` / stat)
+///
+/// Prints labeled lines per file: type, size, permissions, and times; on
+/// Unix also the mode in octal and symbolic form, link count, and owner and
+/// group. Symlinks are described themselves, not their targets.
+pub fn cmd_stat(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    if args.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد ملف", "Please specify a file"),
+            ("احصاء <ملف...>", "stat <file...>"),
+        ).into();
+    }
+
+    let mut reports = Vec::new();
+    for path in args {
+        let meta = match fs::symlink_metadata(options.path(path)) {
+            Ok(meta) => meta,
+            Err(e) => {
+                return CommandError::file(FileOp::Access, path, e).into();
+            }
+        };
+
+        #[cfg(unix)]
+        let is_fifo = std::os::unix::fs::FileTypeExt::is_fifo(&meta.file_type());
+        #[cfg(not(unix))]
+        let is_fifo = false;
+
+        let kind = if meta.file_type().is_symlink() {
+            "رابط رمزي / symlink"
+        } else if is_fifo {
+            "أنبوب مسمى / fifo"
+        } else if meta.is_dir() {
+            "مجلد / directory"
+        } else {
+            "ملف / file"
+        };
+        let time = |t: io::Result<std::time::SystemTime>| t.map(format_timestamp).unwrap_or_else(|_| "-".to_string());
+
+        let mut report = format!(
+            "الملف / File: {}\nالنوع / Type: {}\nالحجم / Size: {} ({})\n",
+            path, kind, meta.len(), format_size(meta.len(), true)
+        );
+
+        #[cfg(unix)]
+        {
+            use crate::utils::format_mode;
+            use nix::unistd::{Group, User, Gid, Uid};
+            use std::os::unix::fs::MetadataExt;
+
+            let user = User::from_uid(Uid::from_raw(meta.uid())).ok().flatten().map(|u| u.name);
+            let group = Group::from_gid(Gid::from_raw(meta.gid())).ok().flatten().map(|g| g.name);
+            report.push_str(&format!(
+                "الصلاحيات / Permissions: {:04o} ({})\nالروابط / Links: {}\nالمالك / Owner: {} ({})\nالمجموعة / Group: {} ({})\n",
+                meta.mode() & 0o7777,
+                format_mode(meta.mode()),
+                meta.nlink(),
+                user.as_deref().unwrap_or("?"), meta.uid(),
+                group.as_deref().unwrap_or("?"), meta.gid(),
+            ));
+        }
+        #[cfg(not(unix))]
+        report.push_str(&format!(
+            "الصلاحيات / Permissions: {}\n",
+            if meta.permissions().readonly() { "للقراءة فقط / readonly" } else { "قراءة وكتابة / readwrite" }
+        ));
+
+        report.push_str(&format!(
+            "آخر وصول / Accessed: {}\nآخر تعديل / Modified: {}\n",
+            time(meta.accessed()),
+            time(meta.modified())
+        ));
+        reports.push(report);
+    }
+
+    CommandResult::Success(reports.join("\n"))
+}
+
+/// Create a named pipe (`انبوب_مسمى` / mkfifo)
+///
+/// `انبوب_مسمى PATH [MODE]` creates a FIFO with the octal MODE (default
+/// `644`), applied exactly rather than filtered by the umask. Fails if
+/// PATH already exists.
+#[cfg(unix)]
+pub fn cmd_mkfifo(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    use crate::utils::normalize_digits;
+    use nix::sys::stat::Mode;
+    use std::os::unix::fs::PermissionsExt;
+
+    if args.is_empty() || args.len() > 2 {
+        return CommandError::usage(
+            ("يرجى تحديد المسار", "Please specify a path"),
+            ("انبوب_مسمى <مسار> [وضع]", "mkfifo <path> [mode]"),
+        ).into();
+    }
+
+    let path = options.path(args[0]);
+    let mode = match args.get(1) {
+        None => 0o644,
+        Some(spec) => match u32::from_str_radix(&normalize_digits(spec), 8) {
+            Ok(mode) if mode <= 0o7777 => mode,
+            _ => {
+                return CommandError::failed((
+                    format!("صلاحيات غير صالحة '{}' - استخدم صيغة ثمانية (مثل 644)", spec),
+                    format!("Invalid mode '{}' - use octal format (e.g., 644)", spec),
+                )).into();
+            }
+        },
+    };
+
+    if fs::symlink_metadata(&path).is_ok() {
+        return CommandError::failed((
+            format!("'{}' موجود مسبقاً", args[0]),
+            format!("'{}' already exists", args[0]),
+        )).into();
+    }
+
+    let created = nix::unistd::mkfifo(&path, Mode::from_bits_truncate(mode))
+        .map_err(io::Error::from)
+        .and_then(|()| fs::set_permissions(&path, fs::Permissions::from_mode(mode)));
+    match created {
+        Ok(()) => CommandResult::None,
+        Err(e) => CommandError::file(FileOp::CreatePipe, args[0], e).into(),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn cmd_mkfifo(_args: &[&str], _options: &BuiltinOptions) -> CommandResult {
+    CommandError::failed(("أمر انبوب_مسمى غير مدعوم على هذا النظام", "mkfifo not supported on this platform")).into()
+}
+
+/// Flush filesystem buffers to disk (`مزامنة` / sync)
+///
+/// Useful before unplugging removable media. Takes no arguments.
+#[cfg(unix)]
+pub fn cmd_sync(args: &[&str]) -> CommandResult {
+    if !args.is_empty() {
+        return CommandError::usage(
+            ("الأمر لا يقبل معاملات", "Command takes no arguments"),
+            ("مزامنة", "sync"),
+        ).into();
+    }
+
+    nix::unistd::sync();
+    CommandResult::None
+}
+
+#[cfg(not(unix))]
+pub fn cmd_sync(_args: &[&str]) -> CommandResult {
+    CommandError::failed(("أمر مزامنة غير مدعوم على هذا النظام", "sync not supported on this platform")).into()
+}
+
+/// Guess what kind of file each path is (`راس_ملف` / file)
+///
+/// Sniffs the first 512 bytes for a known signature (ELF, PNG, PDF, gzip),
+/// then falls back to text for valid UTF-8 and data otherwise. Prints one
+/// `path: kind` line per file.
+pub fn cmd_file(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    if args.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد ملف", "Please specify a file"),
+            ("راس_ملف <ملف...>", "file <file...>"),
+        ).into();
+    }
+
+    let mut output = String::new();
+    for path in args {
+        let full = options.path(path);
+        let head = if full.is_dir() {
+            None
+        } else {
+            let mut head = Vec::with_capacity(FILE_SNIFF_LEN);
+            let read = fs::File::open(&full).and_then(|file| file.take(FILE_SNIFF_LEN as u64).read_to_end(&mut head));
+            if let Err(e) = read {
+                return CommandError::file(FileOp::Read, path, e).into();
+            }
+            Some(head)
+        };
+
+        let kind = match head {
+            None => "مجلد / directory",
+            Some(head) => file_kind(&head),
+        };
+        output.push_str(&format!("{}: {}\n", path, kind));
+    }
+    CommandResult::Success(output)
+}
+
+/// How many leading bytes `راس_ملف` looks at
+const FILE_SNIFF_LEN: usize = 512;
+
+/// Classify a file by its leading bytes
+fn file_kind(head: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x7fELF", "ملف تنفيذي ELF / ELF executable"),
+        (b"\x89PNG\r\n\x1a\n", "صورة PNG / PNG image"),
+        (b"%PDF-", "مستند PDF / PDF document"),
+        (b"\x1f\x8b", "بيانات مضغوطة gzip / gzip compressed data"),
+    ];
+
+    if head.is_empty() {
+        return "فارغ / empty";
+    }
+    if let Some((_, kind)) = SIGNATURES.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return kind;
+    }
+
+    // The cut at 512 bytes may split a character; only invalid bytes count
+    let utf8 = match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    };
+    if utf8 && !head.contains(&0) {
+        "نص / text"
+    } else {
+        "بيانات / data"
+    }
+}
+
+/// Print a checksum per file, `hash  name` like coreutils (`تحقق` / checksum)
+///
+/// `-a sha256` (the default) or `-a md5` picks the algorithm. Without
+/// files the piped input is hashed and named `-`.
+#[cfg(feature = "checksum")]
+pub fn cmd_checksum(args: &[&str], input: Option<&str>, options: &BuiltinOptions) -> CommandResult {
+    let usage = |problem: &str, problem_en: &str| -> CommandResult {
+        CommandError::usage((problem, problem_en), ("تحقق [-a sha256|md5] [ملف...]", "checksum [-a sha256|md5] [file...]")).into()
+    };
+
+    let (algorithm, files) = match args {
+        ["-a", name, files @ ..] => match Checksum::by_name(name) {
+            Some(algorithm) => (algorithm, files),
+            None => {
+                return usage(
+                    &format!("خوارزمية غير معروفة '{}'", name),
+                    &format!("Unknown algorithm '{}'", name),
+                );
+            }
+        },
+        ["-a"] => return usage("الخيار -a يتطلب خوارزمية", "-a requires an algorithm"),
+        files => (Checksum::Sha256, files),
+    };
+
+    if files.is_empty() {
+        return match input {
+            Some(text) => match algorithm.digest(text.as_bytes()) {
+                Ok(hash) => CommandResult::Success(format!("{}  -\n", hash)),
+                Err(e) => CommandError::file(FileOp::Read, "-", e).into(),
+            },
+            None => usage("يرجى تحديد ملف أو استخدام أنبوب", "Please specify a file or use a pipe"),
+        };
+    }
+
+    let mut output = String::new();
+    for file in files {
+        match fs::File::open(options.path(file)).and_then(|f| algorithm.digest(f)) {
+            Ok(hash) => output.push_str(&format!("{}  {}\n", hash, file)),
+            Err(e) => return CommandError::file(FileOp::Read, file, e).into(),
+        }
+    }
+    CommandResult::Success(output)
+}
+
+#[cfg(not(feature = "checksum"))]
+pub fn cmd_checksum(_args: &[&str], _input: Option<&str>, _options: &BuiltinOptions) -> CommandResult {
+    CommandError::failed((
+        "أمر تحقق غير مضمن في هذا البناء",
+        "checksum not built in (enable the `checksum` feature)",
+    )).into()
+}
+
+/// Hash algorithms offered by `تحقق`
+#[cfg(feature = "checksum")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Checksum {
+    Sha256,
+    Md5,
+}
+
+#[cfg(feature = "checksum")]
+impl Checksum {
+    fn by_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sha256" => Some(Checksum::Sha256),
+            "md5" => Some(Checksum::Md5),
+            _ => None,
+        }
+    }
+
+    /// Hash everything `reader` yields, as lowercase hex
+    fn digest(self, reader: impl Read) -> io::Result<String> {
+        match self {
+            Checksum::Sha256 => hex_digest::<sha2::Sha256>(reader),
+            Checksum::Md5 => hex_digest::<md5::Md5>(reader),
+        }
+    }
+}
+
+#[cfg(feature = "checksum")]
+fn hex_digest<D: sha2::Digest>(mut reader: impl Read) -> io::Result<String> {
+    let mut hasher = D::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// How recursive traversal treats symlinks (`-P` physical, `-L` follow)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymlinkMode {
+    /// Never follow symlinks (the default)
+    Physical,
+    /// Follow symlinks, skipping any directory already visited
+    Follow,
+}
+
+/// Pull `-L`/`-P` out of an argument list; the last one given wins
+pub fn symlink_mode<'a>(args: &[&'a str]) -> (SymlinkMode, Vec<&'a str>) {
+    let mut mode = SymlinkMode::Physical;
+    let mut rest = Vec::new();
+    for arg in args {
+        match *arg {
+            "-L" => mode = SymlinkMode::Follow,
+            "-P" => mode = SymlinkMode::Physical,
+            other => rest.push(other),
+        }
+    }
+    (mode, rest)
+}
+
+/// Collect every regular file beneath `root`, depth-first in name order
+///
+/// In `Physical` mode symlinks are neither entered nor returned. In `Follow`
+/// mode they are resolved, and the canonical path of each directory is
+/// recorded so a link back to an already visited directory is skipped with
+/// a warning instead of recursing forever.
+pub fn walk_files(root: &Path, mode: SymlinkMode, options: &BuiltinOptions) -> Vec<PathBuf> {
+    walk(root, mode, options)
+        .into_iter()
+        .filter(|(_, meta)| meta.is_file())
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Collect `root` and everything beneath it, each directory before its
+/// contents and siblings in name order
+///
+/// Symlinks are treated as in [`walk_files`]; other entries such as FIFOs
+/// are included. `root` itself is always followed.
+pub fn walk_tree(root: &Path, mode: SymlinkMode, options: &BuiltinOptions) -> Vec<PathBuf> {
+    walk(root, mode, options).into_iter().map(|(path, _)| path).collect()
+}
+
+/// Pre-order traversal shared by [`walk_files`] and [`walk_tree`]
+///
+/// Uses an explicit work stack, so deep trees can't overflow the call stack.
+/// Unreadable entries are skipped with a warning.
+fn walk(root: &Path, mode: SymlinkMode, options: &BuiltinOptions) -> Vec<(PathBuf, fs::Metadata)> {
+    let mut found = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(path) = pending.pop() {
+        let meta = if mode == SymlinkMode::Follow || path == root {
+            fs::metadata(&path)
+        } else {
+            fs::symlink_metadata(&path)
+        };
+        let meta = match meta {
+            Ok(meta) if meta.file_type().is_symlink() => continue,
+            Ok(meta) => meta,
+            Err(e) => {
+                warn_skipped(&path, &e, options);
+                continue;
+            }
+        };
+
+        if meta.is_dir() {
+            if mode == SymlinkMode::Follow {
+                match fs::canonicalize(&path) {
+                    Ok(canonical) => {
+                        if !visited.insert(canonical) {
+                            let location = path.display();
+                            warn(
+                                (
+                                    format!("تخطي حلقة روابط عند '{}'", location),
+                                    format!("Skipping symlink loop at '{}'", location),
+                                ),
+                                options,
+                            );
+                            continue;
+                        }
+                    }
+                    Err(e) => {
+                        warn_skipped(&path, &e, options);
+                        continue;
+                    }
+                }
+            }
+
+            match fs::read_dir(&path) {
+                Ok(entries) => {
+                    let mut children: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+                    children.sort();
+                    // Reversed, so the first name is popped first
+                    pending.extend(children.into_iter().rev());
+                }
+                Err(e) => warn_skipped(&path, &e, options),
+            }
+        }
+        found.push((path, meta));
+    }
+    found
+}
+
+/// Warn that `path` was skipped during a traversal
+pub fn warn_skipped(path: &Path, e: &io::Error, options: &BuiltinOptions) {
+    let location = path.display();
+    warn((format!("تخطي '{}' - {}", location, e), format!("Skipping '{}' - {}", location, e)), options);
+}
+
+/// Print a warning on the command's stderr in the active locale
+fn warn(message: impl Into<Message>, options: &BuiltinOptions) {
+    options.print_error(&Message::warning(message).render(active_locale()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::execute_builtin;
+
+    #[test]
+    fn test_realpath_arabic_and_english() {
+        let dir = tempfile::tempdir().unwrap();
+        let canonical = fs::canonicalize(dir.path()).unwrap();
+        let dotted = dir.path().join("sub").join("..");
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let dotted = dotted.to_str().unwrap();
+
+        let expected = CommandResult::Success(format!("{}\n", canonical.display()));
+        assert_eq!(execute_builtin("مسار", &[dotted], None), Some(expected.clone()));
+        assert_eq!(execute_builtin("realpath", &[dotted], None), Some(expected));
+    }
+
+    #[test]
+    fn test_realpath_multiple_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        fs::write(&a, "").unwrap();
+        fs::write(&b, "").unwrap();
+
+        let result = cmd_realpath(&[a.to_str().unwrap(), b.to_str().unwrap()], &BuiltinOptions::default());
+        let output = result.output().unwrap().to_string();
+        assert_eq!(output.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_realpath_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing").join("file");
+        let missing = missing.to_str().unwrap();
+
+        assert!(matches!(cmd_realpath(&[missing], &BuiltinOptions::default()), CommandResult::Error(_)));
+
+        let canonical = fs::canonicalize(dir.path()).unwrap();
+        assert_eq!(
+            cmd_realpath(&["-m", missing], &BuiltinOptions::default()),
+            CommandResult::Success(format!("{}\n", canonical.join("missing/file").display()))
+        );
+    }
+
+    #[test]
+    fn test_basename_arabic_and_english() {
+        let expected = Some(CommandResult::Success("ملف.txt\n".to_string()));
+        assert_eq!(execute_builtin("اسم_الملف", &["/مجلد/ملف.txt"], None), expected);
+        assert_eq!(execute_builtin("basename", &["/مجلد/ملف.txt"], None), expected);
+    }
+
+    #[test]
+    fn test_basename_edge_cases() {
+        assert_eq!(base_name("dir/sub/"), "sub");
+        assert_eq!(base_name("/"), "/");
+        assert_eq!(base_name("//"), "/");
+        assert_eq!(base_name("file"), "file");
+        assert_eq!(base_name(""), "");
+    }
+
+    #[test]
+    fn test_basename_suffix() {
+        assert_eq!(cmd_basename(&["a/notes.txt", ".txt"]), CommandResult::Success("notes\n".to_string()));
+        // A suffix equal to the whole name is not stripped
+        assert_eq!(cmd_basename(&[".txt", ".txt"]), CommandResult::Success(".txt\n".to_string()));
+    }
+
+    #[test]
+    fn test_dirname_arabic_and_english() {
+        let expected = Some(CommandResult::Success("/مجلد\n".to_string()));
+        assert_eq!(execute_builtin("اسم_المجلد", &["/مجلد/ملف.txt"], None), expected);
+        assert_eq!(execute_builtin("dirname", &["/مجلد/ملف.txt"], None), expected);
+    }
+
+    #[test]
+    fn test_dirname_edge_cases() {
+        assert_eq!(dir_name("dir/sub/"), "dir");
+        assert_eq!(dir_name("/usr"), "/");
+        assert_eq!(dir_name("/"), "/");
+        assert_eq!(dir_name("file"), ".");
+        assert_eq!(dir_name("a//b"), "a");
+        assert_eq!(dir_name(""), ".");
+    }
+
+    #[test]
+    fn test_dirname_multiple() {
+        assert_eq!(cmd_dirname(&["a/b", "c"]), CommandResult::Success("a\n.\n".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_realpath_follows_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        fs::write(&target, "").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let canonical = fs::canonicalize(&target).unwrap();
+        assert_eq!(
+            cmd_realpath(&[link.to_str().unwrap()], &BuiltinOptions::default()),
+            CommandResult::Success(format!("{}\n", canonical.display()))
+        );
+    }
+
+    #[test]
+    fn test_du_arabic_and_english() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("a.txt"), vec![b'a'; 1000]).unwrap();
+        fs::write(dir.path().join("sub").join("b.txt"), vec![b'b'; 1048]).unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+
+        for name in ["حجم", "du"] {
+            let result = execute_builtin(name, &[&path], None);
+            assert_eq!(result, Some(CommandResult::Success(format!("2048\t{}\n", path))));
+        }
+    }
+
+    #[test]
+    fn test_du_human_readable() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.bin");
+        fs::write(&file, vec![0u8; 1536]).unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        let result = execute_builtin("du", &["-h", &path], None);
+        assert_eq!(result, Some(CommandResult::Success(format!("1.5 KB\t{}\n", path))));
+    }
+
+    #[test]
+    fn test_du_missing_path() {
+        let result = execute_builtin("حجم", &["/nonexistent/ocean-path"], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Cannot access")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_df_arabic_and_english() {
+        for name in ["مساحة", "df"] {
+            match execute_builtin(name, &["/"], None) {
+                Some(CommandResult::Success(output)) => {
+                    let lines: Vec<&str> = output.lines().collect();
+                    assert_eq!(lines.len(), 4);
+                    assert_eq!(lines[0], "المسار / Path: /");
+                    assert!(lines[1].starts_with("الكلي / Total: "));
+                    assert!(lines[3].starts_with("المتاح / Available: "));
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_df_missing_path() {
+        let result = execute_builtin("df", &["/nonexistent/ocean-path"], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Cannot read filesystem")));
+    }
+
+    #[test]
+    fn test_symlink_mode_last_flag_wins() {
+        assert_eq!(symlink_mode(&["-L", "a"]), (SymlinkMode::Follow, vec!["a"]));
+        assert_eq!(symlink_mode(&["-L", "-P", "a"]), (SymlinkMode::Physical, vec!["a"]));
+        assert_eq!(symlink_mode(&["a"]).0, SymlinkMode::Physical);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_files_self_referential_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("file.txt"), "").unwrap();
+        std::os::unix::fs::symlink(&sub, sub.join("loop")).unwrap();
+
+        // Following the loop visits each real directory once
+        let followed = walk_files(dir.path(), SymlinkMode::Follow, &BuiltinOptions::default());
+        assert_eq!(followed, vec![sub.join("file.txt")]);
+
+        // Physical mode never enters the link
+        let physical = walk_files(dir.path(), SymlinkMode::Physical, &BuiltinOptions::default());
+        assert_eq!(physical, vec![sub.join("file.txt")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_files_follows_links_only_with_follow() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("target.txt"), "").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+
+        assert!(walk_files(dir.path(), SymlinkMode::Physical, &BuiltinOptions::default()).is_empty());
+        assert_eq!(
+            walk_files(dir.path(), SymlinkMode::Follow, &BuiltinOptions::default()),
+            vec![dir.path().join("link").join("target.txt")]
+        );
+    }
+
+    #[test]
+    fn test_stat_arabic_and_english() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("ملف.txt");
+        fs::write(&file, "12345").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        for name in ["احصاء", "stat"] {
+            let output = execute_builtin(name, &[&path], None).unwrap().output().unwrap().to_string();
+            assert!(output.starts_with(&format!("الملف / File: {}\n", path)));
+            assert!(output.contains("النوع / Type: ملف / file\n"));
+            assert!(output.contains("الحجم / Size: 5 (5 B)\n"));
+            assert!(output.contains("آخر تعديل / Modified: "));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stat_unix_fields() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("script.sh");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o754)).unwrap();
+
+        let output = cmd_stat(&[file.to_str().unwrap()], &BuiltinOptions::default()).output().unwrap().to_string();
+        assert!(output.contains("الصلاحيات / Permissions: 0754 (-rwxr-xr--)\n"));
+        assert!(output.contains("الروابط / Links: 1\n"));
+        assert!(output.contains("المالك / Owner: "));
+    }
+
+    #[test]
+    fn test_stat_missing_file() {
+        let result = execute_builtin("احصاء", &["/nonexistent/ocean-path"], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Cannot access")));
+        assert!(!cmd_stat(&[], &BuiltinOptions::default()).is_success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mkfifo_arabic_and_english() {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        for (name, mode, expected) in [("انبوب_مسمى", None, 0o644), ("mkfifo", Some("٦٠٠"), 0o600)] {
+            let path = dir.path().join(name);
+            let path_str = path.to_string_lossy().to_string();
+            let mut args = vec![path_str.as_str()];
+            args.extend(mode);
+
+            assert_eq!(execute_builtin(name, &args, None), Some(CommandResult::None));
+            let meta = fs::symlink_metadata(&path).unwrap();
+            assert!(meta.file_type().is_fifo());
+            assert_eq!(meta.permissions().mode() & 0o7777, expected);
+
+            let stat = cmd_stat(&[&path_str], &BuiltinOptions::default());
+            assert!(stat.output().unwrap().contains("fifo"));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mkfifo_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("file");
+        fs::write(&existing, "").unwrap();
+        let existing = existing.to_string_lossy().to_string();
+        let fresh = dir.path().join("pipe").to_string_lossy().to_string();
+
+        assert!(matches!(cmd_mkfifo(&[&existing], &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("already exists")));
+        assert!(matches!(cmd_mkfifo(&[&fresh, "9z"], &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Invalid mode")));
+        assert!(!cmd_mkfifo(&[], &BuiltinOptions::default()).is_success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sync_arabic_and_english() {
+        for name in ["مزامنة", "نظف", "sync"] {
+            assert_eq!(execute_builtin(name, &[], None), Some(CommandResult::None));
+        }
+        assert!(matches!(cmd_sync(&["extra"]), CommandResult::Error(msg) if msg.to_string().contains("takes no arguments")));
+    }
+
+    #[test]
+    fn test_walk_tree_preorder() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("b/c")).unwrap();
+        fs::write(dir.path().join("a"), "").unwrap();
+        fs::write(dir.path().join("b/c/d"), "").unwrap();
+        fs::write(dir.path().join("e"), "").unwrap();
+
+        let relative: Vec<PathBuf> = walk_tree(dir.path(), SymlinkMode::Physical, &BuiltinOptions::default())
+            .iter()
+            .map(|path| path.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        let expected: Vec<PathBuf> = ["", "a", "b", "b/c", "b/c/d", "e"].iter().map(PathBuf::from).collect();
+        assert_eq!(relative, expected);
+    }
+
+    #[test]
+    fn test_walk_tree_deep_nesting() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut deepest = dir.path().to_path_buf();
+        for _ in 0..1000 {
+            deepest.push("d");
+        }
+        fs::create_dir_all(&deepest).unwrap();
+        assert_eq!(walk_tree(dir.path(), SymlinkMode::Physical, &BuiltinOptions::default()).len(), 1001);
+    }
+
+    #[test]
+    fn test_file_kind_signatures() {
+        assert_eq!(file_kind(b""), "فارغ / empty");
+        assert_eq!(file_kind(b"\x7fELF\x02\x01\x01\0\0"), "ملف تنفيذي ELF / ELF executable");
+        assert_eq!(file_kind(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), "صورة PNG / PNG image");
+        assert_eq!(file_kind(b"%PDF-1.7\n"), "مستند PDF / PDF document");
+        assert_eq!(file_kind(b"\x1f\x8b\x08\0"), "بيانات مضغوطة gzip / gzip compressed data");
+        assert_eq!(file_kind("مرحبا\n".as_bytes()), "نص / text");
+        assert_eq!(file_kind(b"\xff\xfe\x00a"), "بيانات / data");
+        assert_eq!(file_kind(b"text\0with nul"), "بيانات / data");
+
+        // A character cut off at the end of the sniffed bytes is still text
+        let cut = &"سطر".as_bytes()[..5];
+        assert_eq!(file_kind(cut), "نص / text");
+    }
+
+    #[test]
+    fn test_file_reports_each_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("notes.txt");
+        let png = dir.path().join("image.png");
+        fs::write(&text, "ا".repeat(FILE_SNIFF_LEN)).unwrap();
+        fs::write(&png, b"\x89PNG\r\n\x1a\n").unwrap();
+        let (text, png) = (text.to_string_lossy().to_string(), png.to_string_lossy().to_string());
+        let dir_path = dir.path().to_string_lossy().to_string();
+
+        for name in ["راس_ملف", "file"] {
+            let output = execute_builtin(name, &[&text, &png, &dir_path], None).unwrap();
+            assert_eq!(output, CommandResult::Success(format!(
+                "{}: نص / text\n{}: صورة PNG / PNG image\n{}: مجلد / directory\n",
+                text, png, dir_path
+            )));
+        }
+        assert!(matches!(cmd_file(&["/nonexistent/ocean"], &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Cannot read")));
+        assert!(!cmd_file(&[], &BuiltinOptions::default()).is_success());
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_checksum_known_vectors() {
+        const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+        let dir = tempfile::tempdir().unwrap();
+        let (empty, abc) = (dir.path().join("empty"), dir.path().join("abc.txt"));
+        fs::write(&empty, "").unwrap();
+        fs::write(&abc, "abc").unwrap();
+        let (empty, abc) = (empty.to_string_lossy().to_string(), abc.to_string_lossy().to_string());
+
+        for name in ["تحقق", "checksum"] {
+            let output = execute_builtin(name, &[&empty, &abc], None).unwrap();
+            assert_eq!(output, CommandResult::Success(format!("{}  {}\n{}  {}\n", EMPTY_SHA256, empty, ABC_SHA256, abc)));
+        }
+
+        let output = execute_builtin("checksum", &["-a", "MD5", &empty], None).unwrap();
+        assert_eq!(output, CommandResult::Success(format!("d41d8cd98f00b204e9800998ecf8427e  {}\n", empty)));
+        // Piped input is hashed when no file is given
+        let output = execute_builtin("تحقق", &["-a", "md5"], Some("abc")).unwrap();
+        assert_eq!(output, CommandResult::Success("900150983cd24fb0d6963f7d28e17f72  -\n".to_string()));
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_checksum_errors() {
+        assert!(matches!(cmd_checksum(&["-a", "crc"], Some("x"), &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Unknown algorithm 'crc'")));
+        assert!(matches!(cmd_checksum(&["/nonexistent/ocean"], None, &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Cannot read")));
+        assert!(!cmd_checksum(&[], None, &BuiltinOptions::default()).is_success());
+    }
+}
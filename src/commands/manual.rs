@@ -0,0 +1,82 @@
+//! Manual pages
+//! `صفحة` (man) and `مساعدة أمر` print one builtin's usage, options and an
+//! example, as written on its entry in the builtin table
+
+use super::registry::{find_builtin, BuiltinSpec};
+use crate::executor::{CommandError, CommandResult};
+
+/// Print a builtin's manual page (`صفحة` / man)
+///
+/// The command may be named in Arabic, English or by an alias.
+pub fn cmd_manual(args: &[&str]) -> CommandResult {
+    let [name] = args else {
+        return CommandError::usage(
+            ("يرجى تحديد أمر واحد", "Please specify one command"),
+            ("صفحة <أمر>  أو  مساعدة [أمر]", "man <command>  or  help [command]"),
+        ).into();
+    };
+    match find_builtin(name) {
+        Some(spec) => CommandResult::Success(render(spec)),
+        None => CommandError::failed((
+            format!("لا يوجد أمر باسم '{}'", name),
+            format!("No command named '{}'", name),
+        )).into(),
+    }
+}
+
+fn render(spec: &BuiltinSpec) -> String {
+    let (ar, en) = spec.description;
+    let mut out = format!("{} ({})\n  {}\n  {}\n\n", spec.arabic, spec.english, ar, en);
+    out.push_str(&format!("الاستخدام / Usage:\n  {}\n", spec.usage));
+
+    if !spec.options.is_empty() {
+        let width = spec.options.iter().map(|(flag, _, _)| flag.chars().count()).max().unwrap_or(0);
+        out.push_str("\nالخيارات / Options:\n");
+        for (flag, ar, en) in spec.options {
+            let pad = " ".repeat(width - flag.chars().count());
+            out.push_str(&format!("  {}{}  {} / {}\n", flag, pad, ar, en));
+        }
+    }
+
+    out.push_str(&format!("\nمثال / Example:\n  {}\n", spec.example));
+    if !spec.aliases.is_empty() {
+        out.push_str(&format!("\nأسماء أخرى / Also: {}\n", spec.aliases.join("، ")));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::execute_builtin;
+    use crate::commands::registry::BUILTINS;
+
+    #[test]
+    fn test_every_builtin_has_a_page() {
+        for spec in BUILTINS {
+            assert!(spec.usage.contains(spec.arabic), "usage of '{}'", spec.english);
+            assert!(!spec.description.0.is_empty() && !spec.description.1.is_empty(), "description of '{}'", spec.english);
+            assert!(!spec.example.is_empty(), "example of '{}'", spec.english);
+        }
+    }
+
+    #[test]
+    fn test_help_for_one_command() {
+        for (name, args) in [("مساعدة", &["اعرض"]), ("help", &["ls"]), ("صفحة", &["dir"]), ("man", &["اعرض"])] {
+            let Some(CommandResult::Success(text)) = execute_builtin(name, args, None) else {
+                panic!("no page from {} {:?}", name, args);
+            };
+            assert!(text.starts_with("اعرض (ls)\n"), "{}", text);
+            assert!(text.contains("  -l      تنسيق طويل: الصلاحيات والحجم ووقت التعديل / long format: mode, size and modification time\n"), "{}", text);
+            assert!(text.contains("أسماء أخرى / Also: dir"));
+        }
+    }
+
+    #[test]
+    fn test_manual_errors() {
+        let result = cmd_manual(&["ocean-no-such-command"]);
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("No command named 'ocean-no-such-command'")));
+        assert!(matches!(cmd_manual(&[]), CommandResult::Error(msg) if msg.to_string().contains("Usage: man <command>")));
+        assert!(!cmd_manual(&["ls", "cp"]).is_success());
+    }
+}
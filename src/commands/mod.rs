@@ -4,55 +4,95 @@
 //! Each command returns a CommandResult for pipeline support.
 
 mod builtin;
+mod clipboard;
+mod conditions;
 mod filesystem;
-
-use crate::executor::CommandResult;
-use crate::utils::{expand_tilde, shape_arabic};
+mod manual;
+mod process;
+mod registry;
+mod text;
+
+use clipboard::{cmd_clipcopy, cmd_clippaste};
+use conditions::cmd_test;
+use manual::cmd_manual;
+use process::{cmd_ps, cmd_kill};
+use text::{cmd_tr, cmd_cut, cmd_sed, cmd_head, cmd_diff};
+use filesystem::{cmd_realpath, cmd_basename, cmd_dirname, cmd_du, cmd_df, cmd_stat, cmd_file, cmd_checksum, cmd_mkfifo, cmd_sync, symlink_mode, walk_files, SymlinkMode};
+
+pub use builtin::execute_shell_builtin;
+#[allow(unused_imports)]
+pub use registry::{builtin_commands, find_builtin, BuiltinSpec, Handler, Section, BUILTINS};
+
+use crate::executor::{CommandError, CommandResult, FileOp};
+use crate::utils::{expand_tilde, format_size, format_timestamp, normalize_digits, shape_arabic, shape_if_arabic, colorize_entry_full, paint, theme_by_name, active_theme, set_theme, THEMES};
+use crate::utils::{active_locale, locale_by_name, set_locale, LOCALES};
 
 use std::env;
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 pub fn execute_builtin(name: &str, args: &[&str], input: Option<&str>) -> Option<CommandResult> {
-    match name {
-        "خروج" | "exit" | "quit" => Some(CommandResult::Exit(0)),
-
-        "مساعدة" | "help" | "?" => Some(cmd_help()),
-
-        "اطبع" | "echo" => Some(cmd_echo(args, input)),
-
-        "امسح" | "clear" | "cls" => Some(cmd_clear()),
-
-        "اين" | "pwd" => Some(cmd_pwd()),
-
-        "انتقل" | "cd" => Some(cmd_cd(args)),
-
-        "اعرض" | "ls" | "dir" => Some(cmd_ls(args)),
-
-        "اقرأ" | "cat" => Some(cmd_cat(args, input)),
-
-        "انشئ" | "mkdir" => Some(cmd_mkdir(args)),
-
-        "المس" | "touch" => Some(cmd_touch(args)),
-
-        "احذف" | "rm" => Some(cmd_rm(args)),
-
-        "انسخ" | "cp" => Some(cmd_cp(args)),
-
-        "انقل" | "mv" => Some(cmd_mv(args)),
+    execute_builtin_with(name, args, input, BuiltinOptions::default())
+}
 
-        "اصدار" | "version" => Some(cmd_version()),
+/// What the executor tells a plain builtin about how it runs
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinOptions<'a> {
+    /// Color is on and the output goes straight to a terminal, so it may be
+    /// highlighted
+    pub color: bool,
+    /// Describe file changes instead of making them (`اضبط -d` / `--dry-run`)
+    pub dry_run: bool,
+    /// The executor's directory, which relative paths are resolved against
+    /// (empty for the process's own)
+    pub cwd: &'a Path,
+    /// A background job's buffer, which warnings go to instead of stderr
+    pub stderr: Option<&'a Mutex<String>>,
+}
 
-        "ابحث" | "grep" | "search" => Some(cmd_search(args, input)),
+impl Default for BuiltinOptions<'_> {
+    fn default() -> Self {
+        Self { color: false, dry_run: false, cwd: Path::new(""), stderr: None }
+    }
+}
 
-        "صلاحيات" | "chmod" => Some(cmd_chmod(args)),
+impl BuiltinOptions<'_> {
+    /// A path argument with `~` expanded, resolved against [`Self::cwd`]
+    pub fn path(&self, arg: &str) -> PathBuf {
+        self.cwd.join(expand_tilde(arg))
+    }
 
-        "مالك" | "chown" => Some(cmd_chown(args)),
+    /// Print error text where this command's stderr goes
+    pub fn print_error(&self, text: &str) {
+        print_error(text, self.stderr);
+    }
+}
 
-        "رابط" | "ln" | "link" => Some(cmd_ln(args)),
+/// Print error text on stderr, or append it unshaped to a background job's
+/// `buffer`
+pub fn print_error(text: &str, buffer: Option<&Mutex<String>>) {
+    match buffer {
+        Some(buffer) => {
+            if let Ok(mut buffer) = buffer.lock() {
+                for line in text.lines() {
+                    buffer.push_str(line);
+                    buffer.push('\n');
+                }
+            }
+        }
+        None => eprintln!("{}", shape_if_arabic(text)),
+    }
+}
 
-        _ => None,
+/// Run a plain builtin with the executor's options
+pub fn execute_builtin_with(name: &str, args: &[&str], input: Option<&str>, options: BuiltinOptions) -> Option<CommandResult> {
+    // Arabic names, English names and aliases all resolve through the table
+    match find_builtin(name)?.handler {
+        Handler::Plain(run) => Some(run(args, input)),
+        Handler::Options(run) => Some(run(args, input, &options)),
+        Handler::Shell(_) => None,
     }
 }
 
@@ -68,17 +108,19 @@ pub fn execute_command(input: &str) -> bool {
     let args = &parts[1..];
 
     if let Some(result) = execute_builtin(command, args, None) {
-        match result {
-            CommandResult::Exit(_) => return true,
-            CommandResult::Success(output) => {
-                if !output.is_empty() {
-                    print!("{}", output);
+        for part in result.parts() {
+            match part {
+                CommandResult::Exit(_) => return true,
+                CommandResult::Success(output) => {
+                    if !output.is_empty() {
+                        print!("{}", output);
+                    }
                 }
+                CommandResult::Error(msg) => {
+                    eprintln!("{}", msg);
+                }
+                CommandResult::None | CommandResult::Sequence(_) => {}
             }
-            CommandResult::Error(msg) => {
-                eprintln!("{}", msg);
-            }
-            CommandResult::None => {}
         }
         return false;
     }
@@ -101,7 +143,12 @@ pub fn execute_command(input: &str) -> bool {
     false
 }
 
-fn cmd_help() -> CommandResult {
+/// Show the command table (`مساعدة` / help), or one command's manual page
+fn cmd_help(args: &[&str]) -> CommandResult {
+    if !args.is_empty() {
+        return cmd_manual(args);
+    }
+
     let mut help = String::new();
     help.push('\n');
     help.push_str("╔═══════════════════════════════════════════════════════════════════╗\n");
@@ -110,27 +157,11 @@ fn cmd_help() -> CommandResult {
     help.push_str("║                                                                   ║\n");
     help.push_str(&format!("║  {}:                               ║\n", shape_arabic("الأوامر الأساسية (Basic Commands)")));
     help.push_str("║  ─────────────────────────────────                                ║\n");
-    help.push_str(&format!("║  {}        │ help     │ {}                      ║\n", shape_arabic("مساعدة"), shape_arabic("عرض هذه المساعدة")));
-    help.push_str(&format!("║  {}          │ exit     │ {}                      ║\n", shape_arabic("خروج"), shape_arabic("الخروج من الصدفة")));
-    help.push_str(&format!("║  {}          │ clear    │ {}                            ║\n", shape_arabic("امسح"), shape_arabic("مسح الشاشة")));
-    help.push_str(&format!("║  {}         │ version  │ {}                           ║\n", shape_arabic("اصدار"), shape_arabic("عرض الإصدار")));
+    push_help_rows(&mut help, Section::Basic);
     help.push_str("║                                                                   ║\n");
     help.push_str(&format!("║  {}:                                   ║\n", shape_arabic("أوامر الملفات (File Commands)")));
     help.push_str("║  ─────────────────────────────                                    ║\n");
-    help.push_str(&format!("║  {} <>      │ echo     │ {}                              ║\n", shape_arabic("اطبع"), shape_arabic("طباعة نص")));
-    help.push_str(&format!("║  {}           │ pwd      │ {}                         ║\n", shape_arabic("اين"), shape_arabic("المسار الحالي")));
-    help.push_str(&format!("║  {} <>   │ cd       │ {}                      ║\n", shape_arabic("انتقل"), shape_arabic("الانتقال إلى مجلد")));
-    help.push_str(&format!("║  {} []   │ ls       │ {}                           ║\n", shape_arabic("اعرض"), shape_arabic("عرض الملفات")));
-    help.push_str(&format!("║  {} <>    │ cat      │ {}                       ║\n", shape_arabic("اقرأ"), shape_arabic("قراءة محتوى ملف")));
-    help.push_str(&format!("║  {} <>   │ mkdir    │ {}                            ║\n", shape_arabic("انشئ"), shape_arabic("إنشاء مجلد")));
-    help.push_str(&format!("║  {} <>    │ touch    │ {}                        ║\n", shape_arabic("المس"), shape_arabic("إنشاء ملف فارغ")));
-    help.push_str(&format!("║  {} <>    │ rm       │ {}                               ║\n", shape_arabic("احذف"), shape_arabic("حذف ملف")));
-    help.push_str(&format!("║  {} <> <> │ cp       │ {}                               ║\n", shape_arabic("انسخ"), shape_arabic("نسخ ملف")));
-    help.push_str(&format!("║  {} <> <> │ mv       │ {}                               ║\n", shape_arabic("انقل"), shape_arabic("نقل ملف")));
-    help.push_str(&format!("║  {} <>     │ grep     │ {}                         ║\n", shape_arabic("ابحث"), shape_arabic("البحث في النص")));
-    help.push_str(&format!("║  {}       │ chmod    │ {}                   ║\n", shape_arabic("صلاحيات"), shape_arabic("تغيير صلاحيات الملف")));
-    help.push_str(&format!("║  {} <>     │ chown    │ {}                        ║\n", shape_arabic("مالك"), shape_arabic("تغيير مالك الملف")));
-    help.push_str(&format!("║  {} <>      │ ln       │ {}                            ║\n", shape_arabic("رابط"), shape_arabic("إنشاء رابط")));
+    push_help_rows(&mut help, Section::Files);
     help.push_str("║                                                                   ║\n");
     help.push_str(&format!("║  {}:                                             ║\n", shape_arabic("العوامل (Operators)")));
     help.push_str("║  ─────────────────                                                ║\n");
@@ -148,13 +179,244 @@ fn cmd_help() -> CommandResult {
     CommandResult::None
 }
 
+/// Append one aligned help line per builtin in `section`
+///
+/// Columns are padded by character count of the unshaped text, which
+/// matches how the terminal lays out the shaped Arabic.
+fn push_help_rows(help: &mut String, section: Section) {
+    for spec in BUILTINS.iter().filter(|spec| spec.section == section) {
+        let (name, name_width) = if spec.args.is_empty() {
+            (shape_arabic(spec.arabic), spec.arabic.chars().count())
+        } else {
+            (
+                format!("{} {}", shape_arabic(spec.arabic), spec.args),
+                spec.arabic.chars().count() + 1 + spec.args.chars().count(),
+            )
+        };
+        help.push_str(&format!(
+            "║  {}{}│ {:<8} │ {}{}║\n",
+            name,
+            " ".repeat(14usize.saturating_sub(name_width).max(1)),
+            spec.english,
+            shape_arabic(spec.summary),
+            " ".repeat(38usize.saturating_sub(spec.summary.chars().count()).max(1)),
+        ));
+    }
+}
+
 fn cmd_version() -> CommandResult {
-    let version = format!(
+    CommandResult::Success(version_text())
+}
+
+/// Version banner shared by `اصدار` and `ocean --version`
+pub fn version_text() -> String {
+    format!(
         "{}\n{}\nhttps://github.com/osama1998H/ocean\n",
         shape_arabic(&format!("محيط (Ocean) v{}", env!("CARGO_PKG_VERSION"))),
         shape_arabic("مشروع ترقيم - Tarqeem Project")
+    )
+}
+
+fn cmd_sleep(args: &[&str]) -> CommandResult {
+    let usage = |problem: &str, problem_en: &str| -> CommandResult {
+        CommandError::usage((problem, problem_en), ("نم <ثوان>", "sleep <seconds>")).into()
+    };
+
+    let arg = match args {
+        [arg] => *arg,
+        _ => return usage("يرجى تحديد المدة", "Please specify a duration"),
+    };
+
+    // Accepts fractions and Arabic-Indic digits: `نم ٢٫٥`
+    match normalize_digits(arg).parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => {
+            std::thread::sleep(std::time::Duration::from_secs_f64(seconds));
+            CommandResult::None
+        }
+        _ => usage(
+            &format!("مدة غير صالحة '{}'", arg),
+            &format!("Invalid duration '{}'", arg),
+        ),
+    }
+}
+
+/// Lines `كرر_نص` prints without `-n` (output is buffered, so never infinite)
+const YES_DEFAULT_COUNT: usize = 10;
+
+/// Upper bound for `كرر_نص -n`, to keep the buffered output reasonable
+const YES_MAX_COUNT: usize = 1_000_000;
+
+fn cmd_yes(args: &[&str]) -> CommandResult {
+    let (count, words) = match args {
+        ["-n", count, rest @ ..] => match normalize_digits(count).parse::<usize>() {
+            Ok(n) if n <= YES_MAX_COUNT => (n, rest),
+            Ok(_) => {
+                return CommandError::failed((
+                    format!("العدد أكبر من الحد الأقصى {}", YES_MAX_COUNT),
+                    format!("Count exceeds the maximum of {}", YES_MAX_COUNT),
+                )).into();
+            }
+            Err(_) => {
+                return CommandError::usage(
+                    (format!("عدد غير صالح '{}'", count), format!("Invalid count '{}'", count)),
+                    ("كرر_نص [-n عدد] [نص]", "yes [-n COUNT] [TEXT]"),
+                ).into();
+            }
+        },
+        ["-n"] => {
+            return CommandError::usage(
+                ("الخيار -n يتطلب عدداً", "-n requires a count"),
+                ("كرر_نص [-n عدد] [نص]", "yes [-n COUNT] [TEXT]"),
+            ).into();
+        }
+        _ => (YES_DEFAULT_COUNT, args),
+    };
+
+    let line = if words.is_empty() { "y".to_string() } else { words.join(" ") };
+    let mut output = String::with_capacity((line.len() + 1) * count);
+    for _ in 0..count {
+        output.push_str(&line);
+        output.push('\n');
+    }
+    CommandResult::Success(output)
+}
+
+/// Upper bound for the number of lines `تسلسل` prints
+const SEQ_MAX_COUNT: usize = 1_000_000;
+
+/// Print a sequence of numbers (`تسلسل` / seq)
+///
+/// `تسلسل [FIRST] [STEP] LAST`, with FIRST and STEP defaulting to 1. A
+/// negative STEP counts down. Numbers may be fractions or use Arabic-Indic
+/// digits; output uses as many decimals as the most precise argument.
+fn cmd_seq(args: &[&str]) -> CommandResult {
+    let usage = |problem: &str, problem_en: &str| -> CommandResult {
+        CommandError::usage((problem, problem_en), ("تسلسل [بداية] [خطوة] نهاية", "seq [FIRST] [STEP] LAST")).into()
+    };
+
+    let mut numbers = Vec::with_capacity(args.len());
+    let mut decimals = 0;
+    for arg in args {
+        let normalized = normalize_digits(arg);
+        match normalized.parse::<f64>() {
+            Ok(n) if n.is_finite() => numbers.push(n),
+            _ => return usage(&format!("رقم غير صالح '{}'", arg), &format!("Invalid number '{}'", arg)),
+        }
+        if let Some((_, fraction)) = normalized.split_once('.') {
+            decimals = decimals.max(fraction.len());
+        }
+    }
+
+    let (first, step, last) = match numbers[..] {
+        [last] => (1.0, 1.0, last),
+        [first, last] => (first, 1.0, last),
+        [first, step, last] => (first, step, last),
+        _ => return usage("يرجى تحديد نهاية التسلسل", "Please specify where the sequence ends"),
+    };
+    if step == 0.0 {
+        return usage("الخطوة لا يمكن أن تكون صفراً", "The step cannot be zero");
+    }
+
+    // Counting from the start avoids drift from adding fractional steps;
+    // the epsilon keeps `0.1 0.1 0.3` from stopping one short
+    let span = (last - first) / step;
+    if span < 0.0 {
+        return CommandResult::None;
+    }
+    let count = (span + 1e-9).floor() + 1.0;
+    if count > SEQ_MAX_COUNT as f64 {
+        return CommandError::failed((
+            format!("التسلسل أطول من الحد الأقصى {}", SEQ_MAX_COUNT),
+            format!("The sequence exceeds the maximum of {} numbers", SEQ_MAX_COUNT),
+        )).into();
+    }
+
+    let mut output = String::new();
+    for i in 0..count as usize {
+        output.push_str(&format!("{:.*}\n", decimals, first + i as f64 * step));
+    }
+    CommandResult::Success(output)
+}
+
+fn cmd_theme(args: &[&str]) -> CommandResult {
+    if args.is_empty() {
+        // List available themes, marking the active one
+        let active = active_theme();
+        let mut output = String::new();
+        for theme in THEMES {
+            let marker = if *theme == active { "*" } else { " " };
+            output.push_str(&format!("{} {} ({})\n", marker, theme.arabic_name, theme.name));
+        }
+        return CommandResult::Success(output);
+    }
+
+    match theme_by_name(args[0]) {
+        Some(theme) => {
+            set_theme(theme);
+            CommandResult::None
+        }
+        None => CommandError::usage(
+            (format!("سمة غير معروفة '{}'", args[0]), format!("Unknown theme '{}'", args[0])),
+            ("سمة [داكن|فاتح]", "theme [dark|light]"),
+        ).into(),
+    }
+}
+
+/// Preview the active theme's colors (`الوان` / colors)
+///
+/// Prints one labeled swatch per themed style, after a line saying whether
+/// color is enabled at all; with color off the swatches print plain.
+fn cmd_colors(_args: &[&str]) -> CommandResult {
+    use colored::Colorize;
+
+    let theme = active_theme();
+    let enabled = colored::control::SHOULD_COLORIZE.should_colorize();
+    let mut output = format!(
+        "السمة: {} / Theme: {}\nالألوان: {} / Colors: {}\n",
+        theme.arabic_name,
+        theme.name,
+        if enabled { "مفعلة" } else { "معطلة" },
+        if enabled { "on" } else { "off" },
     );
-    CommandResult::Success(version)
+
+    let swatches = [
+        ("مجلد", "directory", theme.directory),
+        ("ملف تنفيذي", "executable", theme.executable),
+        ("رابط رمزي", "symlink", theme.symlink),
+        ("للقراءة فقط", "read-only", theme.readonly),
+        ("اسم المحث", "prompt name", theme.prompt_name),
+        ("مسار المحث", "prompt path", theme.prompt_cwd),
+        ("خطأ", "error", theme.error),
+        ("نجاح", "success", theme.success),
+    ];
+    for (ar, en, color) in swatches {
+        output.push_str(&format!("  {}\n", format!("{} / {}", ar, en).color(color)));
+    }
+    CommandResult::Success(output)
+}
+
+/// List message languages or choose one (`لغة` / lang)
+fn cmd_lang(args: &[&str]) -> CommandResult {
+    if args.is_empty() {
+        let active = active_locale();
+        let mut output = String::new();
+        for locale in LOCALES {
+            let marker = if *locale == active { "*" } else { " " };
+            output.push_str(&format!("{} {} ({})\n", marker, locale.arabic_name(), locale.name()));
+        }
+        return CommandResult::Success(output);
+    }
+
+    match locale_by_name(args[0]) {
+        Some(locale) => {
+            set_locale(locale);
+            CommandResult::None
+        }
+        None => CommandError::usage(
+            (format!("لغة غير معروفة '{}'", args[0]), format!("Unknown language '{}'", args[0])),
+            ("لغة [عربي|انجليزي|كلاهما]", "lang [ar|en|both]"),
+        ).into(),
+    }
 }
 
 fn cmd_echo(args: &[&str], input: Option<&str>) -> CommandResult {
@@ -170,128 +432,386 @@ fn cmd_echo(args: &[&str], input: Option<&str>) -> CommandResult {
     CommandResult::Success(output)
 }
 
-fn cmd_clear() -> CommandResult {
-    // ANSI escape code to clear screen and move cursor to top
-    print!("\x1B[2J\x1B[1;1H");
-    std::io::stdout().flush().unwrap();
+/// Write text to stderr (`صدى_خطأ` / eprintln)
+///
+/// Like `اطبع`, but the text bypasses stdout, so `>` redirection and pipes
+/// don't capture it.
+fn cmd_eprintln(args: &[&str], input: Option<&str>, options: &BuiltinOptions) -> CommandResult {
+    let text = if args.is_empty() {
+        input.unwrap_or("").trim_end_matches('\n').to_string()
+    } else {
+        args.join(" ")
+    };
+    options.print_error(&text);
     CommandResult::None
 }
 
-fn cmd_pwd() -> CommandResult {
-    match env::current_dir() {
-        Ok(path) => CommandResult::Success(format!("{}\n", path.display())),
-        Err(e) => CommandResult::Error(format!(
-            "خطأ: لا يمكن قراءة المسار الحالي - {} / Error: Cannot read current path - {}",
-            e, e
-        )),
+/// ANSI escape code to clear the screen and move the cursor to the top
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[1;1H";
+
+/// ANSI escape code to erase the terminal's scrollback buffer
+const CLEAR_SCROLLBACK: &str = "\x1B[3J";
+
+/// Clear the screen (`امسح` / clear); `-ك` (`-a`) also erases scrollback
+fn cmd_clear(args: &[&str]) -> CommandResult {
+    match clear_sequence(args) {
+        Some(sequence) => {
+            print!("{}", sequence);
+            std::io::stdout().flush().unwrap();
+            CommandResult::None
+        }
+        None => CommandError::usage(
+            ("خيار غير صالح", "Invalid option"),
+            ("امسح [-ك]", "clear [-a]"),
+        ).into(),
+    }
+}
+
+/// The escape codes `امسح` prints for `args`, or `None` for a bad option
+fn clear_sequence(args: &[&str]) -> Option<String> {
+    match args {
+        [] => Some(CLEAR_SCREEN.to_string()),
+        ["-ك" | "-a"] => Some(format!("{}{}", CLEAR_SCREEN, CLEAR_SCROLLBACK)),
+        _ => None,
     }
 }
 
-fn cmd_cd(args: &[&str]) -> CommandResult {
-    let path = if args.is_empty() {
-        match dirs::home_dir() {
-            Some(home) => home,
-            None => {
-                return CommandResult::Error(
-                    "خطأ: لا يمكن إيجاد مجلد المنزل / Error: Cannot find home directory".to_string()
-                );
-            }
+/// Order for `اعرض` entries
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LsOrder {
+    /// Directories first, then files, each by name (the default)
+    Name,
+    /// `-t`: most recently modified first
+    Time,
+    /// `-S`: largest first
+    Size,
+}
+
+/// List directories (`اعرض` / ls)
+///
+/// Operands that are files are listed first, by the name given; each
+/// directory operand is then listed under a `dir:` header when there is
+/// more than one operand. With `--json` everything goes into one array and
+/// directory entries are named `dir/entry` instead.
+///
+/// `-R` also lists every subdirectory, each under its own `dir/sub:` header,
+/// in the order [`walk_tree`](filesystem::walk_tree) visits them; unreadable
+/// ones are skipped with a warning.
+fn cmd_ls(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    // --json: machine-readable output without colors
+    let json = args.contains(&"--json");
+    // -t/-S pick the ordering (last one wins), -r reverses it
+    let mut order = LsOrder::Name;
+    let mut reverse = false;
+    let mut recursive = false;
+    let mut long = false;
+    let mut human = false;
+    let mut rest = Vec::new();
+    for arg in args {
+        match *arg {
+            "--json" => {}
+            "-l" => long = true,
+            "-h" => human = true,
+            "-t" => order = LsOrder::Time,
+            "-S" => order = LsOrder::Size,
+            "-r" => reverse = true,
+            "-R" => recursive = true,
+            other => rest.push(other),
         }
+    }
+    // -L reports what symlinks point to; -P (default) reports the links themselves
+    let (links, paths) = symlink_mode(&rest);
+    let entry_metadata = |entry: &fs::DirEntry| match links {
+        SymlinkMode::Follow => fs::metadata(entry.path()).or_else(|_| entry.metadata()),
+        SymlinkMode::Physical => entry.metadata(),
+    };
+
+    let operands: Vec<(String, PathBuf)> = if paths.is_empty() {
+        let cwd = if options.cwd.as_os_str().is_empty() {
+            env::current_dir().unwrap_or_else(|_| Path::new(".").to_path_buf())
+        } else {
+            options.cwd.to_path_buf()
+        };
+        // Recursive headers are named below `.`, like `./sub:`
+        let name = if recursive { ".".to_string() } else { cwd.display().to_string() };
+        vec![(name, cwd)]
     } else {
-        expand_tilde(args[0])
+        paths.iter().map(|path| (path.to_string(), options.path(path))).collect()
     };
 
-    match env::set_current_dir(&path) {
-        Ok(_) => CommandResult::None,
-        Err(e) => CommandResult::Error(format!(
-            "خطأ: لا يمكن الانتقال إلى '{}' - {} / Error: Cannot change to '{}' - {}",
-            path.display(), e, path.display(), e
-        )),
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    for (name, path) in operands {
+        if path.is_dir() {
+            dirs.push((name, path));
+            continue;
+        }
+        let meta = match links {
+            SymlinkMode::Follow => fs::metadata(&path),
+            SymlinkMode::Physical => fs::symlink_metadata(&path),
+        };
+        match meta {
+            Ok(meta) => files.push((name, Some(meta))),
+            Err(e) => {
+                return CommandError::file(FileOp::Access, path.display(), e).into();
+            }
+        }
+    }
+
+    // -R: every directory beneath each operand gets its own group
+    if recursive {
+        dirs = dirs
+            .into_iter()
+            .flat_map(|(name, root)| {
+                filesystem::walk_tree(&root, links, options)
+                    .into_iter()
+                    .filter(|dir| dir.is_dir())
+                    .map(|dir| match dir.strip_prefix(&root) {
+                        Ok(sub) if sub.as_os_str().is_empty() => (name.clone(), dir),
+                        Ok(sub) => (format!("{}/{}", name.trim_end_matches('/'), sub.display()), dir),
+                        Err(_) => (dir.display().to_string(), dir),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+
+    let with_headers = recursive || files.len() + dirs.len() > 1;
+    let mut groups = Vec::new();
+    if !files.is_empty() {
+        groups.push((None, files));
+    }
+    for (name, path) in dirs {
+        let entries = match fs::read_dir(&path) {
+            Ok(entries) => entries,
+            // The walk already warned about it
+            Err(_) if recursive => continue,
+            Err(e) => {
+                return CommandError::file(FileOp::ReadDir, path.display(), e).into();
+            }
+        };
+        let prefix = if json && with_headers { format!("{}/", name.trim_end_matches('/')) } else { String::new() };
+        let items = entries
+            .flatten()
+            .map(|entry| {
+                let entry_name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+                (entry_name, entry_metadata(&entry).ok())
+            })
+            .collect();
+        groups.push((with_headers.then_some(name), items));
+    }
+
+    for (_, items) in &mut groups {
+        sort_ls_items(items, order);
+        if reverse {
+            items.reverse();
+        }
+    }
+
+    if json {
+        let items: Vec<_> = groups.into_iter().flat_map(|(_, items)| items).collect();
+        return CommandResult::Success(ls_json(&items));
     }
+
+    let sections: Vec<String> = groups
+        .into_iter()
+        .map(|(header, items)| {
+            let listing = format_ls_items(items, long, human);
+            match header {
+                Some(header) => format!("{}:\n{}", header, listing),
+                None => listing,
+            }
+        })
+        .collect();
+    CommandResult::Success(sections.join("\n"))
 }
 
-fn cmd_ls(args: &[&str]) -> CommandResult {
-    use colored::Colorize;
+/// One colored line per `اعرض` entry; with `-l` (`long`) each name follows
+/// its mode, size (`human`-readable with `-h`) and modification time
+fn format_ls_items(items: Vec<(String, Option<fs::Metadata>)>, long: bool, human: bool) -> String {
+    let size = |meta: &Option<fs::Metadata>| meta.as_ref().map_or("?".to_string(), |meta| format_size(meta.len(), human));
+    let size_width = items.iter().map(|(_, meta)| size(meta).len()).max().unwrap_or(0);
+
+    let formatted: Vec<String> = items
+        .into_iter()
+        .map(|(name, meta)| {
+            let colored = colorize_ls_name(name, meta.as_ref());
+            if !long {
+                return colored;
+            }
+            let (mode, modified) = match &meta {
+                Some(meta) => (
+                    ls_mode(meta),
+                    meta.modified().map(format_timestamp).unwrap_or_else(|_| "-".to_string()),
+                ),
+                None => ("?".repeat(10), "-".to_string()),
+            };
+            format!("{} {:>width$} {:<19} {}", mode, size(&meta), modified, colored, width = size_width)
+        })
+        .collect();
 
-    let path = if args.is_empty() {
-        env::current_dir().unwrap_or_else(|_| Path::new(".").to_path_buf())
-    } else {
-        expand_tilde(args[0])
-    };
+    formatted.join("\n") + "\n"
+}
 
-    match fs::read_dir(&path) {
-        Ok(entries) => {
-            let mut items: Vec<String> = Vec::new();
-            for entry in entries.flatten() {
-                let name = entry.file_name().to_string_lossy().to_string();
-                let metadata = entry.metadata();
-
-                let formatted = if let Ok(meta) = metadata {
-                    let is_dir = meta.is_dir();
-                    let is_symlink = meta.file_type().is_symlink();
-
-                    #[cfg(unix)]
-                    let is_exec = {
-                        use std::os::unix::fs::PermissionsExt;
-                        meta.permissions().mode() & 0o111 != 0
-                    };
-                    #[cfg(not(unix))]
-                    let is_exec = false;
-
-                    if is_symlink {
-                        // Symlinks in magenta
-                        name.magenta().to_string()
-                    } else if is_dir {
-                        // Directories in bold blue with trailing /
-                        format!("{}/", name.blue().bold())
-                    } else if is_exec {
-                        // Executable files in bold green
-                        name.green().bold().to_string()
-                    } else if meta.permissions().readonly() {
-                        // Read-only files in red
-                        name.red().to_string()
-                    } else {
-                        name
-                    }
+/// An `اعرض` entry's name in its theme color
+fn colorize_ls_name(name: String, meta: Option<&fs::Metadata>) -> String {
+    match meta {
+        Some(meta) => {
+            let is_dir = meta.is_dir();
+            let is_symlink = meta.file_type().is_symlink();
+
+            #[cfg(unix)]
+            let is_exec = {
+                use std::os::unix::fs::PermissionsExt;
+                meta.permissions().mode() & 0o111 != 0
+            };
+            #[cfg(not(unix))]
+            let is_exec = false;
+
+            // Colors come from the active theme
+            colorize_entry_full(&name, is_dir, is_exec, is_symlink, meta.permissions().readonly())
+        }
+        None => name,
+    }
+}
+
+/// The `-rw-r--r--` column of `اعرض -l`
+fn ls_mode(meta: &fs::Metadata) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        crate::utils::format_mode(meta.mode())
+    }
+    #[cfg(not(unix))]
+    {
+        let kind = if meta.is_dir() { 'd' } else { '-' };
+        format!("{}{}", kind, if meta.permissions().readonly() { "r--r--r--" } else { "rw-rw-rw-" })
+    }
+}
+
+/// Sort `اعرض` entries; ties (and entries without metadata) fall back to name
+fn sort_ls_items(items: &mut [(String, Option<fs::Metadata>)], order: LsOrder) {
+    items.sort_by(|(a_name, a), (b_name, b)| {
+        let by_key = match order {
+            LsOrder::Name => {
+                let is_dir = |m: &Option<fs::Metadata>| m.as_ref().is_some_and(|m| m.is_dir());
+                is_dir(b).cmp(&is_dir(a))
+            }
+            LsOrder::Time => {
+                let modified = |m: &Option<fs::Metadata>| m.as_ref().and_then(|m| m.modified().ok());
+                modified(b).cmp(&modified(a))
+            }
+            LsOrder::Size => {
+                let size = |m: &Option<fs::Metadata>| m.as_ref().map(|m| m.len());
+                size(b).cmp(&size(a))
+            }
+        };
+        by_key.then_with(|| a_name.cmp(b_name))
+    });
+}
+
+/// Render directory entries as a JSON array for `اعرض --json`
+///
+/// Each object has `name`, `type` (`file`, `directory`, `symlink`), `size`
+/// in bytes, and `permissions` (octal on Unix, `readonly`/`readwrite`
+/// elsewhere). Entries whose metadata can't be read get `null` fields.
+fn ls_json(items: &[(String, Option<fs::Metadata>)]) -> String {
+    let objects: Vec<String> = items
+        .iter()
+        .map(|(name, meta)| match meta {
+            Some(meta) => {
+                let kind = if meta.file_type().is_symlink() {
+                    "symlink"
+                } else if meta.is_dir() {
+                    "directory"
                 } else {
-                    name
+                    "file"
                 };
-                items.push(formatted);
+
+                #[cfg(unix)]
+                let permissions = {
+                    use std::os::unix::fs::PermissionsExt;
+                    format!("{:o}", meta.permissions().mode() & 0o7777)
+                };
+                #[cfg(not(unix))]
+                let permissions = if meta.permissions().readonly() { "readonly" } else { "readwrite" }.to_string();
+
+                format!(
+                    "{{\"name\":{},\"type\":\"{}\",\"size\":{},\"permissions\":\"{}\"}}",
+                    json_string(name), kind, meta.len(), permissions
+                )
             }
+            None => format!(
+                "{{\"name\":{},\"type\":null,\"size\":null,\"permissions\":null}}",
+                json_string(name)
+            ),
+        })
+        .collect();
+    format!("[{}]\n", objects.join(","))
+}
 
-            items.sort();
-            let output = items.join("\n") + "\n";
-            CommandResult::Success(output)
+/// Quote and escape a string as a JSON string literal
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
         }
-        Err(e) => CommandResult::Error(format!(
-            "خطأ: لا يمكن قراءة المجلد '{}' - {} / Error: Cannot read directory '{}' - {}",
-            path.display(), e, path.display(), e
-        )),
     }
+    out.push('"');
+    out
 }
 
-fn cmd_cat(args: &[&str], input: Option<&str>) -> CommandResult {
+fn cmd_cat(args: &[&str], input: Option<&str>, options: &BuiltinOptions) -> CommandResult {
+    // -b: show binary files (NUL bytes) instead of refusing them
+    let show_binary = args.iter().any(|a| *a == "-b" || *a == "--binary");
+    let files: Vec<&str> = args
+        .iter()
+        .copied()
+        .filter(|a| *a != "-b" && *a != "--binary")
+        .collect();
+
     if let Some(inp) = input {
-        if args.is_empty() {
+        if files.is_empty() {
             return CommandResult::Success(inp.to_string());
         }
     }
 
-    if args.is_empty() {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد ملف للقراءة\nالاستخدام: اقرأ <اسم_الملف>\nError: Please specify a file\nUsage: cat <filename>".to_string()
-        );
+    if files.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد ملف للقراءة", "Please specify a file"),
+            ("اقرأ [-b] <اسم_الملف>", "cat [-b] <filename>"),
+        ).into();
     }
 
     let mut output = String::new();
-    for file in args {
-        match fs::read_to_string(file) {
+    for file in files {
+        let bytes = match fs::read(options.path(file)) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return CommandError::file(FileOp::Read, file, e).into();
+            }
+        };
+
+        match String::from_utf8(bytes) {
             Ok(content) => output.push_str(&content),
             Err(e) => {
-                return CommandResult::Error(format!(
-                    "خطأ: لا يمكن قراءة '{}' - {} / Error: Cannot read '{}' - {}",
-                    file, e, file, e
-                ));
+                let bytes = e.into_bytes();
+                if bytes.contains(&0) && !show_binary {
+                    return CommandError::failed((
+                        format!("'{}' ملف ثنائي (استخدم -b لعرضه)", file),
+                        format!("'{}' is a binary file (use -b to show it)", file),
+                    )).into();
+                }
+                // Legacy encodings: keep the readable parts, replace bad bytes with U+FFFD
+                output.push_str(&String::from_utf8_lossy(&bytes));
             }
         }
     }
@@ -299,172 +819,661 @@ fn cmd_cat(args: &[&str], input: Option<&str>) -> CommandResult {
     CommandResult::Success(output)
 }
 
-fn cmd_mkdir(args: &[&str]) -> CommandResult {
-    if args.is_empty() {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد اسم المجلد\nالاستخدام: انشئ <اسم_المجلد>\nError: Please specify directory name\nUsage: mkdir <dirname>".to_string()
-        );
-    }
+/// Save piped input to files and pass it on (`حفظ` / tee)
+///
+/// `اقرأ a | ابحث x | حفظ out.txt | بدل a-z A-Z` keeps the matches in
+/// `out.txt` while they still reach `بدل`. `-a` appends instead of
+/// overwriting.
+fn cmd_tee(args: &[&str], input: Option<&str>, options: &BuiltinOptions) -> CommandResult {
+    let append = args.iter().any(|a| *a == "-a" || *a == "--append");
+    let files: Vec<&str> = args
+        .iter()
+        .copied()
+        .filter(|a| *a != "-a" && *a != "--append")
+        .collect();
+
+    let Some(input) = input else {
+        return CommandError::usage(
+            ("يرجى تمرير مدخلات بالأنبوب", "Please pipe input in"),
+            ("<أمر> | حفظ [-a] <ملف...>", "<command> | tee [-a] <file...>"),
+        ).into();
+    };
 
-    for dir in args {
-        if let Err(e) = fs::create_dir_all(dir) {
-            return CommandResult::Error(format!(
-                "خطأ: لا يمكن إنشاء '{}' - {} / Error: Cannot create '{}' - {}",
-                dir, e, dir, e
-            ));
+    for file in files {
+        let written = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(options.path(file))
+            .and_then(|mut f| f.write_all(input.as_bytes()));
+        if let Err(e) = written {
+            return CommandError::file(FileOp::Write, file, e).into();
         }
     }
 
-    CommandResult::None
+    CommandResult::Success(input.to_string())
 }
 
-fn cmd_touch(args: &[&str]) -> CommandResult {
-    if args.is_empty() {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد اسم الملف\nالاستخدام: المس <اسم_الملف>\nError: Please specify filename\nUsage: touch <filename>".to_string()
-        );
+/// `-v` reporting shared by the file commands
+///
+/// Collects one line per affected path, Arabic verb first so the line
+/// reads naturally right-to-left, e.g. `نُسخ / copied 'a' -> 'b'`.
+struct VerboseLog {
+    enabled: bool,
+    output: String,
+}
+
+impl VerboseLog {
+    /// Take `-v`/`-ت`/`--verbose` out of the arguments
+    fn from_args<'a>(args: &[&'a str]) -> (Self, Vec<&'a str>) {
+        let is_flag = |a: &str| matches!(a, "-v" | "-ت" | "--verbose");
+        let log = Self {
+            enabled: args.iter().any(|a| is_flag(a)),
+            output: String::new(),
+        };
+        (log, args.iter().copied().filter(|a| !is_flag(a)).collect())
     }
 
-    for file in args {
-        if let Err(e) = fs::OpenOptions::new().create(true).write(true).open(file) {
-            return CommandResult::Error(format!(
-                "خطأ: لا يمكن إنشاء '{}' - {} / Error: Cannot create '{}' - {}",
-                file, e, file, e
-            ));
+    fn record(&mut self, verb_ar: &str, verb_en: &str, detail: &str) {
+        if self.enabled {
+            self.output.push_str(&format!("{} / {} {}\n", verb_ar, verb_en, detail));
         }
     }
 
-    CommandResult::None
+    fn finish(self) -> CommandResult {
+        if self.output.is_empty() {
+            CommandResult::None
+        } else {
+            CommandResult::Success(self.output)
+        }
+    }
 }
 
-fn cmd_rm(args: &[&str]) -> CommandResult {
-    if args.is_empty() {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد ملف للحذف\nالاستخدام: احذف <اسم_الملف>\nError: Please specify file to delete\nUsage: rm <filename>".to_string()
-        );
+fn cmd_mkdir(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    let (mut log, args) = VerboseLog::from_args(args);
+    // -p: create parents and accept existing directories
+    let parents = args.iter().any(|a| *a == "-p" || *a == "--parents");
+    let dirs: Vec<&str> = args
+        .iter()
+        .copied()
+        .filter(|a| *a != "-p" && *a != "--parents")
+        .collect();
+
+    if dirs.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد اسم المجلد", "Please specify directory name"),
+            ("انشئ [-p] [-v] <اسم_المجلد>", "mkdir [-p] [-v] <dirname>"),
+        ).into();
     }
 
-    for file in args {
-        let path = Path::new(file);
-        let result = if path.is_dir() {
-            fs::remove_dir_all(path)
+    for dir in dirs {
+        let path = options.path(dir);
+        // With -p, every missing ancestor is created (and reported) too,
+        // named as typed (`~/a`, not the expanded home)
+        let mut created: Vec<PathBuf> = if parents {
+            let typed: Vec<&Path> = Path::new(dir).ancestors().filter(|p| !p.as_os_str().is_empty()).collect();
+            path.ancestors()
+                .take_while(|p| !p.as_os_str().is_empty() && !p.exists())
+                .enumerate()
+                .map(|(i, resolved)| typed.get(i).copied().unwrap_or(resolved).to_path_buf())
+                .collect()
         } else {
-            fs::remove_file(path)
+            vec![PathBuf::from(dir)]
+        };
+        created.reverse();
+
+        let result = if parents {
+            fs::create_dir_all(&path)
+        } else {
+            fs::create_dir(&path)
         };
 
         if let Err(e) = result {
-            return CommandResult::Error(format!(
-                "خطأ: لا يمكن حذف '{}' - {} / Error: Cannot delete '{}' - {}",
-                file, e, file, e
-            ));
+            if e.kind() == std::io::ErrorKind::AlreadyExists {
+                return CommandError::failed((
+                    format!("'{}' موجود مسبقاً", dir),
+                    format!("'{}' already exists", dir),
+                )).into();
+            }
+            return CommandError::file(FileOp::Create, dir, e).into();
+        }
+        for path in created {
+            log.record("أُنشئ", "created", &format!("'{}'", path.display()));
         }
     }
 
-    CommandResult::None
+    log.finish()
 }
 
-fn cmd_cp(args: &[&str]) -> CommandResult {
-    if args.len() < 2 {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد المصدر والوجهة\nالاستخدام: انسخ <مصدر> <وجهة>\nError: Please specify source and destination\nUsage: cp <source> <dest>".to_string()
-        );
+fn cmd_touch(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    if args.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد اسم الملف", "Please specify filename"),
+            ("المس <اسم_الملف>", "touch <filename>"),
+        ).into();
     }
 
-    let source = args[0];
-    let dest = args[1];
-
-    if let Err(e) = fs::copy(source, dest) {
-        return CommandResult::Error(format!(
-            "خطأ: لا يمكن نسخ '{}' إلى '{}' - {} / Error: Cannot copy '{}' to '{}' - {}",
-            source, dest, e, source, dest, e
-        ));
+    for file in args {
+        if let Err(e) = fs::OpenOptions::new().create(true).truncate(false).write(true).open(options.path(file)) {
+            return CommandError::file(FileOp::Create, file, e).into();
+        }
     }
 
     CommandResult::None
 }
 
-fn cmd_mv(args: &[&str]) -> CommandResult {
-    if args.len() < 2 {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد المصدر والوجهة\nالاستخدام: انقل <مصدر> <وجهة>\nError: Please specify source and destination\nUsage: mv <source> <dest>".to_string()
-        );
-    }
+/// Truncate files to zero length, or to `-s SIZE` bytes (`افرغ` / truncate)
+///
+/// Missing files are created; growing a file pads it with zero bytes. The
+/// size may use Arabic-Indic digits.
+fn cmd_truncate(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    let usage = |problem: &str, problem_en: &str| -> CommandResult {
+        CommandError::usage((problem, problem_en), ("افرغ [-s حجم] <ملف...>", "truncate [-s SIZE] <file...>")).into()
+    };
 
-    let source = args[0];
-    let dest = args[1];
+    let (size, files) = match args {
+        ["-s", size, files @ ..] => match normalize_digits(size).parse::<u64>() {
+            Ok(size) => (size, files),
+            Err(_) => return usage(&format!("حجم غير صالح '{}'", size), &format!("Invalid size '{}'", size)),
+        },
+        ["-s"] => return usage("الخيار -s يتطلب حجماً", "-s requires a size"),
+        _ => (0, args),
+    };
+    if files.is_empty() {
+        return usage("يرجى تحديد اسم الملف", "Please specify filename");
+    }
 
-    if let Err(e) = fs::rename(source, dest) {
-        return CommandResult::Error(format!(
-            "خطأ: لا يمكن نقل '{}' إلى '{}' - {} / Error: Cannot move '{}' to '{}' - {}",
-            source, dest, e, source, dest, e
-        ));
+    for file in files {
+        let truncated = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(options.path(file))
+            .and_then(|f| f.set_len(size));
+        if let Err(e) = truncated {
+            return CommandError::file(FileOp::Truncate, file, e).into();
+        }
     }
 
     CommandResult::None
 }
 
-fn cmd_search(args: &[&str], input: Option<&str>) -> CommandResult {
-    if args.is_empty() {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد نص للبحث\nالاستخدام: ابحث <نمط> [ملف...]\nError: Please specify search pattern\nUsage: grep <pattern> [file...]".to_string()
-        );
-    }
-
-    let pattern = args[0];
-
-    if let Some(inp) = input {
-        let matching_lines: Vec<&str> = inp
-            .lines()
-            .filter(|line| line.contains(pattern))
-            .collect();
+/// The line a dry run prints for a change it skipped, in the `-v` format
+fn dry_run_line(verb_ar: &str, verb_en: &str, detail: &str) -> String {
+    format!("{} / {} {}\n", verb_ar, verb_en, detail)
+}
 
-        if matching_lines.is_empty() {
-            return CommandResult::Success(String::new());
-        }
-        return CommandResult::Success(matching_lines.join("\n") + "\n");
+fn cmd_rm(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    let (mut log, args) = VerboseLog::from_args(args);
+    // --force-dangerous: allow deleting `/`, home, or the current directory
+    let force = args.contains(&"--force-dangerous");
+    let files: Vec<&str> = args
+        .iter()
+        .copied()
+        .filter(|a| *a != "--force-dangerous")
+        .collect();
+
+    if files.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد ملف للحذف", "Please specify file to delete"),
+            ("احذف [-v] <اسم_الملف>", "rm [-v] <filename>"),
+        ).into();
     }
 
-    if args.len() < 2 {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد ملف للبحث فيه أو استخدام الأنبوب\nError: Please specify a file to search or use pipe".to_string()
-        );
+    // Check every target before deleting anything
+    let paths: Vec<_> = files.iter().map(|file| options.path(file)).collect();
+    if !force {
+        for (file, path) in files.iter().zip(&paths) {
+            if let Some((ar, en)) = protected_target(path, options) {
+                return CommandError::failed((
+                    format!("رفض حذف '{}' لأنه {}! استخدم --force-dangerous إذا كنت متأكداً", file, ar),
+                    format!("Refusing to delete '{}' because it is {}! Pass --force-dangerous if you are sure", file, en),
+                )).into();
+            }
+        }
     }
 
-    let mut output = String::new();
-    for file in &args[1..] {
-        match fs::read_to_string(file) {
-            Ok(content) => {
-                for (i, line) in content.lines().enumerate() {
-                    if line.contains(pattern) {
-                        if args.len() > 2 {
-                            // Multiple files: show filename
-                            output.push_str(&format!("{}:{}:{}\n", file, i + 1, line));
-                        } else {
-                            output.push_str(&format!("{}:{}\n", i + 1, line));
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                return CommandResult::Error(format!(
-                    "خطأ: لا يمكن قراءة '{}' - {} / Error: Cannot read '{}' - {}",
-                    file, e, file, e
-                ));
+    if options.dry_run {
+        let mut output = String::new();
+        for (file, path) in files.iter().zip(&paths) {
+            if let Err(e) = fs::symlink_metadata(path) {
+                return CommandError::file(FileOp::Delete, file, e).into();
             }
+            output.push_str(&dry_run_line("سيُحذف", "would remove", &format!("'{}'", file)));
         }
+        return CommandResult::Success(output);
     }
 
+    for (file, path) in files.iter().zip(&paths) {
+        let result = if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        };
+
+        if let Err(e) = result {
+            return CommandError::file(FileOp::Delete, file, e).into();
+        }
+        log.record("حُذف", "removed", &format!("'{}'", file));
+    }
+
+    log.finish()
+}
+
+/// Whether deleting `path` would be catastrophic: the filesystem root, the
+/// home directory, or the current directory
+///
+/// Returns the (Arabic, English) reason. Paths are compared canonically, so
+/// `/tmp/..` and `~/` are caught too.
+fn protected_target(path: &Path, options: &BuiltinOptions) -> Option<(&'static str, &'static str)> {
+    let canonical = fs::canonicalize(path).ok()?;
+    let same = |other: Option<std::path::PathBuf>| {
+        other.and_then(|p| fs::canonicalize(p).ok()).is_some_and(|p| p == canonical)
+    };
+
+    if canonical.parent().is_none() {
+        Some(("جذر نظام الملفات", "the filesystem root"))
+    } else if same(dirs::home_dir()) {
+        Some(("المجلد الرئيسي", "the home directory"))
+    } else if same(Some(options.path("."))) {
+        Some(("المجلد الحالي", "the current directory"))
+    } else {
+        None
+    }
+}
+
+fn cmd_cp(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    let (mut log, args) = VerboseLog::from_args(args);
+    let (mode, args) = overwrite_mode(&args);
+    // -p: keep the source's mode and access/modify times
+    let preserve = args.iter().any(|a| matches!(*a, "-p" | "-ح" | "--preserve"));
+    let args: Vec<&str> = args.into_iter().filter(|a| !matches!(*a, "-p" | "-ح" | "--preserve")).collect();
+    // --progress: copy in chunks, reporting a percentage on stderr
+    let progress = args.iter().any(|a| matches!(*a, "--progress" | "--تقدم"));
+    let args: Vec<&str> = args.into_iter().filter(|a| !matches!(*a, "--progress" | "--تقدم")).collect();
+    if args.len() < 2 {
+        return CommandError::usage(
+            ("يرجى تحديد المصدر والوجهة", "Please specify source and destination"),
+            ("انسخ [-b|-n] [-p] [-v] [--تقدم] <مصدر> <وجهة>", "cp [-b|-n] [-p] [-v] [--progress] <source> <dest>"),
+        ).into();
+    }
+
+    let source = args[0];
+    let dest = args[1];
+
+    if options.dry_run {
+        return describe_transfer(("سيُنسخ", "would copy"), source, dest, mode, options);
+    }
+
+    if let Err(err) = prepare_destination(dest, mode, options) {
+        return err;
+    }
+    let (source_path, dest_path) = (options.path(source), options.path(dest));
+
+    // A job's buffer can't be rewritten in place, so it gets no progress
+    let copied = if progress && options.stderr.is_none() {
+        let mut shown = None;
+        copy_with_progress(&source_path, &dest_path, |copied, total| {
+            // Rewritten in place on stderr, only when the percentage changes
+            let percent = (copied.min(total) * 100).checked_div(total).unwrap_or(100);
+            if shown != Some(percent) {
+                shown = Some(percent);
+                eprint!("\r{}%", percent);
+            }
+            if copied >= total {
+                eprintln!();
+            }
+        })
+    } else {
+        fs::copy(&source_path, &dest_path)
+    };
+    if let Err(e) = copied {
+        return CommandError::failed((
+            format!("لا يمكن نسخ '{}' إلى '{}' - {}", source, dest, e),
+            format!("Cannot copy '{}' to '{}' - {}", source, dest, e),
+        )).into();
+    }
+
+    if preserve {
+        if let Err(e) = preserve_metadata(&source_path, &dest_path) {
+            return CommandError::file(FileOp::PreserveAttributes, source, e).into();
+        }
+    }
+
+    log.record("نُسخ", "copied", &format!("'{}' -> '{}'", source, dest));
+    log.finish()
+}
+
+/// Bytes read and written per step of `copy_with_progress`
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Copy `source` to `dest` in chunks, calling `report(copied, total)` after
+/// each one
+///
+/// `total` is the source size from its metadata. Like `fs::copy`, the copy
+/// gets the source's permission bits.
+fn copy_with_progress(source: &Path, dest: &Path, mut report: impl FnMut(u64, u64)) -> std::io::Result<u64> {
+    use std::io::Read;
+
+    let mut reader = fs::File::open(source)?;
+    let meta = reader.metadata()?;
+    let total = meta.len();
+    let mut writer = fs::File::create(dest)?;
+    let mut buffer = vec![0; COPY_CHUNK_SIZE];
+    let mut copied = 0;
+    report(copied, total);
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        copied += read as u64;
+        report(copied, total);
+    }
+    fs::set_permissions(dest, meta.permissions())?;
+    Ok(copied)
+}
+
+/// Copy the permission bits and access/modify times of `source` onto `dest`
+///
+/// Times are set first, while `dest` still has the permissions `fs::copy`
+/// gave it.
+fn preserve_metadata(source: &Path, dest: &Path) -> std::io::Result<()> {
+    let meta = fs::metadata(source)?;
+    let times = fs::FileTimes::new()
+        .set_accessed(meta.accessed()?)
+        .set_modified(meta.modified()?);
+    fs::File::open(dest)?.set_times(times)?;
+    fs::set_permissions(dest, meta.permissions())
+}
+
+fn cmd_mv(args: &[&str], options: &BuiltinOptions) -> CommandResult {
+    let (mut log, args) = VerboseLog::from_args(args);
+    let (mode, args) = overwrite_mode(&args);
+    if args.len() < 2 {
+        return CommandError::usage(
+            ("يرجى تحديد المصدر والوجهة", "Please specify source and destination"),
+            ("انقل [-b|-n] [-v] <مصدر> <وجهة>", "mv [-b|-n] [-v] <source> <dest>"),
+        ).into();
+    }
+
+    let source = args[0];
+    let dest = args[1];
+
+    if options.dry_run {
+        return describe_transfer(("سيُنقل", "would move"), source, dest, mode, options);
+    }
+
+    if let Err(err) = prepare_destination(dest, mode, options) {
+        return err;
+    }
+
+    if let Err(e) = fs::rename(options.path(source), options.path(dest)) {
+        return CommandError::failed((
+            format!("لا يمكن نقل '{}' إلى '{}' - {}", source, dest, e),
+            format!("Cannot move '{}' to '{}' - {}", source, dest, e),
+        )).into();
+    }
+
+    log.record("نُقل", "moved", &format!("'{}' -> '{}'", source, dest));
+    log.finish()
+}
+
+/// What `انسخ`/`انقل` do with an existing destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overwrite {
+    /// Replace it (default)
+    Replace,
+    /// Rename it to `dest~` first (`-b`)
+    Backup,
+    /// Refuse to touch it (`-n`, wins over `-b`)
+    NoClobber,
+}
+
+/// Split the `-b`/`--backup` and `-n`/`--no-clobber` flags from the arguments
+fn overwrite_mode<'a>(args: &[&'a str]) -> (Overwrite, Vec<&'a str>) {
+    let has = |short: &str, long: &str| args.iter().any(|a| *a == short || *a == long);
+    let mode = if has("-n", "--no-clobber") {
+        Overwrite::NoClobber
+    } else if has("-b", "--backup") {
+        Overwrite::Backup
+    } else {
+        Overwrite::Replace
+    };
+    let rest = args
+        .iter()
+        .copied()
+        .filter(|a| !matches!(*a, "-n" | "--no-clobber" | "-b" | "--backup"))
+        .collect();
+    (mode, rest)
+}
+
+/// What `انسخ`/`انقل` would do in a dry run, including a backup of the
+/// destination; a missing source or `-n` on an existing destination still
+/// fails as the real command would
+fn describe_transfer(
+    verb: (&str, &str),
+    source: &str,
+    dest: &str,
+    mode: Overwrite,
+    options: &BuiltinOptions,
+) -> CommandResult {
+    if let Err(e) = fs::symlink_metadata(options.path(source)) {
+        return CommandError::file(FileOp::Access, source, e).into();
+    }
+    let mut output = String::new();
+    if fs::symlink_metadata(options.path(dest)).is_ok() {
+        match mode {
+            Overwrite::Replace => {}
+            Overwrite::NoClobber => {
+                return prepare_destination(dest, mode, options).err().unwrap_or(CommandResult::None);
+            }
+            Overwrite::Backup => {
+                let detail = format!("'{}' -> '{}~'", dest, dest);
+                output.push_str(&dry_run_line("سيُحفظ احتياطياً", "would back up", &detail));
+            }
+        }
+    }
+    output.push_str(&dry_run_line(verb.0, verb.1, &format!("'{}' -> '{}'", source, dest)));
     CommandResult::Success(output)
 }
 
+/// Back up or protect an existing destination before it is overwritten
+fn prepare_destination(dest: &str, mode: Overwrite, options: &BuiltinOptions) -> Result<(), CommandResult> {
+    if fs::symlink_metadata(options.path(dest)).is_err() {
+        return Ok(());
+    }
+
+    match mode {
+        Overwrite::Replace => Ok(()),
+        Overwrite::NoClobber => Err(CommandError::failed((
+            format!("'{}' موجود بالفعل (-n يمنع الكتابة فوقه)", dest),
+            format!("'{}' already exists (-n prevents overwriting)", dest),
+        )).into()),
+        Overwrite::Backup => {
+            let backup = format!("{}~", dest);
+            fs::rename(options.path(dest), options.path(&backup)).map_err(|e| CommandError::file(FileOp::CreateBackup, backup, e).into())
+        }
+    }
+}
+
+/// Search for lines containing a pattern (`ابحث` / grep)
+///
+/// `-r` searches every file beneath the given directories (default `.`);
+/// `-P` (the default) leaves symlinks alone while `-L` follows them. With
+/// `color` (output going straight to a terminal) matches are highlighted.
+fn cmd_search(args: &[&str], input: Option<&str>, options: &BuiltinOptions) -> CommandResult {
+    let recursive = args.iter().any(|a| *a == "-r" || *a == "-R");
+    let args: Vec<&str> = args.iter().copied().filter(|a| *a != "-r" && *a != "-R").collect();
+    let (links, args) = symlink_mode(&args);
+
+    if args.is_empty() {
+        return CommandError::usage(
+            ("يرجى تحديد نص للبحث", "Please specify search pattern"),
+            ("ابحث [-r] [-L|-P] <نمط> [ملف...]", "grep [-r] [-L|-P] <pattern> [file...]"),
+        ).into();
+    }
+
+    let pattern = args[0];
+    let show = |line: &str| if options.color { highlight_matches(line, pattern) } else { line.to_string() };
+
+    if recursive {
+        let roots: Vec<&str> = if args.len() > 1 { args[1..].to_vec() } else { vec!["."] };
+        let mut output = String::new();
+        for root in roots {
+            let path = options.path(root);
+            let files = if path.is_dir() { walk_files(&path, links, options) } else { vec![path.clone()] };
+            for file in files {
+                // Unreadable or non-text files are skipped while recursing
+                if let Ok(content) = fs::read_to_string(&file) {
+                    let name = display_under(&file, &path, &expand_tilde(root));
+                    for (i, line) in content.lines().enumerate() {
+                        if line.contains(pattern) {
+                            output.push_str(&format!("{}:{}:{}\n", name.display(), i + 1, show(line)));
+                        }
+                    }
+                }
+            }
+        }
+        return CommandResult::Success(output);
+    }
+
+    if let Some(inp) = input {
+        let matching_lines: Vec<String> = inp
+            .lines()
+            .filter(|line| line.contains(pattern))
+            .map(show)
+            .collect();
+
+        if matching_lines.is_empty() {
+            return CommandResult::Success(String::new());
+        }
+        return CommandResult::Success(matching_lines.join("\n") + "\n");
+    }
+
+    if args.len() < 2 {
+        return CommandError::failed((
+            "يرجى تحديد ملف للبحث فيه أو استخدام الأنبوب",
+            "Please specify a file to search or use pipe",
+        )).into();
+    }
+
+    let mut output = String::new();
+    for file in &args[1..] {
+        match fs::read_to_string(options.path(file)) {
+            Ok(content) => {
+                for (i, line) in content.lines().enumerate() {
+                    if line.contains(pattern) {
+                        if args.len() > 2 {
+                            // Multiple files: show filename
+                            output.push_str(&format!("{}:{}:{}\n", file, i + 1, show(line)));
+                        } else {
+                            output.push_str(&format!("{}:{}\n", i + 1, show(line)));
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                return CommandError::file(FileOp::Read, file, e).into();
+            }
+        }
+    }
+
+    CommandResult::Success(output)
+}
+
+/// Wrap each occurrence of `pattern` in the theme's bold error color, like `grep --color`
+fn highlight_matches(line: &str, pattern: &str) -> String {
+    if pattern.is_empty() {
+        return line.to_string();
+    }
+    line.replace(pattern, &paint(pattern, active_theme().error, true))
+}
+
+/// Split a leading `-R` off `صلاحيات`/`مالك` arguments
 #[cfg(unix)]
-fn cmd_chmod(args: &[&str]) -> CommandResult {
+fn recursive_flag<'a>(args: &'a [&'a str]) -> (bool, &'a [&'a str]) {
+    match args.split_first() {
+        Some((&"-R", rest)) => (true, rest),
+        _ => (false, args),
+    }
+}
+
+/// Apply `change` to `root` and everything beneath it (`-R`)
+///
+/// Entries that can't be changed are reported and skipped rather than
+/// aborting the walk; the command then fails with a count of them.
+/// Symlinks inside the tree are not followed.
+#[cfg(unix)]
+fn change_tree(root: &Path, options: &BuiltinOptions, change: impl Fn(&Path) -> std::io::Result<()>) -> CommandResult {
+    use filesystem::{walk_tree, warn_skipped};
+
+    if let Err(e) = fs::metadata(root) {
+        return CommandError::file(FileOp::Read, root.display(), e).into();
+    }
+
+    let mut failed = 0;
+    for path in walk_tree(root, SymlinkMode::Physical, options) {
+        if let Err(e) = change(&path) {
+            warn_skipped(&path, &e, options);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        CommandError::failed((
+            format!("تعذر تغيير {} من العناصر", failed),
+            format!("{} entries could not be changed", failed),
+        ))
+        .into()
+    } else {
+        CommandResult::None
+    }
+}
+
+/// What `صلاحيات`/`مالك` would change in a dry run: `file` itself, or with
+/// `-R` every entry beneath it
+#[cfg(unix)]
+fn describe_change(verb: (&str, &str), file: &str, recursive: bool, value: &str, options: &BuiltinOptions) -> CommandResult {
+    let root = options.path(file);
+    if let Err(e) = fs::metadata(&root) {
+        return CommandError::file(FileOp::Read, file, e).into();
+    }
+    let paths = if recursive {
+        filesystem::walk_tree(&root, SymlinkMode::Physical, options)
+    } else {
+        vec![root.clone()]
+    };
+    let output = paths
+        .iter()
+        .map(|path| display_under(path, &root, &expand_tilde(file)))
+        .map(|path| dry_run_line(verb.0, verb.1, &format!("'{}' -> {}", path.display(), value)))
+        .collect();
+    CommandResult::Success(output)
+}
+
+/// Name `path`, found beneath `root`, starting from `typed`: the operand
+/// as the user wrote it rather than resolved against the executor's
+/// directory, so `ابحث -r x .` shows `./a.txt`
+fn display_under(path: &Path, root: &Path, typed: &Path) -> PathBuf {
+    match path.strip_prefix(root) {
+        Ok(sub) if sub.as_os_str().is_empty() => typed.to_path_buf(),
+        Ok(sub) => typed.join(sub),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(unix)]
+fn cmd_chmod(args: &[&str], options: &BuiltinOptions) -> CommandResult {
     use std::os::unix::fs::PermissionsExt;
 
+    let (recursive, args) = recursive_flag(args);
     if args.len() < 2 {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد الصلاحيات والملف\nالاستخدام: صلاحيات <وضع> <ملف>\nError: Please specify mode and file\nUsage: chmod <mode> <file>".to_string()
-        );
+        return CommandError::usage(
+            ("يرجى تحديد الصلاحيات والملف", "Please specify mode and file"),
+            ("صلاحيات [-R] <وضع> <ملف>", "chmod [-R] <mode> <file>"),
+        ).into();
     }
 
     let mode_str = args[0];
@@ -473,52 +1482,55 @@ fn cmd_chmod(args: &[&str]) -> CommandResult {
     let mode = match u32::from_str_radix(mode_str, 8) {
         Ok(m) => m,
         Err(_) => {
-            return CommandResult::Error(format!(
-                "خطأ: صلاحيات غير صالحة '{}' - استخدم صيغة ثمانية (مثل 755)\nError: Invalid mode '{}' - use octal format (e.g., 755)",
-                mode_str, mode_str
-            ));
+            return CommandError::failed((
+                format!("صلاحيات غير صالحة '{}' - استخدم صيغة ثمانية (مثل 755)", mode_str),
+                format!("Invalid mode '{}' - use octal format (e.g., 755)", mode_str),
+            )).into();
         }
     };
 
-    match fs::metadata(file) {
+    if options.dry_run {
+        return describe_change(("ستتغير صلاحيات", "would change mode of"), file, recursive, mode_str, options);
+    }
+
+    let path = options.path(file);
+    if recursive {
+        return change_tree(&path, options, |path| fs::set_permissions(path, fs::Permissions::from_mode(mode)));
+    }
+
+    match fs::metadata(&path) {
         Ok(metadata) => {
             let mut perms = metadata.permissions();
             perms.set_mode(mode);
 
-            if let Err(e) = fs::set_permissions(file, perms) {
-                return CommandResult::Error(format!(
-                    "خطأ: لا يمكن تغيير صلاحيات '{}' - {} / Error: Cannot change permissions of '{}' - {}",
-                    file, e, file, e
-                ));
+            if let Err(e) = fs::set_permissions(&path, perms) {
+                return CommandError::file(FileOp::ChangePermissions, file, e).into();
             }
             CommandResult::None
         }
-        Err(e) => CommandResult::Error(format!(
-            "خطأ: لا يمكن قراءة '{}' - {} / Error: Cannot read '{}' - {}",
-            file, e, file, e
-        )),
+        Err(e) => CommandError::file(FileOp::Read, file, e).into(),
     }
 }
 
 #[cfg(not(unix))]
-fn cmd_chmod(_args: &[&str]) -> CommandResult {
-    CommandResult::Error(
-        "خطأ: أمر صلاحيات غير مدعوم على هذا النظام\nError: chmod not supported on this platform".to_string()
-    )
+fn cmd_chmod(_args: &[&str], _options: &BuiltinOptions) -> CommandResult {
+    CommandError::failed(("أمر صلاحيات غير مدعوم على هذا النظام", "chmod not supported on this platform")).into()
 }
 
 #[cfg(unix)]
-fn cmd_chown(args: &[&str]) -> CommandResult {
+fn cmd_chown(args: &[&str], options: &BuiltinOptions) -> CommandResult {
     use nix::unistd::{chown, User, Group, Uid, Gid};
 
+    let (recursive, args) = recursive_flag(args);
     if args.len() < 2 {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد المالك والملف\nالاستخدام: مالك مستخدم[:مجموعة] ملف\nError: Please specify owner and file\nUsage: chown user[:group] file".to_string()
-        );
+        return CommandError::usage(
+            ("يرجى تحديد المالك والملف", "Please specify owner and file"),
+            ("مالك [-R] مستخدم[:مجموعة] ملف", "chown [-R] user[:group] file"),
+        ).into();
     }
 
     let owner_spec = args[0];
-    let file_path = expand_tilde(args[1]);
+    let file = args[1];
 
     let (user_str, group_str) = if owner_spec.contains(':') {
         let parts: Vec<&str> = owner_spec.split(':').collect();
@@ -535,16 +1547,16 @@ fn cmd_chown(args: &[&str]) -> CommandResult {
         match User::from_name(user_str) {
             Ok(Some(user)) => Some(user.uid),
             Ok(None) => {
-                return CommandResult::Error(format!(
-                    "خطأ: المستخدم '{}' غير موجود / Error: User '{}' not found",
-                    user_str, user_str
-                ));
+                return CommandError::failed((
+                    format!("المستخدم '{}' غير موجود", user_str),
+                    format!("User '{}' not found", user_str),
+                )).into();
             }
             Err(e) => {
-                return CommandResult::Error(format!(
-                    "خطأ: فشل البحث عن المستخدم - {} / Error: Failed to lookup user - {}",
-                    e, e
-                ));
+                return CommandError::failed((
+                    format!("فشل البحث عن المستخدم - {}", e),
+                    format!("Failed to lookup user - {}", e),
+                )).into();
             }
         }
     };
@@ -557,16 +1569,16 @@ fn cmd_chown(args: &[&str]) -> CommandResult {
                 match Group::from_name(g) {
                     Ok(Some(group)) => Some(group.gid),
                     Ok(None) => {
-                        return CommandResult::Error(format!(
-                            "خطأ: المجموعة '{}' غير موجودة / Error: Group '{}' not found",
-                            g, g
-                        ));
+                        return CommandError::failed((
+                            format!("المجموعة '{}' غير موجودة", g),
+                            format!("Group '{}' not found", g),
+                        )).into();
                     }
                     Err(e) => {
-                        return CommandResult::Error(format!(
-                            "خطأ: فشل البحث عن المجموعة - {} / Error: Failed to lookup group - {}",
-                            e, e
-                        ));
+                        return CommandError::failed((
+                            format!("فشل البحث عن المجموعة - {}", e),
+                            format!("Failed to lookup group - {}", e),
+                        )).into();
                     }
                 }
             }
@@ -574,31 +1586,38 @@ fn cmd_chown(args: &[&str]) -> CommandResult {
         _ => None,
     };
 
+    if options.dry_run {
+        return describe_change(("سيتغير مالك", "would change owner of"), file, recursive, owner_spec, options);
+    }
+
+    let file_path = options.path(file);
+    if recursive {
+        return change_tree(&file_path, options, |path| chown(path, uid, gid).map_err(std::io::Error::from));
+    }
+
     match chown(&file_path, uid, gid) {
         Ok(_) => CommandResult::None,
-        Err(e) => CommandResult::Error(format!(
-            "خطأ: فشل تغيير مالك '{}' - {} / Error: Failed to change owner of '{}' - {}",
-            file_path.display(), e, file_path.display(), e
-        )),
+        Err(e) => CommandError::failed((
+            format!("فشل تغيير مالك '{}' - {}", file, e),
+            format!("Failed to change owner of '{}' - {}", file, e),
+        )).into(),
     }
 }
 
 #[cfg(not(unix))]
-fn cmd_chown(_args: &[&str]) -> CommandResult {
-    CommandResult::Error(
-        "خطأ: أمر مالك غير مدعوم على هذا النظام\nError: chown not supported on this platform".to_string()
-    )
+fn cmd_chown(_args: &[&str], _options: &BuiltinOptions) -> CommandResult {
+    CommandError::failed(("أمر مالك غير مدعوم على هذا النظام", "chown not supported on this platform")).into()
 }
 
-#[cfg(unix)]
-fn cmd_ln(args: &[&str]) -> CommandResult {
-    use std::os::unix::fs::symlink;
+/// Create a hard link, or with `-s` a symbolic link (`رابط` / ln)
+fn cmd_ln(args: &[&str], options: &BuiltinOptions) -> CommandResult {
     use std::fs::hard_link;
 
     if args.is_empty() {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد المصدر والهدف\nالاستخدام: رابط [-s|-ر] مصدر هدف\nError: Please specify source and target\nUsage: ln [-s] source target".to_string()
-        );
+        return CommandError::usage(
+            ("يرجى تحديد المصدر والهدف", "Please specify source and target"),
+            ("رابط [-s|-ر] مصدر هدف", "ln [-s] source target"),
+        ).into();
     }
 
     // Check for symbolic link flag (-s or -ر for Arabic)
@@ -609,40 +1628,874 @@ fn cmd_ln(args: &[&str]) -> CommandResult {
     };
 
     if args.len() < source_idx + 2 {
-        return CommandResult::Error(
-            "خطأ: يرجى تحديد المصدر والهدف\nالاستخدام: رابط [-s|-ر] مصدر هدف\nError: Please specify source and target\nUsage: ln [-s] source target".to_string()
-        );
+        return CommandError::usage(
+            ("يرجى تحديد المصدر والهدف", "Please specify source and target"),
+            ("رابط [-s|-ر] مصدر هدف", "ln [-s] source target"),
+        ).into();
     }
 
     let source = expand_tilde(args[source_idx]);
     let target = expand_tilde(args[source_idx + 1]);
 
+    // A symlink's source is stored as given: relative to the link itself
     let result = if symbolic {
-        symlink(&source, &target)
+        symlink(&source, &options.cwd.join(&target))
     } else {
-        hard_link(&source, &target)
+        hard_link(options.cwd.join(&source), options.cwd.join(&target))
     };
 
     match result {
         Ok(_) => CommandResult::None,
         Err(e) => {
-            let link_type = if symbolic {
-                "الرابط الرمزي / symbolic link"
+            let (link_ar, link_en) = if symbolic {
+                ("الرابط الرمزي", "symbolic link")
             } else {
-                "الرابط الصلب / hard link"
+                ("الرابط الصلب", "hard link")
             };
-            CommandResult::Error(format!(
-                "خطأ: فشل إنشاء {} من '{}' إلى '{}' - {}\nError: Failed to create {} from '{}' to '{}' - {}",
-                link_type, source.display(), target.display(), e,
-                link_type, source.display(), target.display(), e
-            ))
+            let (hint_ar, hint_en) = symlink_hint(&e);
+            CommandError::failed((
+                format!("فشل إنشاء {} من '{}' إلى '{}' - {}{}", link_ar, source.display(), target.display(), e, hint_ar),
+                format!("Failed to create {} from '{}' to '{}' - {}{}", link_en, source.display(), target.display(), e, hint_en),
+            )).into()
         }
     }
 }
 
-#[cfg(not(unix))]
-fn cmd_ln(_args: &[&str]) -> CommandResult {
-    CommandResult::Error(
-        "خطأ: أمر رابط غير مدعوم على هذا النظام\nError: ln not supported on this platform".to_string()
-    )
+#[cfg(unix)]
+fn symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+/// Windows needs to know up front whether the link points at a directory
+///
+/// A relative source is resolved from the link's own directory, the way the
+/// link itself will be.
+#[cfg(windows)]
+fn symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+
+    let resolved = match target.parent() {
+        Some(parent) if source.is_relative() => parent.join(source),
+        _ => source.to_path_buf(),
+    };
+    if resolved.is_dir() {
+        symlink_dir(source, target)
+    } else {
+        symlink_file(source, target)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_source: &Path, _target: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "symbolic links not supported on this platform"))
+}
+
+/// Extra advice for the privilege error Windows gives unprivileged users
+#[cfg(windows)]
+fn symlink_hint(error: &std::io::Error) -> (&'static str, &'static str) {
+    // ERROR_PRIVILEGE_NOT_HELD
+    if error.raw_os_error() == Some(1314) {
+        (
+            "\nتلميح: فعّل وضع المطور (Developer Mode) في إعدادات ويندوز أو شغّل الصدفة كمسؤول",
+            "\nHint: Enable Developer Mode in Windows Settings, or run the shell as administrator",
+        )
+    } else {
+        ("", "")
+    }
+}
+
+#[cfg(not(windows))]
+fn symlink_hint(_error: &std::io::Error) -> (&'static str, &'static str) {
+    ("", "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Locale;
+
+    #[test]
+    fn test_clear_scrollback_option() {
+        assert_eq!(clear_sequence(&[]).as_deref(), Some(CLEAR_SCREEN));
+        for flag in ["-ك", "-a"] {
+            let sequence = clear_sequence(&[flag]).unwrap();
+            assert!(sequence.starts_with(CLEAR_SCREEN) && sequence.ends_with(CLEAR_SCROLLBACK));
+        }
+        assert!(matches!(execute_builtin("امسح", &["-x"], None), Some(CommandResult::Error(msg)) if msg.to_string().contains("Usage")));
+    }
+
+    #[test]
+    fn test_ln_hard_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source.txt");
+        fs::write(&source, "محتوى").unwrap();
+        let source_path = source.to_string_lossy().to_string();
+
+        for (name, link) in [("رابط", "a"), ("ln", "b")] {
+            let target = dir.path().join(link);
+            let result = execute_builtin(name, &[&source_path, &target.to_string_lossy()], None);
+            assert_eq!(result, Some(CommandResult::None));
+            assert_eq!(fs::read_to_string(&target).unwrap(), "محتوى");
+        }
+
+        let result = cmd_ln(&[&source_path, &dir.path().join("a").to_string_lossy()], &BuiltinOptions::default());
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("hard link")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ln_symbolic_to_file_and_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file"), "x").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+
+        for (flag, source, link) in [("-s", "file", "file_link"), ("-ر", "sub", "sub_link")] {
+            let target = dir.path().join(link);
+            // Relative sources are stored as given, resolved from the link's directory
+            assert_eq!(cmd_ln(&[flag, source, &target.to_string_lossy()], &BuiltinOptions::default()), CommandResult::None);
+            assert_eq!(fs::read_link(&target).unwrap(), Path::new(source));
+            assert!(target.exists());
+        }
+        assert!(dir.path().join("sub_link").is_dir());
+    }
+
+    #[test]
+    fn test_verbose_file_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        let (a, b, c) = (path("a"), path("b"), path("c"));
+        fs::write(&a, "").unwrap();
+
+        let result = execute_builtin("انسخ", &["-v", &a, &b], None);
+        assert_eq!(result, Some(CommandResult::Success(format!("نُسخ / copied '{}' -> '{}'\n", a, b))));
+        let result = execute_builtin("mv", &[&b, &c, "--verbose"], None);
+        assert_eq!(result, Some(CommandResult::Success(format!("نُقل / moved '{}' -> '{}'\n", b, c))));
+        let result = execute_builtin("احذف", &["-ت", &a, &c], None);
+        assert_eq!(
+            result,
+            Some(CommandResult::Success(format!("حُذف / removed '{}'\nحُذف / removed '{}'\n", a, c)))
+        );
+
+        // Silent without -v
+        assert_eq!(cmd_mkdir(&[&path("quiet")], &BuiltinOptions::default()), CommandResult::None);
+    }
+
+    #[test]
+    fn test_mkdir_verbose_reports_each_created_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("x")).unwrap();
+        let nested = dir.path().join("x/y/z");
+
+        let output = cmd_mkdir(&["-p", "-v", &nested.to_string_lossy()], &BuiltinOptions::default());
+        let expected = format!(
+            "أُنشئ / created '{}'\nأُنشئ / created '{}'\n",
+            dir.path().join("x/y").display(),
+            nested.display()
+        );
+        assert_eq!(output, CommandResult::Success(expected));
+        // Nothing new to report the second time
+        assert_eq!(cmd_mkdir(&["-p", "-v", &nested.to_string_lossy()], &BuiltinOptions::default()), CommandResult::None);
+    }
+
+    #[test]
+    fn test_cp_preserve_mode_and_times() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("script.sh");
+        fs::write(&source, "#!/bin/sh\n").unwrap();
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        fs::File::open(&source)
+            .unwrap()
+            .set_times(fs::FileTimes::new().set_accessed(old).set_modified(old))
+            .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&source, fs::Permissions::from_mode(0o751)).unwrap();
+        }
+        let src = source.to_string_lossy().to_string();
+
+        for (name, flag) in [("انسخ", "-ح"), ("cp", "-p")] {
+            let dest = dir.path().join(format!("copy-{}", name));
+            let dst = dest.to_string_lossy().to_string();
+            assert_eq!(execute_builtin(name, &[flag, &src, &dst], None), Some(CommandResult::None));
+
+            let (src_meta, dest_meta) = (fs::metadata(&source).unwrap(), fs::metadata(&dest).unwrap());
+            assert_eq!(dest_meta.permissions(), src_meta.permissions());
+            assert_eq!(dest_meta.modified().unwrap(), old);
+        }
+
+        // Without -p the copy gets a fresh modification time
+        let plain = dir.path().join("plain").to_string_lossy().to_string();
+        assert_eq!(cmd_cp(&[&src, &plain], &BuiltinOptions::default()), CommandResult::None);
+        assert_ne!(fs::metadata(&plain).unwrap().modified().unwrap(), old);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chmod_recursive() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tree");
+        fs::create_dir_all(root.join("sub/deeper")).unwrap();
+        fs::write(root.join("a.txt"), "").unwrap();
+        fs::write(root.join("sub/deeper/b.txt"), "").unwrap();
+        let root_str = root.to_string_lossy().to_string();
+
+        for name in ["صلاحيات", "chmod"] {
+            assert_eq!(execute_builtin(name, &["-R", "750", &root_str], None), Some(CommandResult::None));
+            for path in ["", "a.txt", "sub", "sub/deeper", "sub/deeper/b.txt"] {
+                let mode = fs::metadata(root.join(path)).unwrap().permissions().mode() & 0o777;
+                assert_eq!(mode, 0o750, "{}", path);
+            }
+            fs::set_permissions(root.join("a.txt"), fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        // Without -R only the named entry changes
+        assert_eq!(cmd_chmod(&["700", &root_str], &BuiltinOptions::default()), CommandResult::None);
+        let mode = fs::metadata(root.join("a.txt")).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o644);
+        assert!(!cmd_chmod(&["-R", "700", &dir.path().join("missing").to_string_lossy()], &BuiltinOptions::default()).is_success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_change_tree_counts_failed_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("a.txt"), "").unwrap();
+        fs::write(dir.path().join("sub/b.txt"), "").unwrap();
+
+        let result = change_tree(dir.path(), &BuiltinOptions::default(), |path| {
+            if path.is_file() {
+                Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+            } else {
+                Ok(())
+            }
+        });
+        let CommandResult::Error(error) = result else { panic!("expected an error, got {:?}", result) };
+        assert!(error.to_string().contains("2 entries could not be changed"), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chown_recursive_to_current_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/file"), "").unwrap();
+
+        let uid = nix::unistd::getuid().to_string();
+        let result = cmd_chown(&["-R", &uid, &dir.path().to_string_lossy()], &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::None);
+    }
+
+    #[test]
+    fn test_theme_lists_themes() {
+        for name in ["سمة", "theme"] {
+            let output = execute_builtin(name, &[], None).unwrap();
+            let output = output.output().unwrap().to_string();
+            assert!(output.contains("dark") && output.contains("فاتح"));
+        }
+    }
+
+    #[test]
+    fn test_theme_unknown_name() {
+        let result = cmd_theme(&["neon"]);
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("Unknown theme")));
+    }
+
+    #[test]
+    fn test_colors_lists_swatches() {
+        for name in ["الوان", "colors"] {
+            let output = execute_builtin(name, &[], None).unwrap();
+            let output = output.output().unwrap().to_string();
+            assert!(output.contains("Theme: "), "{}", output);
+            for label in ["directory", "executable", "symlink", "prompt name", "error"] {
+                assert!(output.contains(label), "{}", label);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lang_switches_locale() {
+        for name in ["لغة", "lang"] {
+            let output = execute_builtin(name, &[], None).unwrap();
+            assert!(output.output().unwrap().contains("* كلاهما (both)"));
+        }
+        assert_eq!(execute_builtin("lang", &["en"], None), Some(CommandResult::None));
+        assert_eq!(active_locale(), Locale::English);
+        assert_eq!(execute_builtin("لغة", &["كلاهما"], None), Some(CommandResult::None));
+        assert_eq!(active_locale(), Locale::Both);
+
+        let result = cmd_lang(&["fr"]);
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("Unknown language 'fr'")));
+    }
+
+    #[test]
+    fn test_mkdir_fails_if_exists() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("مجلد");
+        let target = target.to_str().unwrap();
+
+        assert_eq!(execute_builtin("انشئ", &[target], None), Some(CommandResult::None));
+        assert!(Path::new(target).is_dir());
+
+        let result = execute_builtin("mkdir", &[target], None).unwrap();
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("already exists")));
+    }
+
+    #[test]
+    fn test_mkdir_without_parents_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+
+        let result = cmd_mkdir(&[nested.to_str().unwrap()], &BuiltinOptions::default());
+        assert!(matches!(result, CommandResult::Error(_)));
+        assert!(!nested.exists());
+    }
+
+    #[test]
+    fn test_mkdir_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        let nested_str = nested.to_str().unwrap();
+
+        assert_eq!(cmd_mkdir(&["-p", nested_str], &BuiltinOptions::default()), CommandResult::None);
+        assert!(nested.is_dir());
+        // Existing directories are fine with -p
+        assert_eq!(cmd_mkdir(&["-p", nested_str], &BuiltinOptions::default()), CommandResult::None);
+    }
+
+    #[test]
+    fn test_cat_invalid_utf8_is_lossy() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("legacy.txt");
+        // "مرحبا" in Windows-1256 is not valid UTF-8
+        fs::write(&file, [b'a', 0xE3, 0xD1, b'b', b'\n']).unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        for name in ["اقرأ", "cat"] {
+            let result = execute_builtin(name, &[&path], None);
+            assert_eq!(result, Some(CommandResult::Success("a\u{FFFD}\u{FFFD}b\n".to_string())));
+        }
+    }
+
+    #[test]
+    fn test_cat_binary_file_needs_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data.bin");
+        fs::write(&file, [b'x', 0x00, 0xFF]).unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        let result = execute_builtin("cat", &[&path], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("binary file")));
+
+        let result = execute_builtin("اقرأ", &["-b", &path], None);
+        assert_eq!(result, Some(CommandResult::Success("x\0\u{FFFD}".to_string())));
+    }
+
+    #[test]
+    fn test_tee_writes_and_passes_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("out.txt");
+        let path = file.to_string_lossy().to_string();
+
+        let result = execute_builtin("حفظ", &[&path], Some("أ\n"));
+        assert_eq!(result, Some(CommandResult::Success("أ\n".to_string())));
+        let result = execute_builtin("tee", &["-a", &path], Some("b\n"));
+        assert_eq!(result, Some(CommandResult::Success("b\n".to_string())));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "أ\nb\n");
+
+        // Without -a the file is replaced
+        execute_builtin("tee", &[&path], Some("c\n"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "c\n");
+
+        let result = execute_builtin("حفظ", &[&path], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Usage: <command> | tee")));
+    }
+
+    #[test]
+    fn test_rm_guard_protects_root_and_home() {
+        assert!(protected_target(Path::new("/"), &BuiltinOptions::default()).is_some());
+        assert!(protected_target(&expand_tilde("~"), &BuiltinOptions::default()).is_some());
+        assert!(protected_target(&expand_tilde("~/."), &BuiltinOptions::default()).is_some());
+        assert!(protected_target(Path::new("."), &BuiltinOptions::default()).is_some());
+    }
+
+    #[test]
+    fn test_rm_guard_allows_regular_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("x.txt");
+        fs::write(&file, "").unwrap();
+        assert_eq!(protected_target(&file, &BuiltinOptions::default()), None);
+        // Missing paths have nothing to protect
+        assert_eq!(protected_target(Path::new("/nonexistent/ocean-path"), &BuiltinOptions::default()), None);
+
+        let path = file.to_string_lossy().to_string();
+        assert_eq!(execute_builtin("احذف", &[&path], None), Some(CommandResult::None));
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_cp_progress_copies_identical_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("media.bin");
+        // Several chunks plus a partial one, with every byte value
+        let data: Vec<u8> = (0..COPY_CHUNK_SIZE * 3 + 123).map(|i| (i * 31 % 256) as u8).collect();
+        fs::write(&source, &data).unwrap();
+        let src = source.to_string_lossy().to_string();
+
+        for (name, flag) in [("انسخ", "--تقدم"), ("cp", "--progress")] {
+            let dest = dir.path().join(format!("copy-{}", name));
+            let dst = dest.to_string_lossy().to_string();
+            assert_eq!(execute_builtin(name, &[flag, &src, &dst], None), Some(CommandResult::None));
+            assert_eq!(fs::read(&dest).unwrap(), data);
+        }
+
+        let mut reports = Vec::new();
+        let dest = dir.path().join("reported.bin");
+        let copied = copy_with_progress(&source, &dest, |done, total| reports.push((done, total))).unwrap();
+        assert_eq!(copied, data.len() as u64);
+        assert_eq!(reports.len(), 5);
+        assert_eq!(reports.first(), Some(&(0, data.len() as u64)));
+        assert_eq!(reports.last(), Some(&(data.len() as u64, data.len() as u64)));
+    }
+
+    #[test]
+    fn test_cp_mv_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest.txt");
+        let backup = dir.path().join("dest.txt~");
+        let (src_s, dest_s) = (src.to_string_lossy().to_string(), dest.to_string_lossy().to_string());
+
+        for name in ["انسخ", "cp", "انقل", "mv"] {
+            fs::write(&src, "new").unwrap();
+            fs::write(&dest, "old").unwrap();
+            assert_eq!(execute_builtin(name, &["-b", &src_s, &dest_s], None), Some(CommandResult::None));
+            assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+            assert_eq!(fs::read_to_string(&backup).unwrap(), "old");
+            fs::remove_file(&backup).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_cp_mv_no_clobber() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest.txt");
+        fs::write(&src, "new").unwrap();
+        fs::write(&dest, "old").unwrap();
+        let (src_s, dest_s) = (src.to_string_lossy().to_string(), dest.to_string_lossy().to_string());
+
+        for name in ["انسخ", "cp", "انقل", "mv"] {
+            let result = execute_builtin(name, &["-n", &src_s, &dest_s], None);
+            assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("already exists")));
+            assert_eq!(fs::read_to_string(&dest).unwrap(), "old");
+            assert!(src.exists());
+        }
+    }
+
+    #[test]
+    fn test_dry_run_leaves_files_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest.txt");
+        fs::write(&src, "new").unwrap();
+        fs::write(&dest, "old").unwrap();
+        let (src_s, dest_s) = (src.to_string_lossy().to_string(), dest.to_string_lossy().to_string());
+        let dry_run = BuiltinOptions { dry_run: true, ..BuiltinOptions::default() };
+        let run = |name, args: &[&str]| match execute_builtin_with(name, args, None, dry_run) {
+            Some(CommandResult::Success(output)) => output,
+            other => panic!("{} failed: {:?}", name, other),
+        };
+
+        assert_eq!(run("احذف", &[&src_s]), format!("سيُحذف / would remove '{}'\n", src_s));
+        assert_eq!(run("cp", &[&src_s, &dest_s]), format!("سيُنسخ / would copy '{}' -> '{}'\n", src_s, dest_s));
+        let moved = run("انقل", &["-b", &src_s, &dest_s]);
+        assert!(moved.starts_with(&format!("سيُحفظ احتياطياً / would back up '{}' -> '{}~'\n", dest_s, dest_s)));
+        assert!(run("صلاحيات", &["600", &src_s]).contains("would change mode of"));
+        #[cfg(unix)]
+        assert!(run("chown", &["0", &src_s]).contains(&format!("would change owner of '{}' -> 0", src_s)));
+
+        assert_eq!(fs::read_to_string(&src).unwrap(), "new");
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "old");
+        assert!(!dir.path().join("dest.txt~").exists());
+        // Errors the real command would hit are still reported
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+        assert!(!execute_builtin_with("rm", &[&missing], None, dry_run).unwrap().is_success());
+    }
+
+    #[test]
+    fn test_cp_overwrites_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest.txt");
+        fs::write(&src, "new").unwrap();
+        fs::write(&dest, "old").unwrap();
+        let (src_s, dest_s) = (src.to_string_lossy().to_string(), dest.to_string_lossy().to_string());
+
+        assert_eq!(execute_builtin("cp", &[&src_s, &dest_s], None), Some(CommandResult::None));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new");
+        assert!(!dir.path().join("dest.txt~").exists());
+    }
+
+    #[test]
+    fn test_ls_json_structure() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("ملف \"1\".txt"), "12345").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+
+        for name in ["اعرض", "ls"] {
+            let output = match execute_builtin(name, &["--json", &path], None) {
+                Some(CommandResult::Success(output)) => output,
+                other => panic!("unexpected result: {:?}", other),
+            };
+            // No color codes in machine-readable output
+            assert!(!output.contains('\x1b'));
+
+            let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+            let entries = value.as_array().unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0]["name"], "sub");
+            assert_eq!(entries[0]["type"], "directory");
+            assert_eq!(entries[1]["name"], "ملف \"1\".txt");
+            assert_eq!(entries[1]["type"], "file");
+            assert_eq!(entries[1]["size"], 5);
+            assert!(entries[1]["permissions"].is_string());
+        }
+    }
+
+    fn ls_names(args: &[&str]) -> Vec<String> {
+        let output = execute_builtin("ls", args, None).unwrap().output().unwrap().to_string();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        value.as_array().unwrap().iter().map(|e| e["name"].as_str().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn test_ls_directories_first() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "").unwrap();
+        fs::create_dir(dir.path().join("z_dir")).unwrap();
+        fs::write(dir.path().join("ملف"), "").unwrap();
+        fs::create_dir(dir.path().join("مجلد")).unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+
+        assert_eq!(ls_names(&["--json", &path]), ["z_dir", "مجلد", "a.txt", "ملف"]);
+        assert_eq!(ls_names(&["-r", "--json", &path]), ["ملف", "a.txt", "مجلد", "z_dir"]);
+
+        // The colored listing uses the same order
+        for name in ["اعرض", "ls"] {
+            let output = execute_builtin(name, &[&path], None).unwrap().output().unwrap().to_string();
+            let order: Vec<usize> = ["z_dir", "مجلد", "a.txt", "ملف"]
+                .iter()
+                .map(|entry| output.find(entry).unwrap())
+                .collect();
+            assert!(order.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+
+    #[test]
+    fn test_ls_file_and_directory_operands() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b.txt"), "").unwrap();
+        fs::write(dir.path().join("a.txt"), "").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub").join("inner"), "").unwrap();
+        let a = dir.path().join("a.txt").to_string_lossy().to_string();
+        let b = dir.path().join("b.txt").to_string_lossy().to_string();
+        let sub = dir.path().join("sub").to_string_lossy().to_string();
+
+        assert_eq!(ls_names(&["--json", &b, &a]), [a.clone(), b.clone()]);
+        assert_eq!(ls_names(&["--json", &a, &sub]), [a.clone(), format!("{}/inner", sub)]);
+
+        // A lone file prints just its name, as given
+        for name in ["اعرض", "ls"] {
+            let output = execute_builtin(name, &[&a], None).unwrap().output().unwrap().to_string();
+            assert_eq!(crate::utils::strip_ansi(&output), format!("{}\n", a));
+        }
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+        assert!(matches!(cmd_ls(&[&missing], &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Cannot access")));
+
+        // Several operands: files first, then each directory under a header
+        let output = cmd_ls(&[&sub, &a], &BuiltinOptions::default()).output().unwrap().to_string();
+        let header = output.find(&format!("{}:\n", sub)).unwrap();
+        assert!(output.find("a.txt").unwrap() < header);
+        assert!(header < output.find("inner").unwrap());
+    }
+
+    #[test]
+    fn test_ls_recursive() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("b").join("deep")).unwrap();
+        fs::create_dir(root.join("a")).unwrap();
+        fs::write(root.join("top.txt"), "").unwrap();
+        fs::write(root.join("a").join("z.txt"), "").unwrap();
+        fs::write(root.join("a").join("y.txt"), "").unwrap();
+        fs::write(root.join("b").join("deep").join("x.txt"), "").unwrap();
+        let path = root.to_string_lossy().to_string();
+
+        let output = cmd_ls(&["-R", &path], &BuiltinOptions::default()).output().unwrap().to_string();
+        let expected = format!(
+            "{p}:\na/\nb/\ntop.txt\n\n{p}/a:\ny.txt\nz.txt\n\n{p}/b:\ndeep/\n\n{p}/b/deep:\nx.txt\n",
+            p = path
+        );
+        assert_eq!(crate::utils::strip_ansi(&output), expected);
+
+        // JSON names every entry by its path
+        let names = ls_names(&["--json", "-R", &path]);
+        assert!(names.contains(&format!("{}/b/deep/x.txt", path)));
+        assert_eq!(names.len(), 7);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ls_recursive_skips_unreadable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("secret"), "").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        // Root can read it anyway
+        let readable = fs::read_dir(&locked).is_ok();
+
+        let path = dir.path().to_string_lossy().to_string();
+        let output = cmd_ls(&["-R", &path], &BuiltinOptions::default()).output().unwrap().to_string();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(output.starts_with(&format!("{}:\n", path)));
+        assert_eq!(output.contains("secret"), readable);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ls_long_format() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "hello").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::File::options().write(true).open(&file).unwrap().set_modified(UNIX_EPOCH + Duration::from_secs(86_400)).unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+
+        for name in ["اعرض", "ls"] {
+            let output = execute_builtin(name, &["-l", &path], None).unwrap().output().unwrap().to_string();
+            let output = crate::utils::strip_ansi(&output);
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines.len(), 2, "{}", output);
+            assert!(lines[0].starts_with("drwx") && lines[0].ends_with(" sub/"), "{}", output);
+            assert!(lines[1].starts_with("-rw-r--r-- "), "{}", output);
+            assert!(lines[1].ends_with(" 5 1970-01-02 00:00:00 a.txt"), "{}", output);
+        }
+
+        fs::write(&file, vec![0; 1536]).unwrap();
+        let output = cmd_ls(&["-l", "-h", &path], &BuiltinOptions::default()).output().unwrap().to_string();
+        assert!(crate::utils::strip_ansi(&output).contains(" 1.5 KB "), "{}", output);
+    }
+
+    #[test]
+    fn test_ls_sort_by_time_and_size() {
+        use std::time::{Duration, SystemTime};
+
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        for (name, size, age) in [("old", 30, 300), ("new", 10, 0), ("middle", 20, 100)] {
+            let file = fs::File::create(dir.path().join(name)).unwrap();
+            file.set_len(size).unwrap();
+            file.set_modified(now - Duration::from_secs(age)).unwrap();
+        }
+        let path = dir.path().to_string_lossy().to_string();
+
+        assert_eq!(ls_names(&["-t", "--json", &path]), ["new", "middle", "old"]);
+        assert_eq!(ls_names(&["-t", "-r", "--json", &path]), ["old", "middle", "new"]);
+        assert_eq!(ls_names(&["-S", "--json", &path]), ["old", "middle", "new"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ls_symlink_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("sub"), dir.path().join("link")).unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+
+        let link_type = |flags: &[&str]| {
+            let args: Vec<&str> = flags.iter().copied().chain(["--json", path.as_str()]).collect();
+            let output = execute_builtin("ls", &args, None).unwrap().output().unwrap().to_string();
+            let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+            let link = value.as_array().unwrap().iter().find(|e| e["name"] == "link").unwrap();
+            link["type"].as_str().unwrap().to_string()
+        };
+        assert_eq!(link_type(&[]), "symlink");
+        assert_eq!(link_type(&["-P"]), "symlink");
+        assert_eq!(link_type(&["-L"]), "directory");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_recursive_self_referential_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("notes.txt"), "بحر\nocean\n").unwrap();
+        std::os::unix::fs::symlink(&sub, sub.join("loop")).unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let expected = format!("{}:2:ocean\n", sub.join("notes.txt").display());
+
+        for name in ["ابحث", "grep"] {
+            for mode in ["-L", "-P"] {
+                let result = execute_builtin(name, &["-r", mode, "ocean", &path], None);
+                assert_eq!(result, Some(CommandResult::Success(expected.clone())));
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_highlights_matches_on_terminal() {
+        let input = "بحر محيط\nocean ocean\nland\n";
+        let terminal = BuiltinOptions { color: true, ..BuiltinOptions::default() };
+
+        let result = cmd_search(&["ocean"], Some(input), &terminal).output().unwrap().to_string();
+        assert_eq!(result.matches("\x1b[").count(), 4);
+        assert_eq!(crate::utils::strip_ansi(&result), "ocean ocean\n");
+
+        let result = cmd_search(&["محيط"], Some(input), &terminal).output().unwrap().to_string();
+        assert!(result.starts_with("بحر \x1b["));
+
+        // Without a terminal the output is plain
+        assert_eq!(cmd_search(&["ocean"], Some(input), &BuiltinOptions::default()), CommandResult::Success("ocean ocean\n".to_string()));
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+
+    #[test]
+    fn test_eprintln_returns_no_output() {
+        for name in ["صدى_خطأ", "eprintln"] {
+            assert_eq!(execute_builtin(name, &["تحذير"], None), Some(CommandResult::None));
+            assert_eq!(execute_builtin(name, &[], Some("من الأنبوب\n")), Some(CommandResult::None));
+        }
+    }
+
+    #[test]
+    fn test_sleep_arabic_and_english() {
+        let start = std::time::Instant::now();
+        assert_eq!(execute_builtin("نم", &["٠٫٠٥"], None), Some(CommandResult::None));
+        assert_eq!(execute_builtin("sleep", &["0.05"], None), Some(CommandResult::None));
+        assert!(start.elapsed() >= std::time::Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_sleep_invalid_duration() {
+        for arg in ["-1", "abc", "inf"] {
+            let result = execute_builtin("sleep", &[arg], None);
+            assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Invalid duration")));
+        }
+        let result = execute_builtin("نم", &[], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Usage")));
+    }
+
+    #[test]
+    fn test_yes_arabic_and_english() {
+        for name in ["كرر_نص", "مكرر", "yes"] {
+            assert_eq!(
+                execute_builtin(name, &["-n", "3", "مرحبا", "بك"], None),
+                Some(CommandResult::Success("مرحبا بك\nمرحبا بك\nمرحبا بك\n".to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_yes_default_count_and_text() {
+        let expected = "y\n".repeat(YES_DEFAULT_COUNT);
+        assert_eq!(execute_builtin("yes", &[], None), Some(CommandResult::Success(expected)));
+        assert_eq!(
+            execute_builtin("كرر_نص", &["-n", "٢", "hi"], None),
+            Some(CommandResult::Success("hi\nhi\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_yes_invalid_count() {
+        let result = execute_builtin("yes", &["-n", "lots"], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("Invalid count")));
+        let result = execute_builtin("yes", &["-n", "2000000"], None);
+        assert!(matches!(result, Some(CommandResult::Error(msg)) if msg.to_string().contains("maximum")));
+    }
+
+    #[test]
+    fn test_truncate_empties_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("log.txt");
+        fs::write(&file, "سجل قديم\n").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        for name in ["افرغ", "truncate"] {
+            assert_eq!(execute_builtin(name, &[&path], None), Some(CommandResult::None));
+            assert_eq!(fs::read(&file).unwrap(), b"");
+        }
+    }
+
+    #[test]
+    fn test_truncate_to_size_and_create() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data");
+        fs::write(&file, "0123456789").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        assert_eq!(cmd_truncate(&["-s", "٤", &path], &BuiltinOptions::default()), CommandResult::None);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "0123");
+
+        let new = dir.path().join("new").to_string_lossy().to_string();
+        assert_eq!(cmd_truncate(&["-s", "3", &new], &BuiltinOptions::default()), CommandResult::None);
+        assert_eq!(fs::read(&new).unwrap(), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_truncate_errors() {
+        assert!(!cmd_truncate(&[], &BuiltinOptions::default()).is_success());
+        assert!(matches!(cmd_truncate(&["-s", "big", "f"], &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Invalid size")));
+        assert!(matches!(
+            cmd_truncate(&["/nonexistent/ocean-dir/file"], &BuiltinOptions::default()),
+            CommandResult::Error(msg) if msg.to_string().contains("Cannot truncate")
+        ));
+    }
+
+    #[test]
+    fn test_seq_arabic_and_english() {
+        for name in ["تسلسل", "seq"] {
+            let result = execute_builtin(name, &["5"], None);
+            assert_eq!(result, Some(CommandResult::Success("1\n2\n3\n4\n5\n".to_string())));
+        }
+        let result = cmd_seq(&["2", "10"]).output().unwrap().to_string();
+        assert_eq!(result.lines().collect::<Vec<_>>(), ["2", "3", "4", "5", "6", "7", "8", "9", "10"]);
+        assert_eq!(cmd_seq(&["1", "2", "9"]), CommandResult::Success("1\n3\n5\n7\n9\n".to_string()));
+    }
+
+    #[test]
+    fn test_seq_descending_fractions_and_arabic_digits() {
+        assert_eq!(cmd_seq(&["3", "-1", "1"]), CommandResult::Success("3\n2\n1\n".to_string()));
+        assert_eq!(cmd_seq(&["0", "0.1", "0.3"]), CommandResult::Success("0.0\n0.1\n0.2\n0.3\n".to_string()));
+        assert_eq!(cmd_seq(&["١", "٢", "٥"]), CommandResult::Success("1\n3\n5\n".to_string()));
+        assert_eq!(cmd_seq(&["٠٫٥", "١٫٥"]), CommandResult::Success("0.5\n1.5\n".to_string()));
+        // A range that runs the wrong way prints nothing
+        assert_eq!(cmd_seq(&["5", "1"]), CommandResult::None);
+    }
+
+    #[test]
+    fn test_seq_errors() {
+        assert!(matches!(cmd_seq(&["1", "0", "5"]), CommandResult::Error(msg) if msg.to_string().contains("zero")));
+        assert!(matches!(cmd_seq(&["abc"]), CommandResult::Error(msg) if msg.to_string().contains("Invalid number")));
+        assert!(!cmd_seq(&[]).is_success());
+        assert!(!cmd_seq(&["1", "2", "3", "4"]).is_success());
+        assert!(matches!(cmd_seq(&["1", "0.000001", "100"]), CommandResult::Error(msg) if msg.to_string().contains("maximum")));
+    }
 }
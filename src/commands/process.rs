@@ -0,0 +1,180 @@
+//! Process commands
+//! `عمليات` (ps) and `اقتل` (kill)
+
+use crate::executor::{CommandError, CommandResult, FileOp};
+#[cfg(unix)]
+use crate::utils::normalize_digits;
+
+/// List running processes (`عمليات` / ps)
+///
+/// Reads `/proc`, printing a `PID`/`NAME` header and one tab-separated line
+/// per process, ordered by pid.
+#[cfg(target_os = "linux")]
+pub fn cmd_ps(_args: &[&str]) -> CommandResult {
+    let entries = match std::fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(e) => {
+            return CommandError::file(FileOp::Read, "/proc", e).into();
+        }
+    };
+
+    // Processes may exit while we read; those are simply skipped
+    let mut processes: Vec<(u32, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse().ok()?;
+            let name = std::fs::read_to_string(entry.path().join("comm")).ok()?;
+            Some((pid, name.trim_end().to_string()))
+        })
+        .collect();
+    processes.sort();
+
+    let mut output = String::from("PID\tالاسم / NAME\n");
+    for (pid, name) in processes {
+        output.push_str(&format!("{}\t{}\n", pid, name));
+    }
+    CommandResult::Success(output)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cmd_ps(_args: &[&str]) -> CommandResult {
+    CommandError::failed(("أمر عمليات غير مدعوم على هذا النظام", "ps not supported on this platform")).into()
+}
+
+/// Send a signal to a process (`اقتل` / kill)
+///
+/// `اقتل PID [SIGNAL]` sends SIGTERM by default. The signal can be a number
+/// (`9`), a name with or without `SIG` (`KILL`, `SIGKILL`), optionally with a
+/// leading `-`; `0` only checks that the process exists. The pid may use
+/// Arabic-Indic digits.
+#[cfg(unix)]
+pub fn cmd_kill(args: &[&str]) -> CommandResult {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+
+    if args.is_empty() || args.len() > 2 {
+        return CommandError::usage(
+            ("يرجى تحديد معرف العملية", "Please specify a process id"),
+            ("اقتل <معرف> [إشارة]", "kill <pid> [signal]"),
+        ).into();
+    }
+
+    // Only positive pids: 0 and negatives would signal whole process groups
+    let pid = match normalize_digits(args[0]).parse::<i32>() {
+        Ok(pid) if pid > 0 => Pid::from_raw(pid),
+        _ => {
+            return CommandError::failed((
+                format!("معرف عملية غير صالح '{}'", args[0]),
+                format!("Invalid process id '{}'", args[0]),
+            )).into();
+        }
+    };
+
+    let signal = match args.get(1) {
+        None => Some(Signal::SIGTERM),
+        Some(spec) => match parse_signal(spec) {
+            Some(signal) => signal,
+            None => {
+                return CommandError::failed((
+                    format!("إشارة غير معروفة '{}'", spec),
+                    format!("Unknown signal '{}'", spec),
+                )).into();
+            }
+        },
+    };
+
+    match kill(pid, signal) {
+        Ok(()) => CommandResult::None,
+        Err(e) => CommandError::failed((
+            format!("لا يمكن إرسال الإشارة إلى {} - {}", pid, e),
+            format!("Cannot signal {} - {}", pid, e),
+        )).into(),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn cmd_kill(_args: &[&str]) -> CommandResult {
+    CommandError::failed(("أمر اقتل غير مدعوم على هذا النظام", "kill not supported on this platform")).into()
+}
+
+/// Parse a signal number or name; `Some(None)` is the existence check `0`
+#[cfg(unix)]
+fn parse_signal(spec: &str) -> Option<Option<nix::sys::signal::Signal>> {
+    use nix::sys::signal::Signal;
+    use std::str::FromStr;
+
+    let spec = normalize_digits(spec);
+    let spec = spec.strip_prefix('-').unwrap_or(&spec);
+    if let Ok(number) = spec.parse::<i32>() {
+        return match number {
+            0 => Some(None),
+            _ => Signal::try_from(number).ok().map(Some),
+        };
+    }
+
+    let name = spec.to_uppercase();
+    let name = if name.starts_with("SIG") { name } else { format!("SIG{}", name) };
+    Signal::from_str(&name).ok().map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::execute_builtin;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_ps_lists_current_process() {
+        for name in ["عمليات", "ps"] {
+            let output = execute_builtin(name, &[], None).unwrap().output().unwrap().to_string();
+            assert!(output.starts_with("PID\t"));
+            let own = format!("{}\t", std::process::id());
+            assert!(output.lines().any(|line| line.starts_with(&own)));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kill_terminates_child() {
+        use std::os::unix::process::ExitStatusExt;
+
+        for (name, signal) in [("اقتل", "KILL"), ("kill", "-15"), ("kill", "sigterm")] {
+            let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+            let pid = child.id().to_string();
+
+            assert_eq!(execute_builtin(name, &[&pid, signal], None), Some(CommandResult::None));
+            assert!(child.wait().unwrap().signal().is_some());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kill_arabic_indic_pid_and_default_signal() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid: String = child
+            .id()
+            .to_string()
+            .chars()
+            .map(|c| char::from_u32('٠' as u32 + c.to_digit(10).unwrap()).unwrap())
+            .collect();
+
+        assert_eq!(cmd_kill(&[&pid]), CommandResult::None);
+        assert_eq!(child.wait().unwrap().signal(), Some(15));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kill_invalid_arguments() {
+        let own = std::process::id().to_string();
+        // Signal 0 only checks that the process exists
+        assert_eq!(cmd_kill(&[&own, "0"]), CommandResult::None);
+
+        for pid in ["abc", "0", "-1"] {
+            assert!(matches!(cmd_kill(&[pid]), CommandResult::Error(msg) if msg.to_string().contains("Invalid process id")));
+        }
+        assert!(matches!(cmd_kill(&[&own, "NOPE"]), CommandResult::Error(msg) if msg.to_string().contains("Unknown signal")));
+        assert!(!cmd_kill(&[]).is_success());
+    }
+}
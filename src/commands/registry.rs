@@ -0,0 +1,351 @@
+//! Builtin command table
+//! The one list of builtins that dispatch, tab completion and `مساعدة` read
+
+use super::builtin::{cmd_bench, cmd_cd, cmd_clear_history, cmd_clobber, cmd_complete, cmd_log, cmd_pwd, cmd_set, cmd_time, cmd_which};
+use super::{
+    cmd_basename, cmd_cat, cmd_checksum, cmd_chmod, cmd_chown, cmd_clear, cmd_clipcopy,
+    cmd_clippaste, cmd_colors, cmd_cp, cmd_cut, cmd_df, cmd_diff, cmd_dirname, cmd_du, cmd_echo,
+    cmd_eprintln, cmd_file, cmd_head, cmd_help, cmd_kill, cmd_lang, cmd_ln, cmd_ls, cmd_manual,
+    cmd_mkdir, cmd_mkfifo, cmd_mv, cmd_ps, cmd_realpath, cmd_rm, cmd_search, cmd_sed, cmd_seq,
+    cmd_sleep, cmd_stat, cmd_sync, cmd_tee, cmd_test, cmd_theme, cmd_touch, cmd_tr,
+    cmd_truncate, cmd_version, cmd_yes,
+};
+use super::BuiltinOptions;
+use crate::executor::{CommandResult, Executor};
+
+/// How a builtin is run
+#[derive(Clone, Copy)]
+pub enum Handler {
+    /// A plain command: arguments and piped input in, result out
+    Plain(fn(&[&str], Option<&str>) -> CommandResult),
+    /// A plain command that needs to know how it runs: the directory its
+    /// paths are relative to, whether it may highlight output shown on a
+    /// terminal, and whether file changes are only described (a dry run)
+    Options(fn(&[&str], Option<&str>, &BuiltinOptions) -> CommandResult),
+    /// A command that changes shell state and needs the executor
+    Shell(fn(&mut Executor, &[&str]) -> CommandResult),
+}
+
+/// Which `مساعدة` section a builtin is listed under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Basic,
+    Files,
+}
+
+/// A builtin command's names, help entry, manual page and handler
+#[derive(Clone, Copy)]
+pub struct BuiltinSpec {
+    pub arabic: &'static str,
+    pub english: &'static str,
+    /// Extra accepted names, not offered for completion (`quit`, `?`, ...)
+    pub aliases: &'static [&'static str],
+    /// Argument hint shown in help: `<>` required, `[]` optional
+    pub args: &'static str,
+    /// Arabic one-line description shown in help
+    pub summary: &'static str,
+    pub section: Section,
+    pub handler: Handler,
+    /// Usage line with the Arabic name, shown by `صفحة`
+    pub usage: &'static str,
+    /// What the command does, as (Arabic, English)
+    pub description: (&'static str, &'static str),
+    /// `(flag, Arabic, English)` for each option
+    pub options: &'static [(&'static str, &'static str, &'static str)],
+    pub example: &'static str,
+}
+
+const fn spec(
+    arabic: &'static str,
+    english: &'static str,
+    aliases: &'static [&'static str],
+    args: &'static str,
+    summary: &'static str,
+    section: Section,
+    handler: Handler,
+) -> BuiltinSpec {
+    BuiltinSpec {
+        arabic,
+        english,
+        aliases,
+        args,
+        summary,
+        section,
+        handler,
+        usage: "",
+        description: ("", ""),
+        options: &[],
+        example: "",
+    }
+}
+
+impl BuiltinSpec {
+    /// Add the manual page `صفحة` prints
+    const fn page(
+        self,
+        usage: &'static str,
+        description: (&'static str, &'static str),
+        options: &'static [(&'static str, &'static str, &'static str)],
+        example: &'static str,
+    ) -> Self {
+        BuiltinSpec { usage, description, options, example, ..self }
+    }
+}
+
+/// Every builtin, in help order
+///
+/// Adding a command here makes it runnable, completable and listed in help;
+/// its `.page(...)` is what `صفحة` shows.
+pub const BUILTINS: &[BuiltinSpec] = &[
+    spec("مساعدة", "help", &["?"], "[]", "عرض هذه المساعدة", Section::Basic, Handler::Plain(|args, _| cmd_help(args)))
+        .page("مساعدة [أمر]", ("عرض جدول الأوامر، أو صفحة أمر واحد", "Show the command table, or one command's page"), &[], "مساعدة اعرض"),
+    spec("صفحة", "man", &["manual"], "<>", "صفحة أمر وخياراته", Section::Basic, Handler::Plain(|args, _| cmd_manual(args)))
+        .page("صفحة <أمر>", ("صفحة أمر: استخدامه وخياراته ومثال", "A command's page: usage, options and an example"), &[], "صفحة انسخ"),
+    spec("خروج", "exit", &["quit"], "", "الخروج من الصدفة", Section::Basic, Handler::Plain(|_, _| CommandResult::Exit(0)))
+        .page("خروج", ("الخروج من الصدفة", "Leave the shell"), &[], "خروج"),
+    spec("امسح", "clear", &["cls"], "[-ك]", "مسح الشاشة", Section::Basic, Handler::Plain(|args, _| cmd_clear(args)))
+        .page("امسح [-ك]", ("مسح الشاشة", "Clear the screen"), &[
+            ("-ك, -a", "يمسح سجل التمرير أيضاً", "also clear the scrollback"),
+        ], "امسح -ك"),
+    spec("اصدار", "version", &[], "", "عرض الإصدار", Section::Basic, Handler::Plain(|_, _| cmd_version()))
+        .page("اصدار", ("عرض الإصدار", "Print the version"), &[], "اصدار"),
+    spec("سمة", "theme", &[], "[]", "تغيير سمة الألوان", Section::Basic, Handler::Plain(|args, _| cmd_theme(args)))
+        .page("سمة [اسم]", ("عرض السمات أو تغيير سمة الألوان", "List color themes or switch to one"), &[], "سمة فاتح"),
+    spec("الوان", "colors", &[], "", "معاينة ألوان السمة", Section::Basic, Handler::Plain(|args, _| cmd_colors(args)))
+        .page("الوان", ("معاينة ألوان السمة ومعرفة هل الألوان مفعلة", "Preview the theme's colors and show whether color is on"), &[], "الوان"),
+    spec("لغة", "lang", &[], "[]", "اختيار لغة الرسائل", Section::Basic, Handler::Plain(|args, _| cmd_lang(args)))
+        .page("لغة [اسم]", ("عرض لغات الرسائل أو اختيار لغة", "List message languages or pick one"), &[], "لغة انجليزي"),
+    spec("سجل", "log", &[], "[]", "تسجيل وقت المخرجات", Section::Basic, Handler::Shell(cmd_log))
+        .page("سجل [تشغيل|ايقاف]", ("إضافة الوقت قبل كل سطر من المخرجات", "Prefix output lines with a timestamp"), &[], "سجل تشغيل"),
+    spec("اختبار", "test", &[], "<>", "اختبار شرط (مع اذا)", Section::Basic, Handler::Options(|args, _, options| cmd_test(args, options)))
+        .page("اختبار <شرط>", ("اختبار شرط؛ يفشل بصمت إذا كان خاطئاً", "Check a condition; fails silently when false"), &[
+            ("-e <مسار>", "المسار موجود", "the path exists"),
+            ("-f <مسار>", "ملف عادي", "a regular file"),
+            ("-d <مسار>", "مجلد", "a directory"),
+            ("-s <مسار>", "ملف غير فارغ", "a non-empty file"),
+            ("-z <نص>", "نص فارغ", "empty text"),
+            ("-n <نص>", "نص غير فارغ", "non-empty text"),
+            ("a = b", "النصان متساويان (و != للاختلاف)", "the texts are equal (!= for unequal)"),
+            ("a -lt b", "مقارنة أرقام (-eq -ne -gt -ge -le)", "compare numbers (-eq -ne -gt -ge -le)"),
+        ], "اذا اختبار -f ملف ; ثم اقرأ ملف ; انتهى"),
+    spec("نم", "sleep", &[], "<>", "الانتظار لعدد من الثواني", Section::Basic, Handler::Plain(|args, _| cmd_sleep(args)))
+        .page("نم <ثوان>", ("الانتظار لعدد من الثواني (يقبل الكسور والأرقام العربية)", "Pause for N seconds (fractions and Arabic-Indic digits work)"), &[], "نم ٢٫٥"),
+    spec("كرر_نص", "yes", &["مكرر"], "[-n]", "تكرار سطر عدة مرات", Section::Basic, Handler::Plain(|args, _| cmd_yes(args)))
+        .page("كرر_نص [-n عدد] [نص]", ("تكرار سطر، 10 مرات افتراضياً", "Repeat a line, 10 times by default"), &[
+            ("-n <عدد>", "عدد مرات التكرار", "how many times"),
+        ], "كرر_نص -n 3 مرحبا"),
+    spec("تسلسل", "seq", &[], "<>", "طباعة تسلسل أرقام", Section::Basic, Handler::Plain(|args, _| cmd_seq(args)))
+        .page("تسلسل [بداية] [خطوة] نهاية", ("طباعة الأرقام من البداية إلى النهاية، سطراً لكل رقم", "Print numbers from FIRST to LAST, one per line"), &[], "تسلسل 10 -2 0"),
+    spec("عمليات", "ps", &[], "", "العمليات الجارية", Section::Basic, Handler::Plain(|args, _| cmd_ps(args)))
+        .page("عمليات", ("عرض العمليات الجارية (المعرف والاسم، على لينكس)", "List running processes (pid and name; Linux)"), &[], "عمليات | ابحث ocean"),
+    spec("اقتل", "kill", &[], "<>", "إرسال إشارة لعملية", Section::Basic, Handler::Plain(|args, _| cmd_kill(args)))
+        .page("اقتل <معرف> [إشارة]", ("إرسال إشارة إلى عملية، TERM افتراضياً", "Send a signal to a process, TERM by default"), &[], "اقتل 1234 KILL"),
+    spec("واجهة", "which", &[], "<>", "مسار الأمر الخارجي", Section::Basic, Handler::Shell(cmd_which))
+        .page("واجهة <أمر...>", ("مسار الأمر الخارجي في PATH", "Where an external command resolves on PATH"), &[], "واجهة git"),
+    spec("امسح_فوق", "clobber", &[], "[]", "الكتابة فوق الملفات", Section::Basic, Handler::Shell(cmd_clobber))
+        .page("امسح_فوق [تشغيل|ايقاف]", ("السماح بالكتابة فوق الملفات الموجودة بـ > أو منعها", "Allow or forbid > onto existing files"), &[], "امسح_فوق ايقاف"),
+    spec("اضبط", "set", &[], "[]", "خيارات الصدفة (-e و -x و -d)", Section::Basic, Handler::Shell(cmd_set))
+        .page("اضبط [-e|+e] [-x|+x] [-d|+d]", ("تشغيل خيارات الصدفة أو إيقافها (+)؛ بدون وسائط تُعرض حالتها", "Turn shell options on, or off with +; no arguments shows them"), &[
+            ("-e", "إيقاف التسلسل عند أول أمر يفشل", "stop a sequence at the first failing command"),
+            ("-x", "طباعة كل أمر إلى الخطأ القياسي قبل تنفيذه", "print each command to stderr before it runs"),
+            ("-d", "تشغيل تجريبي: أوامر الملفات تصف ما كانت ستفعله", "dry run: file commands describe what they would do"),
+        ], "اضبط -ex"),
+    spec("وقت", "time", &[], "<>", "قياس مدة تنفيذ أمر", Section::Basic, Handler::Shell(cmd_time))
+        .page("وقت <أمر> [وسائط...]", ("تنفيذ أمر ثم طباعة مدته إلى الخطأ القياسي", "Run a command, then print how long it took to stderr"), &[], "وقت ابحث -r نمط ."),
+    spec("قياس", "bench", &["مؤقت", "benchmark"], "<>", "تشغيل أمر عدة مرات وقياس مدته", Section::Basic, Handler::Shell(cmd_bench))
+        .page("قياس [-n عدد] '<أمر>'", ("تشغيل أمر أو خط أنابيب عدة مرات وطباعة أدنى وأقصى ومتوسط مدته", "Run a command or pipeline several times and print its min, max and mean time"), &[
+            ("-n <عدد>", "عدد مرات التشغيل (10 افتراضياً)", "how many runs (10 by default)"),
+        ], "قياس -n 5 'اقرأ سجل.txt | رأس -n 1'"),
+    spec("اكمل", "complete", &[], "[]", "إضافة كلمات للإكمال التلقائي", Section::Basic, Handler::Shell(cmd_complete))
+        .page("اكمل [-r] <أمر> [كلمة...]", ("تسجيل كلمات يقترحها الإكمال التلقائي لوسائط أمر", "Register words Tab offers for a command's arguments"), &[
+            ("-r <أمر...>", "حذف الكلمات المسجلة", "remove the registered words"),
+        ], "اكمل git status commit push"),
+    spec("امسح_ذاكرة", "clear-history", &[], "[-f]", "مسح سجل الأوامر", Section::Basic, Handler::Shell(cmd_clear_history))
+        .page("امسح_ذاكرة [-f]", ("مسح سجل الأوامر بعد التأكيد", "Wipe the command history after asking"), &[
+            ("-f", "دون سؤال", "don't ask"),
+        ], "امسح_ذاكرة"),
+
+    spec("اطبع", "echo", &[], "<>", "طباعة نص", Section::Files, Handler::Plain(cmd_echo))
+        .page("اطبع <نص...>", ("طباعة نص، أو تمرير المدخلات المنقولة بالأنبوب", "Print text, or pass piped input on"), &[], "اطبع مرحبا بالعالم"),
+    spec("صدى_خطأ", "eprintln", &[], "<>", "طباعة إلى الخطأ القياسي", Section::Files, Handler::Options(cmd_eprintln))
+        .page("صدى_خطأ <نص...>", ("طباعة نص إلى الخطأ القياسي", "Print text to stderr"), &[], "صدى_خطأ تحذير"),
+    spec("نسخ_حافظة", "clipcopy", &[], "[]", "نسخ المدخلات إلى الحافظة", Section::Files, Handler::Plain(cmd_clipcopy))
+        .page("<أمر> | نسخ_حافظة", ("نسخ المدخلات المنقولة بالأنبوب أو الوسائط إلى الحافظة", "Copy piped input, or the arguments, to the clipboard"), &[], "اين | نسخ_حافظة"),
+    spec("الصق_حافظة", "clippaste", &[], "", "طباعة محتوى الحافظة", Section::Files, Handler::Plain(|args, _| cmd_clippaste(args)))
+        .page("الصق_حافظة", ("طباعة محتوى الحافظة", "Print the clipboard's text"), &[], "الصق_حافظة > ملاحظة.txt"),
+    spec("اين", "pwd", &[], "", "المسار الحالي", Section::Files, Handler::Shell(cmd_pwd))
+        .page("اين", ("عرض المسار الحالي", "Print the current directory"), &[], "اين"),
+    spec("انتقل", "cd", &[], "<>", "الانتقال إلى مجلد", Section::Files, Handler::Shell(cmd_cd))
+        .page("انتقل [مسار]", ("الانتقال إلى مجلد، أو إلى مجلد المنزل بدون وسيط", "Change directory, or go home with no argument"), &[], "انتقل ~/مشاريع"),
+    spec("اعرض", "ls", &["dir"], "[]", "عرض الملفات", Section::Files, Handler::Options(|args, _, options| cmd_ls(args, options)))
+        .page("اعرض [-l [-h]] [-t|-S] [-r] [-R] [--json] [-L|-P] [مسار...]", ("عرض محتويات المجلد، المجلدات أولاً", "List directory contents, directories first"), &[
+            ("-l", "تنسيق طويل: الصلاحيات والحجم ووقت التعديل", "long format: mode, size and modification time"),
+            ("-h", "أحجام مقروءة (KB، MB) مع -l", "human-readable sizes (KB, MB) with -l"),
+            ("-t", "الأحدث أولاً", "newest first"),
+            ("-S", "الأكبر أولاً", "largest first"),
+            ("-r", "عكس الترتيب", "reverse the order"),
+            ("-R", "عرض المجلدات الفرعية كلاً تحت عنوانه", "list each subdirectory under its own header"),
+            ("--json", "مخرج JSON بدون ألوان", "JSON output without colors"),
+            ("-L", "وصف ما تشير إليه الروابط الرمزية", "describe what symlinks point to"),
+            ("-P", "وصف الروابط نفسها (الافتراضي)", "describe the links themselves (default)"),
+        ], "اعرض -l -h -t"),
+    spec("اقرأ", "cat", &[], "<>", "قراءة محتوى ملف", Section::Files, Handler::Options(cmd_cat))
+        .page("اقرأ [-b] <ملف...>", ("قراءة محتوى الملفات", "Print the contents of files"), &[
+            ("-b", "عرض الملفات الثنائية", "show binary files"),
+        ], "اقرأ ملاحظات.txt"),
+    spec("حفظ", "tee", &[], "<>", "حفظ المدخلات في ملف وتمريرها", Section::Files, Handler::Options(cmd_tee))
+        .page("<أمر> | حفظ [-a] <ملف...>", ("حفظ المدخلات المنقولة بالأنبوب في ملفات وتمريرها", "Save piped input to files and pass it on"), &[
+            ("-a", "الإلحاق بدلاً من الكتابة فوق الملف", "append instead of overwriting"),
+        ], "اقرأ سجل | ابحث خطأ | حفظ أخطاء.txt"),
+    spec("رأس", "head", &[], "[]", "أول أسطر الملف", Section::Files, Handler::Options(cmd_head))
+        .page("رأس [-n عدد] [ملف...]", ("أول أسطر الملفات أو المدخلات المنقولة بالأنبوب", "The first lines of files or piped input"), &[
+            ("-n <عدد>", "عدد الأسطر (10 افتراضياً)", "how many lines (10 by default)"),
+        ], "رأس -n 5 سجل.txt"),
+    spec("انشئ", "mkdir", &[], "<>", "إنشاء مجلد", Section::Files, Handler::Options(|args, _, options| cmd_mkdir(args, options)))
+        .page("انشئ [-p] [-v] <مجلد...>", ("إنشاء مجلدات", "Create directories"), &[
+            ("-p", "إنشاء المجلدات الوسيطة وقبول الموجودة", "create parents and accept existing ones"),
+            ("-ت, -v", "عرض ما أُنشئ", "report what was created"),
+        ], "انشئ -p مشروع/مصدر"),
+    spec("المس", "touch", &[], "<>", "إنشاء ملف فارغ", Section::Files, Handler::Options(|args, _, options| cmd_touch(args, options)))
+        .page("المس <ملف...>", ("إنشاء ملفات فارغة", "Create empty files"), &[], "المس جديد.txt"),
+    spec("افرغ", "truncate", &[], "<>", "تفريغ ملف أو تغيير حجمه", Section::Files, Handler::Options(|args, _, options| cmd_truncate(args, options)))
+        .page("افرغ [-s حجم] <ملف...>", ("تفريغ ملفات أو تغيير حجمها، وإنشاؤها إن لم توجد", "Empty files or resize them, creating missing ones"), &[
+            ("-s <حجم>", "الحجم الجديد بالبايت", "the new size in bytes"),
+        ], "افرغ سجل.txt"),
+    spec("احذف", "rm", &[], "<>", "حذف ملف", Section::Files, Handler::Options(|args, _, options| cmd_rm(args, options)))
+        .page("احذف [-v] <مسار...>", ("حذف ملفات أو مجلدات", "Remove files or directories"), &[
+            ("-ت, -v", "عرض ما حُذف", "report what was removed"),
+            ("--force-dangerous", "السماح بحذف / والمجلد الرئيسي والحالي", "allow removing /, home and the current directory"),
+        ], "احذف *.tmp"),
+    spec("انسخ", "cp", &[], "<> <>", "نسخ ملف", Section::Files, Handler::Options(|args, _, options| cmd_cp(args, options)))
+        .page("انسخ [-b|-n] [-ح] [--تقدم] <من> <إلى>", ("نسخ ملف", "Copy a file"), &[
+            ("-b", "نسخة احتياطية ~ من الهدف الموجود", "back up an existing target as ~"),
+            ("-n", "عدم الكتابة فوق هدف موجود", "don't overwrite an existing target"),
+            ("-ح, -p", "حفظ الصلاحيات والأوقات", "preserve permissions and times"),
+            ("-ت, -v", "عرض ما نُسخ", "report what was copied"),
+            ("--تقدم", "عرض النسبة المنسوخة", "show progress"),
+        ], "انسخ -b تقرير.txt تقرير-قديم.txt"),
+    spec("انقل", "mv", &[], "<> <>", "نقل ملف", Section::Files, Handler::Options(|args, _, options| cmd_mv(args, options)))
+        .page("انقل [-b|-n] <من> <إلى>", ("نقل ملف أو إعادة تسميته", "Move or rename a file"), &[
+            ("-b", "نسخة احتياطية ~ من الهدف الموجود", "back up an existing target as ~"),
+            ("-n", "عدم الكتابة فوق هدف موجود", "don't overwrite an existing target"),
+            ("-ت, -v", "عرض ما نُقل", "report what was moved"),
+        ], "انقل قديم.txt جديد.txt"),
+    spec("ابحث", "grep", &["search"], "<>", "البحث في النص", Section::Files, Handler::Options(cmd_search))
+        .page("ابحث [-r] [-L|-P] <نمط> [ملف...]", ("البحث عن نمط في الملفات أو المدخلات", "Search files or piped input for a pattern"), &[
+            ("-r", "البحث في المجلدات بشكل متكرر", "search directories recursively"),
+            ("-L", "اتباع الروابط الرمزية مع كشف الحلقات", "follow symlinks, skipping loops"),
+        ], "ابحث -r TODO مصدر"),
+    spec("بدل", "tr", &[], "<>", "ترجمة الأحرف", Section::Files, Handler::Plain(cmd_tr))
+        .page("بدل [-d] <مجموعة1> [مجموعة2]", ("استبدال الأحرف في المدخلات أو حذفها", "Translate or delete characters in piped input"), &[
+            ("-d", "حذف أحرف المجموعة الأولى", "delete the characters of the first set"),
+        ], "اطبع مرحبا | بدل ا-ي a-z"),
+    spec("قص", "cut", &[], "<>", "استخراج الحقول", Section::Files, Handler::Options(cmd_cut))
+        .page("قص [-d فاصل] -f قائمة | -c قائمة [ملف...]", ("استخراج حقول أو أحرف من كل سطر", "Extract fields or characters from each line"), &[
+            ("-f <قائمة>", "الحقول المطلوبة مثل 1,3-5", "the fields to keep, like 1,3-5"),
+            ("-d <فاصل>", "فاصل الحقول (Tab افتراضياً)", "the field delimiter (tab by default)"),
+            ("-c <قائمة>", "الأحرف المطلوبة", "the characters to keep"),
+        ], "قص -d : -f 1 /etc/passwd"),
+    spec("استبدل", "sed", &[], "<>", "استبدال نص في كل سطر", Section::Files, Handler::Options(cmd_sed))
+        .page("استبدل [-i] 's/نمط/بديل/[g]' [ملف...]", ("استبدال نص بتعبير نمطي في كل سطر", "Substitute a regular expression on each line"), &[
+            ("-i", "تعديل الملفات نفسها", "edit the files in place"),
+            ("g", "استبدال كل التطابقات في السطر", "replace every match on the line"),
+        ], "استبدل -i 's/قديم/جديد/g' ملف.txt"),
+    spec("مقارنة", "diff", &[], "<>", "مقارنة ملفين سطراً بسطر", Section::Files, Handler::Shell(cmd_diff))
+        .page("مقارنة <ملف1> <ملف2>", ("مقارنة ملفين سطراً بسطر؛ يفشل إذا اختلفا", "Compare two files line by line; fails when they differ"), &[], "مقارنة قديم.txt جديد.txt"),
+    spec("صلاحيات", "chmod", &[], "", "تغيير صلاحيات الملف", Section::Files, Handler::Options(|args, _, options| cmd_chmod(args, options)))
+        .page("صلاحيات [-R] <وضع> <ملف>", ("تغيير صلاحيات الملفات", "Change a file's permissions"), &[
+            ("-R", "لكل ما في المجلد", "for everything beneath a directory"),
+        ], "صلاحيات 755 سكربت.sh"),
+    spec("مالك", "chown", &[], "<>", "تغيير مالك الملف", Section::Files, Handler::Options(|args, _, options| cmd_chown(args, options)))
+        .page("مالك [-R] <مستخدم[:مجموعة]> <ملف>", ("تغيير مالك الملفات", "Change a file's owner"), &[
+            ("-R", "لكل ما في المجلد", "for everything beneath a directory"),
+        ], "مالك -R ocean:ocean مشروع"),
+    spec("رابط", "ln", &["link"], "<>", "إنشاء رابط", Section::Files, Handler::Options(|args, _, options| cmd_ln(args, options)))
+        .page("رابط [-s] <مصدر> <هدف>", ("إنشاء رابط صلب أو رمزي", "Create a hard or symbolic link"), &[
+            ("-s, -ر", "رابط رمزي", "a symbolic link"),
+        ], "رابط -s ~/مشروع اختصار"),
+    spec("مسار", "realpath", &[], "<>", "المسار المطلق", Section::Files, Handler::Options(|args, _, options| cmd_realpath(args, options)))
+        .page("مسار [-m] <مسار...>", ("عرض المسار المطلق", "Print the canonical absolute path"), &[
+            ("-m, -ن", "قبول المسارات غير الموجودة", "accept paths that don't exist"),
+        ], "مسار ../ملف.txt"),
+    spec("اسم_الملف", "basename", &[], "<>", "اسم الملف من المسار", Section::Files, Handler::Plain(|args, _| cmd_basename(args)))
+        .page("اسم_الملف <مسار> [لاحقة]", ("اسم الملف من المسار، دون اللاحقة إن أُعطيت", "The final path component, minus the suffix if given"), &[], "اسم_الملف /مجلد/ملف.txt .txt"),
+    spec("اسم_المجلد", "dirname", &[], "<>", "المجلد الأب للمسار", Section::Files, Handler::Plain(|args, _| cmd_dirname(args)))
+        .page("اسم_المجلد <مسار...>", ("المجلد الأب للمسار", "The parent directory of a path"), &[], "اسم_المجلد /مجلد/ملف.txt"),
+    spec("حجم", "du", &[], "[-h]", "حجم الملفات والمجلدات", Section::Files, Handler::Options(|args, _, options| cmd_du(args, options)))
+        .page("حجم [-h] [مسار...]", ("الحجم الكلي للملفات والمجلدات", "Total size of files and directories"), &[
+            ("-h", "بوحدات مقروءة (KB، MB)", "human-readable units (KB, MB)"),
+        ], "حجم -h ."),
+    spec("مساحة", "df", &[], "[]", "المساحة الحرة على القرص", Section::Files, Handler::Options(|args, _, options| cmd_df(args, options)))
+        .page("مساحة [مسار]", ("المساحة الكلية والمستخدمة والمتاحة لنظام الملفات", "Total, used and available space of a filesystem"), &[], "مساحة /"),
+    spec("احصاء", "stat", &[], "<>", "معلومات الملف", Section::Files, Handler::Options(|args, _, options| cmd_stat(args, options)))
+        .page("احصاء <ملف...>", ("معلومات الملف: الحجم والصلاحيات والمالك والأوقات", "File details: size, permissions, owner and times"), &[], "احصاء ملف.txt"),
+    spec("راس_ملف", "file", &[], "<>", "نوع الملف من محتواه", Section::Files, Handler::Options(|args, _, options| cmd_file(args, options)))
+        .page("راس_ملف <ملف...>", ("نوع الملف من أول بايتاته", "A file's type, guessed from its first bytes"), &[], "راس_ملف صورة.png"),
+    spec("تحقق", "checksum", &[], "[]", "حساب المجموع الاختباري للملفات", Section::Files, Handler::Options(cmd_checksum))
+        .page("تحقق [-a sha256|md5] [ملف...]", ("المجموع الاختباري لكل ملف أو للمدخلات المنقولة بالأنبوب", "The checksum of each file, or of piped input"), &[
+            ("-a <خوارزمية>", "sha256 (الافتراضي) أو md5", "sha256 (default) or md5"),
+        ], "تحقق -a md5 ملف.iso"),
+    spec("انبوب_مسمى", "mkfifo", &[], "<>", "إنشاء أنبوب مسمى", Section::Files, Handler::Options(|args, _, options| cmd_mkfifo(args, options)))
+        .page("انبوب_مسمى <مسار> [وضع]", ("إنشاء أنبوب مسمى (644 افتراضياً)", "Create a named pipe (mode 644 by default)"), &[], "انبوب_مسمى /tmp/قناة"),
+    spec("مزامنة", "sync", &["نظف"], "", "كتابة البيانات المؤقتة إلى القرص", Section::Files, Handler::Plain(|args, _| cmd_sync(args)))
+        .page("مزامنة", ("كتابة البيانات المؤقتة إلى القرص", "Flush filesystem buffers to disk"), &[], "مزامنة"),
+];
+
+/// `(arabic, english)` pairs of [`BUILTINS`], built at compile time
+const NAMES: [(&str, &str); BUILTINS.len()] = {
+    let mut names = [("", ""); BUILTINS.len()];
+    let mut i = 0;
+    while i < BUILTINS.len() {
+        names[i] = (BUILTINS[i].arabic, BUILTINS[i].english);
+        i += 1;
+    }
+    names
+};
+
+/// The `(arabic, english)` names of every builtin command
+pub fn builtin_commands() -> &'static [(&'static str, &'static str)] {
+    &NAMES
+}
+
+/// Look up a builtin by its Arabic name, English name or an alias
+pub fn find_builtin(name: &str) -> Option<&'static BuiltinSpec> {
+    BUILTINS
+        .iter()
+        .find(|spec| spec.arabic == name || spec.english == name || spec.aliases.contains(&name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut seen = HashSet::new();
+        for spec in BUILTINS {
+            for name in [spec.arabic, spec.english].iter().chain(spec.aliases) {
+                assert!(seen.insert(*name), "duplicate builtin name '{}'", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_builtin_by_any_name() {
+        assert_eq!(find_builtin("اطبع").map(|s| s.english), Some("echo"));
+        assert_eq!(find_builtin("echo").map(|s| s.arabic), Some("اطبع"));
+        assert_eq!(find_builtin("مكرر").map(|s| s.english), Some("yes"));
+        assert!(find_builtin("ocean-no-such-command").is_none());
+        assert_eq!(builtin_commands().len(), BUILTINS.len());
+    }
+
+    #[test]
+    fn test_aliases_dispatch() {
+        use crate::commands::execute_builtin;
+        assert_eq!(execute_builtin("quit", &[], None), Some(CommandResult::Exit(0)));
+        assert_eq!(execute_builtin("خروج", &[], None), Some(CommandResult::Exit(0)));
+        // Shell-state builtins are left to `execute_shell_builtin`
+        assert_eq!(execute_builtin("log", &[], None), None);
+    }
+}
@@ -0,0 +1,786 @@
+//! Text-processing commands
+//! Filters that transform piped input or files, like `بدل` (tr), `قص` (cut),
+//! `استبدل` (sed) and `رأس` (head), and the `مقارنة` (diff) file comparison
+
+use super::BuiltinOptions;
+use crate::executor::{CommandError, CommandResult, Executor, FileOp};
+use crate::utils::{active_theme, expand_tilde, normalize_digits, paint};
+
+use regex::Regex;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+
+/// Translate or delete characters in piped input (`بدل` / tr)
+///
+/// `بدل SET1 SET2` replaces each character of SET1 with the character at the
+/// same position in SET2 (the last character of SET2 repeats if it is
+/// shorter); `بدل -d SET1` deletes them instead. Sets may contain ranges
+/// like `a-z` or `ا-ي` and the escapes `\n`, `\t`, `\\` and `\-`. Mapping is
+/// per character, so Arabic letters translate like any other.
+pub fn cmd_tr(args: &[&str], input: Option<&str>) -> CommandResult {
+    let delete = args.first() == Some(&"-d");
+    let sets = if delete { &args[1..] } else { args };
+
+    if (delete && sets.len() != 1) || (!delete && sets.len() != 2) {
+        return CommandError::usage(
+            ("يرجى تحديد مجموعتي الأحرف", "Please specify the character sets"),
+            ("بدل <مجموعة1> <مجموعة2> | بدل -d <مجموعة>", "tr <set1> <set2> | tr -d <set>"),
+        ).into();
+    }
+
+    let Some(input) = input else {
+        return CommandError::failed(("بدل يعمل على مدخل الأنبوب فقط", "tr only works on piped input")).into();
+    };
+
+    let from = match expand_set(sets[0]) {
+        Ok(set) => set,
+        Err(e) => return e.into(),
+    };
+
+    if delete {
+        return CommandResult::Success(input.chars().filter(|c| !from.contains(c)).collect());
+    }
+
+    let to = match expand_set(sets[1]) {
+        Ok(set) => set,
+        Err(e) => return e.into(),
+    };
+    let Some(&last) = to.last() else {
+        return CommandError::failed(("المجموعة الثانية فارغة", "The second set is empty")).into();
+    };
+
+    let output = input
+        .chars()
+        .map(|c| match from.iter().position(|&f| f == c) {
+            Some(i) => to.get(i).copied().unwrap_or(last),
+            None => c,
+        })
+        .collect();
+    CommandResult::Success(output)
+}
+
+/// Expand a `tr` set spec into its characters, resolving ranges and escapes
+fn expand_set(spec: &str) -> Result<Vec<char>, CommandError> {
+    let mut chars = Vec::new();
+    let mut source = spec.chars().peekable();
+    while let Some(c) = source.next() {
+        let c = if c == '\\' { unescape(source.next()) } else { c };
+
+        // `a-z` is a range; a leading or trailing `-` is literal
+        if source.peek() == Some(&'-') {
+            let mut lookahead = source.clone();
+            lookahead.next();
+            if let Some(end) = lookahead.next() {
+                let end = if end == '\\' { unescape(lookahead.next()) } else { end };
+                if end < c {
+                    return Err(CommandError::failed((
+                        format!("نطاق غير صالح '{}-{}'", c, end),
+                        format!("Invalid range '{}-{}'", c, end),
+                    )));
+                }
+                chars.extend(c..=end);
+                source = lookahead;
+                continue;
+            }
+        }
+        chars.push(c);
+    }
+    Ok(chars)
+}
+
+fn unescape(c: Option<char>) -> char {
+    match c {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some(other) => other,
+        None => '\\',
+    }
+}
+
+/// What `قص` extracts from each line
+enum CutMode {
+    Fields(Vec<(usize, usize)>),
+    Chars(Vec<(usize, usize)>),
+}
+
+/// Extract fields or character ranges from each line (`قص` / cut)
+///
+/// `-f LIST` selects fields split on `-d DELIM` (default tab) and joins them
+/// with the same delimiter; lines without the delimiter pass through whole.
+/// `-c LIST` selects characters. A LIST is comma-separated `N`, `N-M`, `N-`
+/// or `-M` (1-based). Reads the given files, or piped input.
+pub fn cmd_cut(args: &[&str], input: Option<&str>, options: &BuiltinOptions) -> CommandResult {
+    let mut delimiter = '\t';
+    let mut mode = None;
+    let mut files = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        // Options take their value attached (`-d:`) or as the next argument
+        let (flag, attached) = match arg {
+            "-d" | "-f" | "-c" => (arg, None),
+            _ if arg.len() > 2 && (arg.starts_with("-d") || arg.starts_with("-f") || arg.starts_with("-c")) => {
+                (&arg[..2], Some(&arg[2..]))
+            }
+            _ => {
+                files.push(arg);
+                continue;
+            }
+        };
+        let Some(value) = attached.or_else(|| iter.next().copied()) else {
+            return cut_usage(&format!("الخيار {} يتطلب قيمة", flag), &format!("{} requires a value", flag));
+        };
+
+        match flag {
+            "-d" => {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => delimiter = c,
+                    _ => return cut_usage("الفاصل يجب أن يكون حرفاً واحداً", "The delimiter must be a single character"),
+                }
+            }
+            _ => {
+                let Some(ranges) = parse_ranges(value) else {
+                    return cut_usage(&format!("قائمة غير صالحة '{}'", value), &format!("Invalid list '{}'", value));
+                };
+                mode = Some(if flag == "-f" { CutMode::Fields(ranges) } else { CutMode::Chars(ranges) });
+            }
+        }
+    }
+
+    let Some(mode) = mode else {
+        return cut_usage("يرجى تحديد -f أو -c", "Please specify -f or -c");
+    };
+
+    let mut text = String::new();
+    if files.is_empty() {
+        match input {
+            Some(inp) => text.push_str(inp),
+            None => return cut_usage("يرجى تحديد ملف أو استخدام الأنبوب", "Please specify a file or use pipe"),
+        }
+    }
+    for file in files {
+        match fs::read_to_string(options.path(file)) {
+            Ok(content) => text.push_str(&content),
+            Err(e) => {
+                return CommandError::file(FileOp::Read, file, e).into();
+            }
+        }
+    }
+
+    let mut output = String::new();
+    for line in text.lines() {
+        match &mode {
+            CutMode::Fields(ranges) if line.contains(delimiter) => {
+                let fields: Vec<&str> = line
+                    .split(delimiter)
+                    .enumerate()
+                    .filter(|(i, _)| in_ranges(ranges, i + 1))
+                    .map(|(_, field)| field)
+                    .collect();
+                output.push_str(&fields.join(&delimiter.to_string()));
+            }
+            CutMode::Fields(_) => output.push_str(line),
+            CutMode::Chars(ranges) => output.extend(
+                line.chars().enumerate().filter(|(i, _)| in_ranges(ranges, i + 1)).map(|(_, c)| c),
+            ),
+        }
+        output.push('\n');
+    }
+    CommandResult::Success(output)
+}
+
+fn cut_usage(ar: &str, en: &str) -> CommandResult {
+    CommandError::usage((ar, en), ("قص [-d فاصل] -f قائمة | -c قائمة [ملف...]", "cut [-d DELIM] -f LIST | -c LIST [file...]")).into()
+}
+
+/// Parse a cut LIST like `1,3-5,7-` into inclusive 1-based ranges
+fn parse_ranges(list: &str) -> Option<Vec<(usize, usize)>> {
+    list.split(',')
+        .map(|part| {
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) => (
+                    if start.is_empty() { 1 } else { start.parse().ok()? },
+                    if end.is_empty() { usize::MAX } else { end.parse().ok()? },
+                ),
+                None => {
+                    let n = part.parse().ok()?;
+                    (n, n)
+                }
+            };
+            (start >= 1 && start <= end && part != "-").then_some((start, end))
+        })
+        .collect()
+}
+
+fn in_ranges(ranges: &[(usize, usize)], n: usize) -> bool {
+    ranges.iter().any(|&(start, end)| start <= n && n <= end)
+}
+
+/// Substitute text on each line (`استبدل` / sed)
+///
+/// `استبدل 's/PATTERN/REPLACEMENT/[g]' [file...]` replaces the first match of
+/// the regular expression on each line, or every match with `g`. Any
+/// character may stand in for `/`. In REPLACEMENT, `&` is the whole match
+/// and `\1`..`\9` are groups. With `-i` the result is written back to each
+/// file instead of printed. Reads the given files, or piped input.
+pub fn cmd_sed(args: &[&str], input: Option<&str>, options: &BuiltinOptions) -> CommandResult {
+    let in_place = args.first() == Some(&"-i");
+    let args = if in_place { &args[1..] } else { args };
+    let Some((script, files)) = args.split_first() else {
+        return sed_usage("يرجى تحديد أمر الاستبدال", "Please specify a substitution").into();
+    };
+    let substitution = match Substitution::parse(script) {
+        Ok(substitution) => substitution,
+        Err(e) => return e.into(),
+    };
+
+    if files.is_empty() {
+        return match input {
+            Some(_) if in_place => sed_usage("الخيار -i يتطلب ملفاً", "-i requires a file").into(),
+            Some(text) => CommandResult::Success(substitution.apply(text)),
+            None => sed_usage("يرجى تحديد ملف أو استخدام الأنبوب", "Please specify a file or use pipe").into(),
+        };
+    }
+
+    let mut output = String::new();
+    for file in files {
+        let path = options.path(file);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                return CommandError::file(FileOp::Read, file, e).into();
+            }
+        };
+
+        let replaced = substitution.apply(&content);
+        if !in_place {
+            output.push_str(&replaced);
+        } else if let Err(e) = fs::write(&path, replaced) {
+            return CommandError::file(FileOp::Write, file, e).into();
+        }
+    }
+
+    if in_place {
+        CommandResult::None
+    } else {
+        CommandResult::Success(output)
+    }
+}
+
+fn sed_usage(ar: &str, en: &str) -> CommandError {
+    CommandError::usage((ar, en), ("استبدل [-i] 's/نمط/بديل/[g]' [ملف...]", "sed [-i] 's/PATTERN/REPLACEMENT/[g]' [file...]"))
+}
+
+/// A parsed `s/PATTERN/REPLACEMENT/FLAGS` command
+struct Substitution {
+    pattern: Regex,
+    /// The replacement in `regex` syntax (`$0`, `${1}`)
+    replacement: String,
+    global: bool,
+}
+
+impl Substitution {
+    fn parse(script: &str) -> Result<Self, CommandError> {
+        let invalid = || sed_usage(&format!("أمر استبدال غير صالح '{}'", script), &format!("Invalid substitution '{}'", script));
+
+        let mut chars = script.chars();
+        let delimiter = match (chars.next(), chars.next()) {
+            (Some('s'), Some(c)) if c != '\\' && c != '\n' => c,
+            _ => return Err(invalid()),
+        };
+
+        // Split on unescaped delimiters; `\/` is a literal delimiter and
+        // other escapes are kept for the regex and replacement
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        let mut escaped = false;
+        for c in chars {
+            if escaped {
+                if c != delimiter {
+                    current.push('\\');
+                    current.push(c);
+                } else if parts.is_empty() {
+                    // A delimiter like `|` must stay literal in the pattern
+                    current.push_str(&regex::escape(&c.to_string()));
+                } else {
+                    current.push(c);
+                }
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == delimiter && parts.len() < 2 {
+                parts.push(std::mem::take(&mut current));
+            } else {
+                current.push(c);
+            }
+        }
+        if escaped {
+            current.push('\\');
+        }
+        let [pattern, replacement] = <[String; 2]>::try_from(parts).map_err(|_| invalid())?;
+
+        let global = match current.as_str() {
+            "" => false,
+            "g" => true,
+            flags => {
+                return Err(sed_usage(&format!("خيار غير معروف '{}'", flags), &format!("Unknown flag '{}'", flags)));
+            }
+        };
+
+        let pattern = Regex::new(&pattern).map_err(|e| {
+            CommandError::failed((
+                format!("نمط غير صالح '{}' - {}", pattern, e),
+                format!("Invalid pattern '{}' - {}", pattern, e),
+            ))
+        })?;
+        Ok(Self { pattern, replacement: regex_replacement(&replacement), global })
+    }
+
+    /// Apply the substitution to each line of `text`, keeping line endings
+    fn apply(&self, text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        for line in text.split_inclusive('\n') {
+            let (body, newline) = match line.strip_suffix('\n') {
+                Some(body) => (body, "\n"),
+                None => (line, ""),
+            };
+            let replaced = if self.global {
+                self.pattern.replace_all(body, self.replacement.as_str())
+            } else {
+                self.pattern.replace(body, self.replacement.as_str())
+            };
+            output.push_str(&replaced);
+            output.push_str(newline);
+        }
+        output
+    }
+}
+
+/// Translate a sed replacement (`&`, `\1`) into `regex` syntax (`${0}`, `${1}`)
+fn regex_replacement(sed: &str) -> String {
+    let mut output = String::with_capacity(sed.len());
+    let mut chars = sed.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => output.push_str("$$"),
+            '&' => output.push_str("${0}"),
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => output.push_str(&format!("${{{}}}", digit)),
+                Some('n') => output.push('\n'),
+                Some('t') => output.push('\t'),
+                Some('$') => output.push_str("$$"),
+                Some(other) => output.push(other),
+                None => output.push('\\'),
+            },
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// Print the first lines of files or piped input (`رأس` / head)
+///
+/// `-n N` sets how many lines (10 by default, Arabic-Indic digits work).
+/// Files are read line by line and closed after the last line wanted, so
+/// the head of a huge log costs no more than its first lines. With several
+/// files, each part starts with a `==> name <==` header.
+pub fn cmd_head(args: &[&str], input: Option<&str>, options: &BuiltinOptions) -> CommandResult {
+    let mut count = 10;
+    let mut files = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        let value = match arg {
+            "-n" => match iter.next() {
+                Some(value) => *value,
+                None => return head_usage("الخيار -n يتطلب عدداً", "-n requires a count"),
+            },
+            _ if arg.len() > 2 && arg.starts_with("-n") => &arg[2..],
+            _ => {
+                files.push(arg);
+                continue;
+            }
+        };
+        count = match normalize_digits(value).parse() {
+            Ok(count) => count,
+            Err(_) => return head_usage(&format!("عدد غير صالح '{}'", value), &format!("Invalid count '{}'", value)),
+        };
+    }
+
+    if files.is_empty() {
+        return match input {
+            Some(text) => CommandResult::Success(text.split_inclusive('\n').take(count).collect()),
+            None => head_usage("يرجى تحديد ملف أو استخدام الأنبوب", "Please specify a file or use pipe"),
+        };
+    }
+
+    let mut output = String::new();
+    for (i, file) in files.iter().enumerate() {
+        if files.len() > 1 {
+            let gap = if i == 0 { "" } else { "\n" };
+            output.push_str(&format!("{}==> {} <==\n", gap, file));
+        }
+        let read = fs::File::open(options.path(file))
+            .and_then(|f| read_lines(BufReader::new(f), count, &mut output));
+        if let Err(e) = read {
+            return CommandError::file(FileOp::Read, file, e).into();
+        }
+    }
+    CommandResult::Success(output)
+}
+
+fn head_usage(ar: &str, en: &str) -> CommandResult {
+    CommandError::usage((ar, en), ("رأس [-n عدد] [ملف...]", "head [-n COUNT] [file...]")).into()
+}
+
+/// Append up to `count` lines from `reader` to `output`, reading no further
+///
+/// Invalid UTF-8 is replaced with U+FFFD, as `اقرأ` does.
+fn read_lines(mut reader: impl BufRead, count: usize, output: &mut String) -> io::Result<()> {
+    let mut line = Vec::new();
+    for _ in 0..count {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        output.push_str(&String::from_utf8_lossy(&line));
+    }
+    Ok(())
+}
+
+/// Compare two files line by line (`مقارنة` / diff)
+///
+/// Prints the lines only in the first file prefixed with `-` and those only
+/// in the second with `+`, in file order, colored on a terminal. Identical
+/// files print nothing; differing ones set exit code 1, so `مقارنة a b &&`
+/// only continues when they match.
+pub(super) fn cmd_diff(executor: &mut Executor, args: &[&str]) -> CommandResult {
+    let [first, second] = args else {
+        return CommandError::usage(
+            ("يرجى تحديد ملفين", "Please specify two files"),
+            ("مقارنة <ملف1> <ملف2>", "diff <file1> <file2>"),
+        ).into();
+    };
+
+    let mut contents = Vec::new();
+    for file in [first, second] {
+        match fs::read_to_string(executor.resolve(expand_tilde(file))) {
+            Ok(content) => contents.push(content),
+            Err(e) => {
+                return CommandError::file(FileOp::Read, file, e).into();
+            }
+        }
+    }
+
+    let old: Vec<&str> = contents[0].lines().collect();
+    let new: Vec<&str> = contents[1].lines().collect();
+    let Some(changes) = diff_lines(&old, &new) else {
+        return CommandError::failed(("الملفان كبيران جداً للمقارنة", "Files are too large to compare")).into();
+    };
+    if changes.is_empty() {
+        return CommandResult::None;
+    }
+
+    let color = executor.color();
+    let theme = active_theme();
+    let mut output = String::new();
+    for change in changes {
+        let line = match change {
+            LineChange::Removed(line) => format!("-{}", line),
+            LineChange::Added(line) => format!("+{}", line),
+        };
+        let line = match change {
+            _ if !color => line,
+            LineChange::Removed(_) => paint(&line, theme.error, false),
+            LineChange::Added(_) => paint(&line, theme.success, false),
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+    executor.last_exit_code = 1;
+    CommandResult::Success(output)
+}
+
+/// Largest line-pair table `diff_lines` will build (about 16 MB)
+const DIFF_MAX_CELLS: usize = 4_000_000;
+
+/// A line present in only one of two files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineChange<'a> {
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The lines removed from `old` and added in `new`, in order
+///
+/// Uses the longest common subsequence of lines after trimming the common
+/// prefix and suffix. Returns `None` if what remains is too large.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Option<Vec<LineChange<'a>>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old.iter().rev().zip(new.iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+
+    let width = new.len() + 1;
+    if (old.len() + 1).saturating_mul(width) > DIFF_MAX_CELLS {
+        return None;
+    }
+
+    // lcs[i * width + j]: common lines between old[i..] and new[j..]
+    let mut lcs = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i * width + j] = if old[i] == new[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+            changes.push(LineChange::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(LineChange::Added(new[j]));
+            j += 1;
+        }
+    }
+    Some(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::execute_builtin;
+
+    #[test]
+    fn test_tr_uppercase_arabic_and_english() {
+        for name in ["بدل", "tr"] {
+            let result = execute_builtin(name, &["a-z", "A-Z"], Some("hello, world\n"));
+            assert_eq!(result, Some(CommandResult::Success("HELLO, WORLD\n".to_string())));
+        }
+    }
+
+    #[test]
+    fn test_head_counts_lines() {
+        let text = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        for name in ["رأس", "head"] {
+            let result = execute_builtin(name, &[], Some(text));
+            assert_eq!(result, Some(CommandResult::Success("1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n".to_string())));
+        }
+        assert_eq!(cmd_head(&["-n", "2"], Some(text), &BuiltinOptions::default()), CommandResult::Success("1\n2\n".to_string()));
+        assert_eq!(cmd_head(&["-n٣"], Some(text), &BuiltinOptions::default()), CommandResult::Success("1\n2\n3\n".to_string()));
+        assert_eq!(cmd_head(&["-n", "0"], Some(text), &BuiltinOptions::default()), CommandResult::Success(String::new()));
+
+        assert!(matches!(cmd_head(&["-n", "كثير"], Some(text), &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Invalid count 'كثير'")));
+        assert!(!cmd_head(&["-n"], Some(text), &BuiltinOptions::default()).is_success());
+        assert!(!cmd_head(&[], None, &BuiltinOptions::default()).is_success());
+    }
+
+    #[test]
+    fn test_head_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+        fs::write(&a, "أ\nب\nت\n").unwrap();
+        fs::write(&b, "x").unwrap();
+        let (a, b) = (a.to_string_lossy().to_string(), b.to_string_lossy().to_string());
+
+        assert_eq!(cmd_head(&["-n", "2", &a], None, &BuiltinOptions::default()), CommandResult::Success("أ\nب\n".to_string()));
+        let expected = format!("==> {} <==\nأ\n\n==> {} <==\nx", a, b);
+        assert_eq!(cmd_head(&["-n1", &a, &b], None, &BuiltinOptions::default()), CommandResult::Success(expected));
+        assert!(matches!(cmd_head(&["/nonexistent/ocean-file"], None, &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Cannot read")));
+    }
+
+    /// The file is 64 GiB (sparse, so it takes no disk space): reading all
+    /// of it would take minutes and run out of memory
+    #[test]
+    fn test_head_stops_reading_early() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("huge.log");
+        fs::write(&file, "أول\nثاني\nثالث\n").unwrap();
+        fs::OpenOptions::new().write(true).open(&file).unwrap().set_len(64 << 30).unwrap();
+
+        let result = cmd_head(&["-n", "2", &file.to_string_lossy()], None, &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("أول\nثاني\n".to_string()));
+    }
+
+    #[test]
+    fn test_tr_delete() {
+        let result = cmd_tr(&["-d", "aeiou"], Some("ocean shell\n"));
+        assert_eq!(result, CommandResult::Success("cn shll\n".to_string()));
+
+        // Arabic letters and escapes are single characters too
+        let result = cmd_tr(&["-d", "ةً\\n"], Some("مدرسةً\nكبيرة\n"));
+        assert_eq!(result, CommandResult::Success("مدرسكبير".to_string()));
+    }
+
+    #[test]
+    fn test_tr_arabic_mapping_and_short_set() {
+        let result = cmd_tr(&["أإآ", "ا"], Some("أحمد إبراهيم آمال"));
+        assert_eq!(result, CommandResult::Success("احمد ابراهيم امال".to_string()));
+    }
+
+    #[test]
+    fn test_expand_set_ranges() {
+        assert_eq!(expand_set("a-c").unwrap(), vec!['a', 'b', 'c']);
+        assert_eq!(expand_set("-a-").unwrap(), vec!['-', 'a', '-']);
+        assert_eq!(expand_set("a\\-c").unwrap(), vec!['a', '-', 'c']);
+        assert!(expand_set("z-a").is_err());
+    }
+
+    #[test]
+    fn test_tr_requires_input_and_sets() {
+        assert!(!cmd_tr(&["a", "b"], None).is_success());
+        assert!(!cmd_tr(&["a"], Some("a")).is_success());
+        assert!(!cmd_tr(&["-d"], Some("a")).is_success());
+    }
+
+    #[test]
+    fn test_cut_fields_arabic_and_english() {
+        let input = "root:x:0:0:root:/root:/bin/bash\nمحيط:x:1000:1000::/home/ocean:/bin/ocean\n";
+        for name in ["قص", "cut"] {
+            let result = execute_builtin(name, &["-d:", "-f1,7"], Some(input));
+            assert_eq!(
+                result,
+                Some(CommandResult::Success("root:/bin/bash\nمحيط:/bin/ocean\n".to_string()))
+            );
+        }
+
+        // Separate option values and open ranges
+        let result = cmd_cut(&["-d", ":", "-f", "6-"], Some(input), &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("/root:/bin/bash\n/home/ocean:/bin/ocean\n".to_string()));
+    }
+
+    #[test]
+    fn test_cut_fields_default_tab_and_missing_delimiter() {
+        let result = cmd_cut(&["-f2"], Some("a\tb\tc\nبلا فاصل\n"), &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("b\nبلا فاصل\n".to_string()));
+    }
+
+    #[test]
+    fn test_cut_character_ranges_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("table.txt");
+        fs::write(&file, "2024-01-15 ok\n٢٠٢٤-٠٢-٢٠ تم\n").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        let result = cmd_cut(&["-c", "1-4,12-", &path], None, &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("2024ok\n٢٠٢٤تم\n".to_string()));
+    }
+
+    #[test]
+    fn test_cut_usage_errors() {
+        assert!(!cmd_cut(&["-d:"], Some("a:b"), &BuiltinOptions::default()).is_success());
+        assert!(!cmd_cut(&["-d", "::", "-f1"], Some("a:b"), &BuiltinOptions::default()).is_success());
+        assert!(!cmd_cut(&["-f", "0"], Some("a"), &BuiltinOptions::default()).is_success());
+        assert!(!cmd_cut(&["-c", "3-1"], Some("a"), &BuiltinOptions::default()).is_success());
+        assert!(!cmd_cut(&["-f1"], None, &BuiltinOptions::default()).is_success());
+    }
+
+    #[test]
+    fn test_sed_single_and_global_replacement() {
+        let input = "أ ب أ\nfoo foo\n";
+        for name in ["استبدل", "sed"] {
+            let result = execute_builtin(name, &["s/أ/ج/"], Some(input));
+            assert_eq!(result, Some(CommandResult::Success("ج ب أ\nfoo foo\n".to_string())));
+        }
+        let result = cmd_sed(&["s/foo/bar/g"], Some(input), &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("أ ب أ\nbar bar\n".to_string()));
+    }
+
+    #[test]
+    fn test_sed_regex_groups_and_delimiters() {
+        let result = cmd_sed(&[r"s/(\w+)@(\w+)/\2 [&] $1/"], Some("user@host"), &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("host [user@host] $1".to_string()));
+
+        // Another delimiter, with an escaped one inside the pattern
+        let result = cmd_sed(&[r"s|/usr\|/bin|/opt|g"], Some("/usr|/bin/ls\n"), &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("/opt/ls\n".to_string()));
+        let result = cmd_sed(&[r"s/\//-/g"], Some("a/b/c"), &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("a-b-c".to_string()));
+    }
+
+    #[test]
+    fn test_sed_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "مرحبا يا عالم\nيا صديقي\n").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        let result = cmd_sed(&["s/يا //", &path], None, &BuiltinOptions::default());
+        assert_eq!(result, CommandResult::Success("مرحبا عالم\nصديقي\n".to_string()));
+        assert_eq!(cmd_sed(&["-i", "s/يا //", &path], None, &BuiltinOptions::default()), CommandResult::None);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "مرحبا عالم\nصديقي\n");
+    }
+
+    #[test]
+    fn test_sed_errors() {
+        for script in ["x/a/b/", "s/a/b", "s", "s/a/b/gq", "s/(/x/"] {
+            assert!(!cmd_sed(&[script], Some("a"), &BuiltinOptions::default()).is_success(), "{}", script);
+        }
+        assert!(!cmd_sed(&[], Some("a"), &BuiltinOptions::default()).is_success());
+        assert!(!cmd_sed(&["s/a/b/"], None, &BuiltinOptions::default()).is_success());
+        assert!(!cmd_sed(&["-i", "s/a/b/"], Some("a"), &BuiltinOptions::default()).is_success());
+        assert!(matches!(cmd_sed(&["s/a/b/", "/nonexistent/ocean"], None, &BuiltinOptions::default()), CommandResult::Error(msg) if msg.to_string().contains("Cannot read")));
+    }
+
+    #[test]
+    fn test_diff_identical_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+        fs::write(&a, "سطر\nline\n").unwrap();
+        fs::write(&b, "سطر\nline\n").unwrap();
+        let (a, b) = (a.to_string_lossy().to_string(), b.to_string_lossy().to_string());
+
+        let mut executor = Executor::default();
+        for name in ["مقارنة", "diff"] {
+            let line = format!("{} {} {} && اطبع متطابقان", name, a, b);
+            assert_eq!(executor.execute_str(&line).unwrap(), CommandResult::Success("متطابقان\n".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_diff_differing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+        fs::write(&a, "أ\nب\nج\nد\n").unwrap();
+        fs::write(&b, "أ\nج\nهـ\nد\nو\n").unwrap();
+        let (a, b) = (a.to_string_lossy().to_string(), b.to_string_lossy().to_string());
+
+        let mut executor = Executor::default();
+        let result = cmd_diff(&mut executor, &[&a, &b]);
+        assert_eq!(result, CommandResult::Success("-ب\n+هـ\n+و\n".to_string()));
+        assert_eq!(executor.last_exit_code, 1);
+
+        // The difference counts as failure, but the diff is still output
+        let out = dir.path().join("out.diff");
+        let line = format!("diff {} {} > {} || اطبع مختلفان", a, b, out.display());
+        assert_eq!(executor.execute_str(&line).unwrap(), CommandResult::Success("مختلفان\n".to_string()));
+        assert_eq!(fs::read_to_string(&out).unwrap(), "-ب\n+هـ\n+و\n");
+    }
+
+    #[test]
+    fn test_diff_lines_lcs() {
+        use LineChange::{Added, Removed};
+        assert_eq!(diff_lines(&["a", "b"], &["a", "b"]), Some(vec![]));
+        assert_eq!(diff_lines(&[], &["a"]), Some(vec![Added("a")]));
+        assert_eq!(diff_lines(&["x", "a", "b", "c"], &["a", "c", "y"]), Some(vec![Removed("x"), Removed("b"), Added("y")]));
+        assert_eq!(diff_lines(&["a", "b"], &["b", "a"]), Some(vec![Removed("a"), Added("a")]));
+    }
+
+    #[test]
+    fn test_diff_usage_errors() {
+        let mut executor = Executor::default();
+        assert!(!cmd_diff(&mut executor, &["one"]).is_success());
+        let result = cmd_diff(&mut executor, &["/nonexistent/a", "/nonexistent/b"]);
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("Cannot read")));
+    }
+}
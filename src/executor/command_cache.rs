@@ -0,0 +1,154 @@
+//! # Command Cache (ذاكرة الأوامر)
+//!
+//! Remembers where external commands were found on `PATH`, so repeated
+//! commands skip the directory scan. The cache is filled lazily and emptied
+//! whenever `PATH` changes.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Resolved absolute paths of external commands, keyed by name
+#[derive(Debug, Default)]
+pub struct CommandCache {
+    /// The `PATH` value the entries were resolved against
+    path: Option<OsString>,
+    entries: HashMap<String, PathBuf>,
+    /// Number of `PATH` scans performed (cache misses)
+    scans: usize,
+}
+
+impl CommandCache {
+    /// Resolve `name` against the current `PATH`
+    ///
+    /// Names containing a path separator are never looked up or cached.
+    /// Misses aren't cached, so a newly installed command is found on its
+    /// next use.
+    pub fn resolve(&mut self, name: &str) -> Option<PathBuf> {
+        self.resolve_in(name, std::env::var_os("PATH"))
+    }
+
+    /// Resolve `name` against the given `PATH` value
+    fn resolve_in(&mut self, name: &str, path: Option<OsString>) -> Option<PathBuf> {
+        if name.is_empty() || name.contains(std::path::is_separator) {
+            return None;
+        }
+
+        if path != self.path {
+            self.entries.clear();
+            self.path = path;
+        }
+        if let Some(found) = self.entries.get(name) {
+            return Some(found.clone());
+        }
+
+        self.scans += 1;
+        let found = std::env::split_paths(self.path.as_ref()?)
+            .map(|dir| dir.join(name))
+            .find(|candidate| is_executable(candidate))?;
+        self.entries.insert(name.to_string(), found.clone());
+        Some(found)
+    }
+
+    /// Drop a stale entry (e.g. the binary was removed since it was cached)
+    pub fn forget(&mut self, name: &str) {
+        self.entries.remove(name);
+    }
+}
+
+#[cfg(unix)]
+pub(super) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub(super) fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temp dir holding `count` executables named `cmd0`, `cmd1`, ...
+    fn path_dir(count: usize) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..count {
+            let file = dir.path().join(format!("cmd{}", i));
+            std::fs::write(&file, "#!/bin/sh\n").unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o755)).unwrap();
+            }
+        }
+        dir
+    }
+
+    #[test]
+    fn test_repeated_lookups_scan_once() {
+        let dir = path_dir(200);
+        let path = Some(dir.path().as_os_str().to_owned());
+        let mut cache = CommandCache::default();
+
+        for _ in 0..1000 {
+            for name in ["cmd0", "cmd199"] {
+                assert_eq!(cache.resolve_in(name, path.clone()), Some(dir.path().join(name)));
+            }
+        }
+        // Only the first lookup of each name touches the filesystem
+        assert_eq!(cache.scans, 2);
+    }
+
+    #[test]
+    fn test_path_change_invalidates() {
+        let first = path_dir(1);
+        let second = path_dir(1);
+        let mut cache = CommandCache::default();
+
+        let found = cache.resolve_in("cmd0", Some(first.path().as_os_str().to_owned()));
+        assert_eq!(found, Some(first.path().join("cmd0")));
+        let found = cache.resolve_in("cmd0", Some(second.path().as_os_str().to_owned()));
+        assert_eq!(found, Some(second.path().join("cmd0")));
+        assert_eq!(cache.scans, 2);
+    }
+
+    #[test]
+    fn test_misses_and_paths_are_not_cached() {
+        let dir = path_dir(1);
+        let path = Some(dir.path().as_os_str().to_owned());
+        let mut cache = CommandCache::default();
+
+        assert_eq!(cache.resolve_in("missing", path.clone()), None);
+        assert_eq!(cache.resolve_in("missing", path.clone()), None);
+        assert_eq!(cache.scans, 2);
+        assert_eq!(cache.resolve_in("./cmd0", path.clone()), None);
+        assert_eq!(cache.resolve_in("cmd0", None), None);
+    }
+
+    #[test]
+    fn test_forget_stale_entry() {
+        let dir = path_dir(1);
+        let path = Some(dir.path().as_os_str().to_owned());
+        let mut cache = CommandCache::default();
+
+        assert!(cache.resolve_in("cmd0", path.clone()).is_some());
+        std::fs::remove_file(dir.path().join("cmd0")).unwrap();
+        // Still cached until the executor notices the spawn failure
+        assert!(cache.resolve_in("cmd0", path.clone()).is_some());
+        cache.forget("cmd0");
+        assert_eq!(cache.resolve_in("cmd0", path), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_executable_files_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("plain"), "").unwrap();
+        let mut cache = CommandCache::default();
+        assert_eq!(cache.resolve_in("plain", Some(dir.path().as_os_str().to_owned())), None);
+    }
+}
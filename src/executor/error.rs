@@ -0,0 +1,222 @@
+//! # Command Errors (أخطاء الأوامر)
+//!
+//! Structured failures reported by builtins and redirections. Each variant
+//! keeps what went wrong; [`CommandError::render`] words it in a locale and
+//! `Display` gives the bilingual form, so the wording lives in one place.
+
+use super::CommandResult;
+use crate::utils::{Locale, Message};
+
+use std::fmt;
+use std::io;
+
+/// The file operation that failed, named in both languages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOp {
+    Read,
+    ReadDir,
+    Write,
+    Open,
+    Create,
+    CreatePipe,
+    CreateBackup,
+    Access,
+    Resolve,
+    ChangeDir,
+    ChangePermissions,
+    PreserveAttributes,
+    Truncate,
+    Delete,
+}
+
+impl FileOp {
+    /// The phrase that precedes the quoted path, as (Arabic, English)
+    fn phrase(self) -> (&'static str, &'static str) {
+        match self {
+            FileOp::Read => ("لا يمكن قراءة", "Cannot read"),
+            FileOp::ReadDir => ("لا يمكن قراءة المجلد", "Cannot read directory"),
+            FileOp::Write => ("لا يمكن الكتابة إلى", "Cannot write to"),
+            FileOp::Open => ("لا يمكن فتح", "Cannot open"),
+            FileOp::Create => ("لا يمكن إنشاء", "Cannot create"),
+            FileOp::CreatePipe => ("لا يمكن إنشاء الأنبوب", "Cannot create pipe"),
+            FileOp::CreateBackup => ("لا يمكن إنشاء نسخة احتياطية", "Cannot create backup"),
+            FileOp::Access => ("لا يمكن الوصول إلى", "Cannot access"),
+            FileOp::Resolve => ("لا يمكن تحليل المسار", "Cannot resolve path"),
+            FileOp::ChangeDir => ("لا يمكن الانتقال إلى", "Cannot change to"),
+            FileOp::ChangePermissions => ("لا يمكن تغيير صلاحيات", "Cannot change permissions of"),
+            FileOp::PreserveAttributes => ("لا يمكن حفظ خصائص", "Cannot preserve attributes of"),
+            FileOp::Truncate => ("لا يمكن تفريغ", "Cannot truncate"),
+            FileOp::Delete => ("لا يمكن حذف", "Cannot delete"),
+        }
+    }
+}
+
+/// Why a command failed
+#[derive(Debug)]
+pub enum CommandError {
+    /// A file operation on `path` failed
+    File { op: FileOp, path: String, source: io::Error },
+    /// Missing or invalid arguments, shown with the command's usage line
+    Usage { problem: Message, usage: Message },
+    /// Any other failure, described in both languages
+    Failed(Message),
+    /// No builtin or program is called `name`; `suggestion` is a builtin
+    /// with a similar name
+    NotFound { name: String, suggestion: Option<String>, source: io::Error },
+    /// An external command failed without writing anything to stderr
+    Exited(i32),
+    /// What a failed external command wrote to stderr, shown as is
+    Stderr(String),
+    /// Ctrl+C stopped a loop or a repeated command (exit code 130)
+    Interrupted,
+    /// A failure with nothing to report, like a false `اختبار`, or one whose
+    /// message already went to a `2>` file
+    Silent,
+}
+
+impl CommandError {
+    pub fn file(op: FileOp, path: impl fmt::Display, source: io::Error) -> Self {
+        CommandError::File { op, path: path.to_string(), source }
+    }
+
+    pub fn usage(problem: impl Into<Message>, usage: impl Into<Message>) -> Self {
+        CommandError::Usage { problem: problem.into(), usage: usage.into() }
+    }
+
+    pub fn failed(message: impl Into<Message>) -> Self {
+        CommandError::Failed(message.into())
+    }
+
+    /// Whether there is nothing to print for this failure
+    pub fn is_silent(&self) -> bool {
+        matches!(self, CommandError::Silent)
+    }
+
+    /// The message in Arabic, English, or both
+    pub fn render(&self, locale: Locale) -> String {
+        match self {
+            CommandError::File { op, path, source } => {
+                let (ar, en) = op.phrase();
+                locale.choose(
+                    &format!("خطأ: {} '{}' - {}", ar, path, source),
+                    &format!("Error: {} '{}' - {}", en, path, source),
+                )
+            }
+            CommandError::Usage { problem, usage } => {
+                let ar = format!("خطأ: {}\nالاستخدام: {}", problem.ar, usage.ar);
+                let en = format!("Error: {}\nUsage: {}", problem.en, usage.en);
+                match locale {
+                    Locale::Arabic => ar,
+                    Locale::English => en,
+                    Locale::Both => format!("{}\n{}", ar, en),
+                }
+            }
+            CommandError::Failed(message) => {
+                locale.choose(&format!("خطأ: {}", message.ar), &format!("Error: {}", message.en))
+            }
+            CommandError::NotFound { name, suggestion: Some(suggestion), .. } => locale.choose(
+                &format!("خطأ: الأمر '{}' غير موجود، هل تقصد '{}'؟", name, suggestion),
+                &format!("Error: Command '{}' not found. Did you mean '{}'?", name, suggestion),
+            ),
+            CommandError::NotFound { name, suggestion: None, source } => locale.choose(
+                &format!("خطأ: الأمر '{}' غير موجود - {}", name, source),
+                &format!("Error: Command '{}' not found - {}", name, source),
+            ),
+            CommandError::Exited(code) => locale.choose(
+                &format!("الأمر انتهى برمز: {}", code),
+                &format!("Command exited with code: {}", code),
+            ),
+            CommandError::Stderr(text) => text.clone(),
+            CommandError::Interrupted => locale.choose("تمت المقاطعة", "Interrupted"),
+            CommandError::Silent => String::new(),
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(Locale::Both))
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CommandError::File { source, .. } | CommandError::NotFound { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// `io::Error` can't be cloned, so a copy keeps its kind and message
+impl Clone for CommandError {
+    fn clone(&self) -> Self {
+        let copy = |source: &io::Error| io::Error::new(source.kind(), source.to_string());
+        match self {
+            CommandError::File { op, path, source } => CommandError::File { op: *op, path: path.clone(), source: copy(source) },
+            CommandError::Usage { problem, usage } => CommandError::Usage { problem: problem.clone(), usage: usage.clone() },
+            CommandError::Failed(message) => CommandError::Failed(message.clone()),
+            CommandError::NotFound { name, suggestion, source } => CommandError::NotFound {
+                name: name.clone(),
+                suggestion: suggestion.clone(),
+                source: copy(source),
+            },
+            CommandError::Exited(code) => CommandError::Exited(*code),
+            CommandError::Stderr(text) => CommandError::Stderr(text.clone()),
+            CommandError::Interrupted => CommandError::Interrupted,
+            CommandError::Silent => CommandError::Silent,
+        }
+    }
+}
+
+/// Errors are equal when they read the same
+impl PartialEq for CommandError {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl From<CommandError> for CommandResult {
+    fn from(error: CommandError) -> Self {
+        CommandResult::Error(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_error_renders_both_languages() {
+        let source = io::Error::new(io::ErrorKind::NotFound, "missing");
+        let error = CommandError::file(FileOp::Read, "a.txt", source);
+        assert_eq!(error.to_string(), "خطأ: لا يمكن قراءة 'a.txt' - missing / Error: Cannot read 'a.txt' - missing");
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn test_usage_error_converts_to_result() {
+        let error = CommandError::usage(("يرجى تحديد ملف", "Please specify a file"), ("اقرأ <ملف>", "cat <file>"));
+        assert_eq!(
+            error.to_string(),
+            "خطأ: يرجى تحديد ملف\nالاستخدام: اقرأ <ملف>\nError: Please specify a file\nUsage: cat <file>"
+        );
+        // The error stays typed until it is printed
+        assert_eq!(CommandResult::from(error.clone()), CommandResult::Error(error));
+    }
+
+    #[test]
+    fn test_render_in_each_locale() {
+        let source = || io::Error::new(io::ErrorKind::NotFound, "missing");
+        let error = CommandError::file(FileOp::Delete, "a.txt", source());
+        assert_eq!(error.render(Locale::Arabic), "خطأ: لا يمكن حذف 'a.txt' - missing");
+        assert_eq!(error.render(Locale::English), "Error: Cannot delete 'a.txt' - missing");
+        assert_eq!(error.render(Locale::Both), error.to_string());
+
+        let error = CommandError::usage(("خيار غير صالح", "Invalid option"), ("سجل [تشغيل|ايقاف]", "log [on|off]"));
+        assert_eq!(error.render(Locale::Arabic), "خطأ: خيار غير صالح\nالاستخدام: سجل [تشغيل|ايقاف]");
+        assert_eq!(error.render(Locale::English), "Error: Invalid option\nUsage: log [on|off]");
+
+        assert_eq!(CommandError::Interrupted.render(Locale::Both), "تمت المقاطعة / Interrupted");
+    }
+}
@@ -0,0 +1,352 @@
+//! # Word Expansion (توسيع الكلمات)
+//!
+//! Expands shell variables (`$name`, `${name}`, `$?`), command
+//! substitutions (`$(...)`) and glob patterns in command arguments, according
+//! to how each word was quoted. Unquoted expansions are split into separate
+//! fields on `IFS` (whitespace by default). Redirect targets get tilde
+//! expansion.
+
+use crate::parser::{Quoting, Redirect, Word};
+use crate::utils::{active_locale, expand_tilde};
+use super::Executor;
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+impl Executor {
+    /// Look up a shell variable, falling back to the environment
+    pub fn variable(&self, name: &str) -> Option<String> {
+        if name == "?" {
+            return Some(self.last_exit_code.to_string());
+        }
+        self.variables
+            .get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+    }
+
+    /// Expand command arguments or for-loop items: variables, then globs on
+    /// unquoted words
+    ///
+    /// This runs before builtin dispatch, so `احذف *.tmp` hands `cmd_rm` the
+    /// matching names; a pattern that matches nothing is passed on literally.
+    pub(crate) fn expand_args(&self, words: &[Word]) -> Vec<String> {
+        let mut items = Vec::new();
+        for word in words {
+            let fields = self.expand_fields(word);
+            if word.quoting == Quoting::Unquoted {
+                items.extend(fields.iter().flat_map(|field| expand_glob(field, &self.cwd)));
+            } else {
+                items.extend(fields);
+            }
+        }
+        items
+    }
+
+    /// Expand a word into fields
+    ///
+    /// Quoted words always stay one field. Unquoted words containing an
+    /// expansion are split on `IFS`, so `$x` holding `a b c` gives three
+    /// arguments and an empty `$x` gives none.
+    fn expand_fields(&self, word: &Word) -> Vec<String> {
+        let expanded = self.expand_word(word);
+        if word.quoting != Quoting::Unquoted || !word.text.contains('$') {
+            return vec![expanded];
+        }
+
+        let ifs = self.variable("IFS").unwrap_or_else(|| DEFAULT_IFS.to_string());
+        expanded
+            .split(|c| ifs.contains(c))
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Substitute variables and commands in a word (single-quoted words are
+    /// literal)
+    ///
+    /// Assignment values are expanded this way, without field splitting or
+    /// globbing, so `x=*` stores a literal `*`.
+    pub(crate) fn expand_word(&self, word: &Word) -> String {
+        if word.quoting == Quoting::Single || !word.text.contains('$') {
+            return word.text.clone();
+        }
+
+        let mut output = String::with_capacity(word.text.len());
+        let mut chars = word.text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                output.push(c);
+                continue;
+            }
+
+            let name = match chars.peek().copied() {
+                Some('(') => {
+                    chars.next();
+                    let command = take_substitution(&mut chars);
+                    output.push_str(&self.command_output(&command));
+                    continue;
+                }
+                Some('?') => {
+                    chars.next();
+                    "?".to_string()
+                }
+                Some('{') => {
+                    chars.next();
+                    let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    name
+                }
+                Some(c) if is_name_char(c) => {
+                    let mut name = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if !is_name_char(c) {
+                            break;
+                        }
+                        name.push(c);
+                        chars.next();
+                    }
+                    name
+                }
+                // A lone `$` is literal
+                _ => {
+                    output.push('$');
+                    continue;
+                }
+            };
+
+            // Unset variables expand to nothing
+            output.push_str(&self.variable(&name).unwrap_or_default());
+        }
+        output
+    }
+
+    /// Run a command substitution and return what it printed
+    ///
+    /// The command runs on a copy of the shell state, so `انتقل` inside it
+    /// doesn't leak out. Trailing newlines are dropped; errors go to stderr.
+    fn command_output(&self, command: &str) -> String {
+        let mut executor = self.background_copy();
+        let buffer = Arc::new(Mutex::new(String::new()));
+        executor.capture = Some(Arc::clone(&buffer));
+        executor.timestamps = false;
+        executor.piped = true;
+
+        match executor.execute_str(command) {
+            Ok(result) => executor.print_result(&result),
+            Err(e) => executor.print_error(&e.render(active_locale())),
+        }
+
+        let output = buffer.lock().map(|output| output.clone()).unwrap_or_default();
+        output.trim_end_matches('\n').to_string()
+    }
+}
+
+/// Consume a `$(...)` body up to its matching `)`, after the opening `(`
+fn take_substitution(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut command = String::new();
+    let mut depth = 1;
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                command.push(c);
+                if let Some(next) = chars.next() {
+                    command.push(next);
+                }
+                continue;
+            }
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        command.push(c);
+    }
+    command
+}
+
+/// Expand a leading `~` in redirect targets, so `> ~/out.txt` writes to
+/// the home directory
+pub(crate) fn expand_redirects(redirects: &[Redirect]) -> Vec<Redirect> {
+    redirects
+        .iter()
+        .map(|redir| {
+            let target = expand_tilde(&redir.target).to_string_lossy().to_string();
+            Redirect::new(redir.kind, target)
+        })
+        .collect()
+}
+
+/// Field separators used when `IFS` is unset
+const DEFAULT_IFS: &str = " \t\n";
+
+/// Characters allowed in variable names (Arabic letters included)
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Expand a glob pattern to matching paths, sorted
+///
+/// A relative pattern matches in `dir` and its matches stay relative.
+/// Words without pattern characters, and patterns that match nothing, are
+/// kept as-is.
+pub fn expand_glob(pattern: &str, dir: &Path) -> Vec<String> {
+    if !pattern.contains(['*', '?', '[']) {
+        return vec![pattern.to_string()];
+    }
+
+    // The directory is matched literally, even if its name has `*` or `[`
+    let prefix = if Path::new(pattern).is_absolute() || dir.as_os_str().is_empty() {
+        String::new()
+    } else {
+        format!("{}/", dir.to_string_lossy().trim_end_matches('/'))
+    };
+    let matches: Vec<String> = match glob::glob(&format!("{}{}", glob::Pattern::escape(&prefix), pattern)) {
+        Ok(paths) => paths
+            .flatten()
+            .map(|path| {
+                let path = path.to_string_lossy();
+                path.strip_prefix(&prefix).unwrap_or(&path).to_string()
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    if matches.is_empty() {
+        vec![pattern.to_string()]
+    } else {
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, quoting: Quoting) -> Word {
+        Word::new(text.to_string(), quoting)
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let mut executor = Executor::default();
+        executor.variables.insert("اسم".to_string(), "محيط".to_string());
+        executor.last_exit_code = 3;
+
+        let args = executor.expand_args(&[
+            word("$اسم", Quoting::Unquoted),
+            word("${اسم}_1", Quoting::Double),
+            word("$اسم", Quoting::Single),
+            word("$?", Quoting::Unquoted),
+            word("$", Quoting::Unquoted),
+            word("$undefined_ocean_var", Quoting::Double),
+        ]);
+        assert_eq!(args, vec!["محيط", "محيط_1", "$اسم", "3", "$", ""]);
+    }
+
+    #[test]
+    fn test_unquoted_expansion_splits_words() {
+        let mut executor = Executor::default();
+        executor.variables.insert("قائمة".to_string(), "أ ب  ج".to_string());
+
+        let unquoted = executor.expand_args(&[word("$قائمة", Quoting::Unquoted)]);
+        assert_eq!(unquoted, vec!["أ", "ب", "ج"]);
+
+        let quoted = executor.expand_args(&[word("$قائمة", Quoting::Double)]);
+        assert_eq!(quoted, vec!["أ ب  ج"]);
+    }
+
+    #[test]
+    fn test_empty_unquoted_expansion_is_dropped() {
+        let mut executor = Executor::default();
+        executor.variables.insert("فارغ".to_string(), String::new());
+
+        let args = executor.expand_args(&[
+            word("$فارغ", Quoting::Unquoted),
+            word("$فارغ", Quoting::Double),
+        ]);
+        assert_eq!(args, vec![""]);
+    }
+
+    #[test]
+    fn test_custom_ifs() {
+        let mut executor = Executor::default();
+        executor.variables.insert("IFS".to_string(), ":".to_string());
+        executor.variables.insert("PATHS".to_string(), "/bin:/usr/bin".to_string());
+
+        let args = executor.expand_args(&[word("$PATHS", Quoting::Unquoted)]);
+        assert_eq!(args, vec!["/bin", "/usr/bin"]);
+    }
+
+    #[test]
+    fn test_expand_args_globs_unquoted_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("b.txt"), "").unwrap();
+        std::fs::write(dir.path().join("a.txt"), "").unwrap();
+        let pattern = format!("{}/*.txt", dir.path().display());
+
+        let executor = Executor::default();
+        let items = executor.expand_args(&[
+            word(&pattern, Quoting::Unquoted),
+            word(&pattern, Quoting::Double),
+        ]);
+        assert_eq!(items, vec![
+            format!("{}/a.txt", dir.path().display()),
+            format!("{}/b.txt", dir.path().display()),
+            pattern,
+        ]);
+    }
+
+    #[test]
+    fn test_glob_without_matches_is_literal() {
+        assert_eq!(expand_glob("/nonexistent/ocean-*.txt", Path::new("/")), vec!["/nonexistent/ocean-*.txt"]);
+        assert_eq!(expand_glob("plain", Path::new("/")), vec!["plain"]);
+    }
+
+    #[test]
+    fn test_redirect_targets_expand_tilde() {
+        use crate::parser::RedirectKind;
+
+        let home = dirs::home_dir().unwrap();
+        let redirects = expand_redirects(&[
+            Redirect::new(RedirectKind::Out, "~/out.txt".to_string()),
+            Redirect::new(RedirectKind::In, "has space.txt".to_string()),
+        ]);
+        assert_eq!(redirects[0].target, home.join("out.txt").to_string_lossy());
+        assert_eq!(redirects[0].kind, RedirectKind::Out);
+        assert_eq!(redirects[1].target, "has space.txt");
+    }
+
+    #[test]
+    fn test_command_substitution() {
+        let mut executor = Executor::default();
+        executor.variables.insert("اسم".to_string(), "محيط".to_string());
+
+        let args = executor.expand_args(&[
+            word("[$(اطبع $اسم)]", Quoting::Unquoted),
+            word("$(echo 'a   b' ; echo c)", Quoting::Unquoted),
+            word("$(echo 'a   b')", Quoting::Double),
+            word("$(echo a)", Quoting::Single),
+        ]);
+        assert_eq!(args, vec!["[محيط]", "a", "b", "c", "a   b", "$(echo a)"]);
+    }
+
+    #[test]
+    fn test_command_substitution_runs_on_a_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let executor = Executor::default();
+        let line = format!("$(انتقل {} ; اين | cut -c 1-1000)", dir.path().display());
+
+        let args = executor.expand_args(&[word(&line, Quoting::Double)]);
+        assert_eq!(args, vec![dir.path().display().to_string()]);
+        assert_ne!(executor.cwd(), dir.path());
+    }
+}
@@ -0,0 +1,156 @@
+//! # Background Jobs (المهام الخلفية)
+//!
+//! A command ending in `&` runs on its own thread with a copy of the shell
+//! state. Its output and errors are buffered in the job instead of printed,
+//! so they can't land in the middle of the prompt; the REPL collects
+//! finished jobs and prints them before drawing the next prompt.
+
+use crate::utils::Locale;
+
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// A command running (or finished) in the background
+#[derive(Debug)]
+pub struct Job {
+    /// Job number shown as `[1]`, reused once the table is empty
+    pub id: usize,
+    /// The command as it would be typed, without the trailing `&`
+    pub command: String,
+    /// Exit code, or `None` while the job is still running
+    pub status: Option<i32>,
+    /// Everything the job printed, in order
+    output: Arc<Mutex<String>>,
+    handle: Option<JoinHandle<i32>>,
+}
+
+impl Job {
+    /// The output captured so far
+    pub fn output(&self) -> String {
+        self.output.lock().map(|out| out.clone()).unwrap_or_default()
+    }
+
+    /// The line printed when the job is reported as finished, in `locale`
+    pub fn notice(&self, locale: Locale) -> String {
+        let status = match self.status {
+            Some(0) => locale.choose("انتهى", "Done"),
+            Some(code) => locale.choose(&format!("خرج {}", code), &format!("Exit {}", code)),
+            None => locale.choose("يعمل", "Running"),
+        };
+        format!("[{}] {}  {}", self.id, status, self.command)
+    }
+
+    /// Collect the exit code if the thread has finished (or `block` is set)
+    fn poll(&mut self, block: bool) {
+        if let Some(handle) = self.handle.take() {
+            if block || handle.is_finished() {
+                // A job that panicked is reported like a failed command
+                self.status = Some(handle.join().unwrap_or(1));
+            } else {
+                self.handle = Some(handle);
+            }
+        }
+    }
+}
+
+/// The shell's background jobs
+#[derive(Debug, Default)]
+pub struct JobTable {
+    jobs: Vec<Job>,
+}
+
+impl JobTable {
+    /// Start `run` on a new thread and return the job number
+    ///
+    /// `run` receives the buffer to write output into and returns the exit
+    /// code.
+    pub fn spawn<F>(&mut self, command: String, run: F) -> usize
+    where
+        F: FnOnce(Arc<Mutex<String>>) -> i32 + Send + 'static,
+    {
+        let id = self.jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+        let output = Arc::new(Mutex::new(String::new()));
+        let buffer = Arc::clone(&output);
+        let handle = thread::spawn(move || run(buffer));
+        self.jobs.push(Job { id, command, status: None, output, handle: Some(handle) });
+        id
+    }
+
+    /// Remove and return the jobs that have finished, oldest first
+    pub fn take_finished(&mut self) -> Vec<Job> {
+        self.take(false)
+    }
+
+    /// Wait for every job to finish, then remove and return them all
+    pub fn wait_all(&mut self) -> Vec<Job> {
+        self.take(true)
+    }
+
+    fn take(&mut self, block: bool) -> Vec<Job> {
+        let (finished, running) = std::mem::take(&mut self.jobs)
+            .into_iter()
+            .map(|mut job| {
+                job.poll(block);
+                job
+            })
+            .partition(|job| job.status.is_some());
+        self.jobs = running;
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_output_is_buffered_until_collected() {
+        let mut table = JobTable::default();
+        let id = table.spawn("اطبع مرحبا".to_string(), |output| {
+            output.lock().unwrap().push_str("مرحبا\n");
+            0
+        });
+        assert_eq!(id, 1);
+
+        let jobs = table.wait_all();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].output(), "مرحبا\n");
+        assert_eq!(jobs[0].status, Some(0));
+        assert_eq!(jobs[0].notice(Locale::Both), "[1] انتهى / Done  اطبع مرحبا");
+        assert_eq!(jobs[0].notice(Locale::Arabic), "[1] انتهى  اطبع مرحبا");
+        assert_eq!(jobs[0].notice(Locale::English), "[1] Done  اطبع مرحبا");
+        assert!(table.wait_all().is_empty());
+    }
+
+    #[test]
+    fn test_running_jobs_stay_in_the_table() {
+        let mut table = JobTable::default();
+        let (release, wait) = mpsc::channel::<()>();
+        table.spawn("slow".to_string(), move |_| {
+            wait.recv().unwrap();
+            3
+        });
+        let second = table.spawn("fast".to_string(), |_| 0);
+        assert_eq!(second, 2);
+
+        // Only the fast job can be collected while the slow one is blocked
+        loop {
+            let finished = table.take_finished();
+            if let Some(job) = finished.first() {
+                assert_eq!(finished.len(), 1);
+                assert_eq!(job.command, "fast");
+                break;
+            }
+            thread::yield_now();
+        }
+
+        release.send(()).unwrap();
+        let jobs = table.wait_all();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].notice(Locale::Both), "[1] خرج 3 / Exit 3  slow");
+        assert_eq!(jobs[0].notice(Locale::English), "[1] Exit 3  slow");
+        // Numbering starts over once every job has been collected
+        assert_eq!(table.spawn("again".to_string(), |_| 0), 1);
+    }
+}
@@ -5,32 +5,104 @@
 //! - I/O redirection
 //! - Command chaining (&&, ||, ;)
 //! - Background execution
+//! - For loops over expanded items, and while loops (stopped by Ctrl+C)
+//! - Interactive menus (`اختر`/select) read from stdin
+//! - If/else conditionals
+//! - Variable and glob expansion
+//! - Cached `PATH` lookup of external commands
 
+mod command_cache;
+mod error;
+mod expand;
+mod jobs;
 mod pipeline;
+mod suggest;
 
-use crate::parser::{Command, Redirect, RedirectKind};
+pub use jobs::{Job, JobTable};
+pub use error::{CommandError, FileOp};
+
+use crate::lexer::Lexer;
+use crate::parser::{Assignment, Command, ParseError, Parser, Redirect, RedirectKind, Word};
 use crate::commands;
-use crate::utils::{shape_if_arabic, contains_arabic, right_align};
+use crate::utils::{active_locale, history_path, interrupted, shape_if_arabic, contains_arabic, right_align, now_timestamp, normalize_digits, normalize_logical};
 
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsStr;
 use std::fs::{File, OpenOptions};
-use std::io::Write;
+use std::io::{self, BufRead, Read, Seek, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command as ProcessCommand, Stdio};
+use std::sync::{Arc, Mutex, RwLock};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CommandResult {
     Success(String),
-    Error(String),
+    Error(CommandError),
     Exit(i32),
     None,
+    /// Results of several commands in the order they ran (a loop, a `;`
+    /// list); the last one is the status. Built with [`CommandResult::join`].
+    Sequence(Vec<CommandResult>),
 }
 
 impl CommandResult {
     pub fn is_success(&self) -> bool {
-        matches!(self, CommandResult::Success(_) | CommandResult::None)
+        matches!(self.status(), CommandResult::Success(_) | CommandResult::None)
     }
 
     pub fn is_exit(&self) -> bool {
-        matches!(self, CommandResult::Exit(_))
+        matches!(self.status(), CommandResult::Exit(_))
+    }
+
+    /// Combine results in order into one
+    ///
+    /// Output runs are merged and silent errors that aren't last are
+    /// dropped, so a sequence that only printed comes back as plain
+    /// `Success`, and a single result comes back as itself.
+    pub fn join(results: Vec<CommandResult>) -> CommandResult {
+        let results: Vec<CommandResult> = results
+            .into_iter()
+            .flat_map(|result| match result {
+                CommandResult::Sequence(parts) => parts,
+                result => vec![result],
+            })
+            .collect();
+        let last = results.len().saturating_sub(1);
+
+        let mut parts: Vec<CommandResult> = Vec::new();
+        for (i, result) in results.into_iter().enumerate() {
+            match (parts.last_mut(), result) {
+                (Some(CommandResult::Success(output)), CommandResult::Success(more)) => output.push_str(&more),
+                (_, CommandResult::None) if i < last => {}
+                (_, CommandResult::Success(output)) if output.is_empty() && i < last => {}
+                (_, CommandResult::Error(error)) if error.is_silent() && i < last => {}
+                (_, result) => parts.push(result),
+            }
+        }
+
+        // Output followed by plain success is just output
+        if matches!(parts.as_slice(), [.., CommandResult::Success(_), CommandResult::None]) {
+            parts.pop();
+        }
+        match parts.len() {
+            0 => CommandResult::None,
+            1 => parts.pop().unwrap_or(CommandResult::None),
+            _ => CommandResult::Sequence(parts),
+        }
+    }
+
+    /// The results in the order they ran: a sequence's parts, or this one
+    pub fn parts(&self) -> &[CommandResult] {
+        match self {
+            CommandResult::Sequence(parts) => parts,
+            result => std::slice::from_ref(result),
+        }
+    }
+
+    /// The result that decides success: the last one that ran
+    pub fn status(&self) -> &CommandResult {
+        self.parts().last().unwrap_or(&CommandResult::None)
     }
 
     #[allow(dead_code)]
@@ -42,9 +114,51 @@ impl CommandResult {
     }
 }
 
+/// Words registered with `اكمل` for tab completion, by command name
+///
+/// Shared with the line editor's completer, which offers them for that
+/// command's arguments.
+pub type CustomCompletions = Arc<RwLock<HashMap<String, Vec<String>>>>;
+
 pub struct Executor {
     pub last_exit_code: i32,
     pub use_rtl_padding: bool,
+    /// Prefix printed output lines with a timestamp (`سجل`/log mode)
+    pub timestamps: bool,
+    /// Shell variables (set by `لكل`/for loops), looked up before the environment
+    pub variables: HashMap<String, String>,
+    /// Refuse `>` onto existing files (`امسح_فوق ايقاف` / `clobber off`)
+    pub noclobber: bool,
+    /// Stop a sequence at its first failing command (`اضبط -e` / `set -e`)
+    pub errexit: bool,
+    /// Print each command to stderr before running it (`اضبط -x` / `set -x`)
+    pub xtrace: bool,
+    /// Have file commands describe their changes instead (`اضبط -d` / `--dry-run`)
+    pub dry_run: bool,
+    /// Where external commands were found on `PATH`
+    pub(crate) command_cache: command_cache::CommandCache,
+    /// Working directory (`انتقل`/cd), independent of other executors
+    cwd: PathBuf,
+    /// Commands started with `&`
+    pub jobs: JobTable,
+    /// Where a background job's output goes instead of the terminal
+    capture: Option<Arc<Mutex<String>>>,
+    /// Set while running a pipeline stage whose output feeds the next one
+    piped: bool,
+    /// Whether the running command's output goes straight to a terminal
+    /// with color on
+    color: bool,
+    /// Color is enabled at all (see `init_color`), read once on creation
+    use_color: bool,
+    /// Prefix assignments (`A=1 cmd`) passed to the command's environment
+    command_env: Vec<(String, String)>,
+    /// Completions added with `اكمل` / complete
+    pub completions: CustomCompletions,
+    /// Where the REPL keeps command history
+    pub history_file: PathBuf,
+    /// Set by `امسح_ذاكرة` once the history file is emptied, so the REPL
+    /// drops the history it holds in memory too
+    pub history_cleared: bool,
 }
 
 impl Default for Executor {
@@ -58,6 +172,94 @@ impl Executor {
         Self {
             last_exit_code: 0,
             use_rtl_padding,
+            timestamps: false,
+            noclobber: false,
+            errexit: false,
+            xtrace: false,
+            dry_run: false,
+            variables: HashMap::new(),
+            command_cache: command_cache::CommandCache::default(),
+            cwd: env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            jobs: JobTable::default(),
+            capture: None,
+            piped: false,
+            color: false,
+            use_color: colored::control::SHOULD_COLORIZE.should_colorize(),
+            command_env: Vec::new(),
+            completions: CustomCompletions::default(),
+            history_file: history_path(),
+            history_cleared: false,
+        }
+    }
+
+    /// A copy of the shell state for a background job
+    fn background_copy(&self) -> Self {
+        Self {
+            last_exit_code: self.last_exit_code,
+            use_rtl_padding: self.use_rtl_padding,
+            timestamps: self.timestamps,
+            noclobber: self.noclobber,
+            errexit: self.errexit,
+            xtrace: self.xtrace,
+            dry_run: self.dry_run,
+            variables: self.variables.clone(),
+            command_cache: command_cache::CommandCache::default(),
+            cwd: self.cwd.clone(),
+            jobs: JobTable::default(),
+            capture: None,
+            piped: false,
+            color: false,
+            use_color: self.use_color,
+            command_env: self.command_env.clone(),
+            completions: self.completions.clone(),
+            history_file: self.history_file.clone(),
+            history_cleared: false,
+        }
+    }
+
+    /// Whether the builtin being run may color its output (it goes straight
+    /// to the terminal, not into a pipe or file)
+    pub(crate) fn color(&self) -> bool {
+        self.color
+    }
+
+    /// The executor's working directory
+    pub fn cwd(&self) -> &Path {
+        &self.cwd
+    }
+
+    /// Change the working directory, resolving `path` against the current one
+    ///
+    /// The stored path is logical: `.` and `..` are collapsed textually, so
+    /// leaving a symlinked directory with `..` returns to where the user came
+    /// from. Fails if the target isn't a directory. The process cwd is left
+    /// alone; see [`Executor::resolve`].
+    pub fn set_cwd(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let target = normalize_logical(&self.cwd.join(path));
+        if !std::fs::metadata(&target)?.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::NotADirectory, "not a directory"));
+        }
+        self.variables.insert("PWD".to_string(), target.to_string_lossy().to_string());
+        self.cwd = target;
+        Ok(())
+    }
+
+    /// A path relative to this executor's directory, as an absolute one
+    ///
+    /// The process cwd is never changed, so several executors (background
+    /// jobs, `$(...)`) can each work in their own directory.
+    pub(crate) fn resolve(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.cwd.join(path)
+    }
+
+    /// Prefix an output line with the current time when log mode is on
+    ///
+    /// Only applied to lines printed to the terminal, never to redirected output.
+    pub fn stamp_line(&self, line: &str) -> String {
+        if self.timestamps {
+            format!("[{}] {}", now_timestamp(), line)
+        } else {
+            line.to_string()
         }
     }
 
@@ -65,10 +267,31 @@ impl Executor {
         self.execute_with_input(cmd, None)
     }
 
+    /// Lex, parse and execute a line of input
+    ///
+    /// Lexer and parse errors include the offending source line, ready to print.
+    pub fn execute_str(&mut self, input: &str) -> Result<CommandResult, ParseError> {
+        Ok(self.execute(Self::parse(input)?))
+    }
+
+    /// Lex and parse a command string, attaching it to any error
+    pub fn parse(input: &str) -> Result<Command, ParseError> {
+        let tokens = Lexer::new(input).tokenize_checked().map_err(|e| ParseError::from(e).with_source(input))?;
+        Parser::new(tokens).parse().map_err(|e| e.with_source(input))
+    }
+
+    /// Stop for Ctrl+C: exit code 130, with a notice saying so
+    pub(crate) fn interrupt(&mut self) -> CommandResult {
+        self.last_exit_code = 130;
+        CommandError::Interrupted.into()
+    }
+
     pub fn execute_with_input(&mut self, cmd: Command, input: Option<String>) -> CommandResult {
         match cmd {
             Command::Empty => CommandResult::None,
 
+            Command::Assign { assignments, command } => self.execute_assign(assignments, command, input),
+
             Command::Simple { name, args, redirects } => {
                 self.execute_simple(&name, &args, &redirects, input)
             }
@@ -77,64 +300,305 @@ impl Executor {
                 self.execute_pipeline(cmds)
             }
 
-            Command::And(left, right) => {
-                let result = self.execute(*left);
-                if result.is_success() {
-                    self.execute(*right)
-                } else {
-                    result
+            Command::And(left, right) => self.execute_chain(*left, *right, true),
+
+            Command::Or(left, right) => self.execute_chain(*left, *right, false),
+
+            Command::Sequence(cmds) => {
+                let mut collected = Collected::default();
+                for cmd in cmds {
+                    // A negated command never trips errexit
+                    let negated = matches!(cmd, Command::Not(_));
+                    let result = self.execute(cmd);
+                    let failed = !self.succeeded(&result) && !negated;
+                    if collected.push(result) {
+                        return collected.finish();
+                    }
+                    if failed && self.errexit {
+                        break;
+                    }
                 }
+                collected.finish()
             }
 
-            Command::Or(left, right) => {
-                let result = self.execute(*left);
-                if !result.is_success() {
-                    self.execute(*right)
-                } else {
-                    result
+            Command::For { var, items, body } => {
+                let items = self.expand_args(&items);
+                // A loop that runs no iterations succeeds
+                self.last_exit_code = 0;
+                let mut collected = Collected::default();
+                for item in items {
+                    self.variables.insert(var.clone(), item);
+                    let result = self.execute((*body).clone());
+                    let failed = !self.succeeded(&result);
+                    if collected.push(result) {
+                        return collected.finish();
+                    }
+                    if failed && self.errexit {
+                        break;
+                    }
                 }
+                collected.finish()
             }
 
-            Command::Sequence(cmds) => {
-                let mut last_result = CommandResult::None;
-                for cmd in cmds {
-                    last_result = self.execute(cmd);
-                    if last_result.is_exit() {
-                        return last_result;
+            Command::While { cond, body } => {
+                let mut collected = Collected::default();
+                loop {
+                    // Ctrl+C in the interactive shell ends the loop with 130
+                    if interrupted() {
+                        let stopped = self.interrupt();
+                        collected.push(stopped);
+                        break;
+                    }
+                    let result = self.execute((*cond).clone());
+                    let holds = self.succeeded(&result);
+                    if collected.push(result) {
+                        return collected.finish();
+                    }
+                    if !holds {
+                        // A false condition ends the loop, which still succeeds
+                        collected.push(CommandResult::None);
+                        self.last_exit_code = 0;
+                        break;
+                    }
+
+                    let result = self.execute((*body).clone());
+                    let failed = !self.succeeded(&result);
+                    if collected.push(result) {
+                        return collected.finish();
+                    }
+                    if failed && self.errexit {
+                        break;
                     }
                 }
-                last_result
+                collected.finish()
+            }
+
+            Command::Select { var, items, body } => {
+                let items = self.expand_args(&items);
+                self.execute_select(&var, &items, &body, &mut io::stdin().lock())
+            }
+
+            Command::If { cond, then_branch, else_branch } => {
+                let mut collected = Collected::default();
+                let cond = self.execute(*cond);
+                let branch = if self.succeeded(&cond) { Some(then_branch) } else { else_branch };
+                if collected.push(cond) {
+                    return collected.finish();
+                }
+
+                match branch {
+                    Some(branch) => {
+                        let result = self.execute(*branch);
+                        if collected.push(result) {
+                            return collected.finish();
+                        }
+                    }
+                    None => {
+                        // A false condition with no else branch still succeeds
+                        collected.push(CommandResult::None);
+                        self.last_exit_code = 0;
+                    }
+                }
+                collected.finish()
+            }
+
+            Command::Not(cmd) => {
+                // The output and errors are kept; only success and failure swap
+                let result = self.execute(*cmd);
+                if result.is_exit() {
+                    return result;
+                }
+                let status = if self.succeeded(&result) {
+                    self.last_exit_code = 1;
+                    CommandError::Silent.into()
+                } else {
+                    self.last_exit_code = 0;
+                    CommandResult::None
+                };
+                CommandResult::join(vec![result, status])
             }
 
             Command::Background(cmd) => {
-                // For now, just execute normally
-                // TODO: Implement proper background execution
-                eprintln!("تحذير: التنفيذ في الخلفية غير مدعوم حالياً / Warning: Background execution not yet supported");
-                self.execute(*cmd)
+                let mut executor = self.background_copy();
+                let id = self.jobs.spawn(cmd.to_string(), move |output| {
+                    executor.capture = Some(output);
+                    let result = executor.execute(*cmd);
+                    executor.capture_result(&result)
+                });
+                self.print_error(&format!("[{}]", id));
+                self.last_exit_code = 0;
+                CommandResult::None
             }
         }
     }
 
+    /// Set shell variables, or with a command, pass them only in its
+    /// environment
+    ///
+    /// `A=1 cmd` leaves the shell's own `A` alone: the command's arguments
+    /// still expand with it, so `A=2 اطبع $A` prints the outer value.
+    fn execute_assign(
+        &mut self,
+        assignments: Vec<Assignment>,
+        command: Option<Box<Command>>,
+        input: Option<String>,
+    ) -> CommandResult {
+        let values: Vec<(String, String)> = assignments
+            .iter()
+            .map(|a| (a.name.clone(), self.expand_word(&a.value)))
+            .collect();
+
+        let Some(command) = command else {
+            self.variables.extend(values);
+            self.last_exit_code = 0;
+            return CommandResult::None;
+        };
+
+        let outer_env = self.command_env.len();
+        self.command_env.extend(values);
+        let result = self.execute_with_input(*command, input);
+        self.command_env.truncate(outer_env);
+        result
+    }
+
+    /// Print the items as a numbered menu and run the body with the chosen
+    /// one in `var`, prompting again until `input` ends
+    ///
+    /// The menu and the `PS3` prompt (`#? ` by default) go to stderr; an
+    /// empty line shows the menu again and anything that is not an item's
+    /// number asks again.
+    fn execute_select(
+        &mut self,
+        var: &str,
+        items: &[String],
+        body: &Command,
+        input: &mut dyn BufRead,
+    ) -> CommandResult {
+        self.last_exit_code = 0;
+        if items.is_empty() {
+            return CommandResult::None;
+        }
+
+        let menu: Vec<String> = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| shape_if_arabic(&format!("{}) {}", i + 1, item)))
+            .collect();
+        let prompt = self.variable("PS3").unwrap_or_else(|| "#? ".to_string());
+
+        let mut status = CommandResult::None;
+        let mut show_menu = true;
+        loop {
+            if show_menu {
+                eprintln!("{}", menu.join("\n"));
+                show_menu = false;
+            }
+            eprint!("{}", shape_if_arabic(&prompt));
+            let _ = io::stderr().flush();
+
+            let mut line = String::new();
+            if !matches!(input.read_line(&mut line), Ok(n) if n > 0) {
+                // Leave the prompt line before the shell prints anything else
+                eprintln!();
+                break;
+            }
+            let reply = line.trim();
+            if reply.is_empty() {
+                show_menu = true;
+                continue;
+            }
+            let choice = normalize_digits(reply)
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| items.get(i));
+            let Some(item) = choice else {
+                let error = CommandError::failed((
+                    format!("اختيار غير صالح '{}' (1-{})", reply, items.len()),
+                    format!("Invalid choice '{}' (1-{})", reply, items.len()),
+                ));
+                self.print_error(&error.render(active_locale()));
+                continue;
+            };
+
+            self.variables.insert(var.to_string(), item.clone());
+            let result = self.execute(body.clone());
+            let failed = !self.succeeded(&result);
+            // Show each choice's output and errors before the next prompt
+            self.print_result(&result);
+            if let CommandResult::Exit(code) = result.status() {
+                return CommandResult::Exit(*code);
+            }
+            status = if failed { CommandError::Silent.into() } else { CommandResult::None };
+            if failed && self.errexit {
+                break;
+            }
+        }
+        status
+    }
+
     fn execute_simple(
         &mut self,
         name: &str,
-        args: &[String],
+        args: &[Word],
         redirects: &[Redirect],
         input: Option<String>,
     ) -> CommandResult {
+        let redirects = &expand::expand_redirects(redirects);
+
+        // `<>` gives the command one file as both stdin and stdout
+        if let Some(redir) = redirects.iter().find(|r| r.kind == RedirectKind::ReadWrite) {
+            if redirects.len() > 1 {
+                return CommandError::failed((
+                    "لا يمكن دمج <> مع إعادة توجيه أخرى",
+                    "<> cannot be combined with other redirections",
+                )).into();
+            }
+            let args = self.expand_args(args);
+            self.trace(name, &args);
+            let args_str: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+            return self.execute_read_write(name, &args_str, redir);
+        }
+
         // Handle redirections
-        let stdin_redirect = redirects.iter().find(|r| r.kind == RedirectKind::In);
-        let stdout_redirect = redirects.iter().find(|r| r.kind == RedirectKind::Out || r.kind == RedirectKind::Append);
+        let stdin_redirects: Vec<&Redirect> = redirects
+            .iter()
+            .filter(|r| r.kind == RedirectKind::In)
+            .collect();
+        let stdout_redirects: Vec<&Redirect> = redirects
+            .iter()
+            .filter(|r| r.kind.writes_stdout())
+            .collect();
+        let writes_stderr = redirects.iter().any(|r| r.kind.writes_stderr());
+
+        // With noclobber, refuse before running anything: `>` (and `2>`,
+        // `&>`) may not truncate an existing regular file, `>|` and `>>` can
+        if self.noclobber {
+            for redir in redirects {
+                let truncates = matches!(redir.kind, RedirectKind::Out | RedirectKind::ErrOut | RedirectKind::BothOut);
+                let exists = std::fs::metadata(self.resolve(&redir.target)).map(|m| m.is_file()).unwrap_or(false);
+                if truncates && exists {
+                    return CommandError::failed((
+                        format!("لا يمكن الكتابة فوق ملف موجود '{}' (استخدم >|)", redir.target),
+                        format!("Cannot overwrite existing file '{}' (use >|)", redir.target),
+                    )).into();
+                }
+            }
+        }
+
+        if stdin_redirects.len() > 1 {
+            return CommandError::failed((
+                "لا يمكن استخدام أكثر من إعادة توجيه للمدخل",
+                "Multiple input redirections are not allowed",
+            )).into();
+        }
 
         // Get input from file if redirected
-        let actual_input = if let Some(redir) = stdin_redirect {
-            match std::fs::read_to_string(&redir.target) {
+        let actual_input = if let Some(redir) = stdin_redirects.first() {
+            match std::fs::read_to_string(self.resolve(&redir.target)) {
                 Ok(content) => Some(content),
                 Err(e) => {
-                    return CommandResult::Error(format!(
-                        "خطأ: لا يمكن قراءة '{}' - {} / Error: Cannot read '{}' - {}",
-                        redir.target, e, redir.target, e
-                    ));
+                    return CommandError::file(FileOp::Read, &redir.target, e).into();
                 }
             }
         } else {
@@ -142,42 +606,135 @@ impl Executor {
         };
 
         // Execute the command
+        let args = self.expand_args(args);
+        self.trace(name, &args);
         let args_str: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-        let result = self.execute_builtin_or_external(name, &args_str, actual_input);
-
-        // Handle output redirection
-        if let Some(redir) = stdout_redirect {
-            if let CommandResult::Success(output) = &result {
-                let file_result = if redir.kind == RedirectKind::Append {
-                    OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open(&redir.target)
-                } else {
-                    File::create(&redir.target)
-                };
+        // Builtins may highlight output that goes straight to the terminal
+        let writes_stdout = !stdout_redirects.is_empty();
+        let color = self.use_color && !self.piped && !writes_stdout;
+        let outer_color = std::mem::replace(&mut self.color, color);
+        let result = self.execute_builtin_or_external(name, &args_str, actual_input, color);
+        self.color = outer_color;
 
-                match file_result {
-                    Ok(mut file) => {
-                        if let Err(e) = file.write_all(output.as_bytes()) {
-                            return CommandResult::Error(format!(
-                                "خطأ: لا يمكن الكتابة إلى '{}' - {} / Error: Cannot write to '{}' - {}",
-                                redir.target, e, redir.target, e
-                            ));
+        // Output only needs redirecting if some target takes it: stdout for
+        // success, stderr (`2>`, `&>`) for error text
+        if (!writes_stdout && !writes_stderr) || result.is_exit() {
+            return result;
+        }
+        // Split the parts between the files and what is left for the
+        // terminal; a part that went to a file leaves its status behind
+        let (mut output, mut error) = (String::new(), String::new());
+        let mut remaining = Vec::new();
+        for part in result.parts() {
+            match part {
+                CommandResult::Success(text) if writes_stdout => {
+                    output.push_str(text);
+                    remaining.push(CommandResult::None);
+                }
+                CommandResult::Error(e) if writes_stderr => {
+                    if !e.is_silent() {
+                        error.push_str(&e.render(active_locale()));
+                        if !error.ends_with('\n') {
+                            error.push('\n');
                         }
-                        return CommandResult::None;
-                    }
-                    Err(e) => {
-                        return CommandResult::Error(format!(
-                            "خطأ: لا يمكن فتح '{}' - {} / Error: Cannot open '{}' - {}",
-                            redir.target, e, redir.target, e
-                        ));
                     }
+                    // The error went to the file; the command still failed
+                    remaining.push(CommandError::Silent.into());
                 }
+                part => remaining.push(part.clone()),
             }
         }
 
-        result
+        // Every target is opened in order (so earlier `>` targets are
+        // truncated); the last one for each stream receives it. A command
+        // with nothing to write still creates/truncates the files.
+        let mut files = Vec::new();
+        let (mut stdout_file, mut stderr_file) = (None, None);
+        for redir in redirects.iter().filter(|r| r.kind.writes_stdout() || r.kind.writes_stderr()) {
+            match self.open_output_redirect(redir) {
+                Ok(file) => files.push((file, redir)),
+                Err(e) => {
+                    return CommandError::file(FileOp::Open, &redir.target, e).into();
+                }
+            }
+            if redir.kind.writes_stdout() {
+                stdout_file = Some(files.len() - 1);
+            }
+            if redir.kind.writes_stderr() {
+                stderr_file = Some(files.len() - 1);
+            }
+        }
+
+        for (index, text) in [(stdout_file, output.as_str()), (stderr_file, error.as_str())] {
+            let Some((file, redir)) = index.map(|i| &mut files[i]) else { continue };
+            if let Err(e) = file.write_all(text.as_bytes()) {
+                return CommandError::file(FileOp::Write, &redir.target, e).into();
+            }
+        }
+
+        // Output with only stderr redirected still goes to the terminal
+        CommandResult::join(remaining)
+    }
+
+    /// Run `left`, then `right` if its success matches `run_on_success`
+    ///
+    /// Output from both sides is kept, and `last_exit_code` is always the
+    /// status of whichever command ran last, so `$?` after the chain is
+    /// right: a failure that set no code (a redirect that couldn't open its
+    /// file) counts as 1.
+    fn execute_chain(&mut self, left: Command, right: Command, run_on_success: bool) -> CommandResult {
+        let mut collected = Collected::default();
+        let result = self.execute(left);
+        let succeeded = self.succeeded(&result);
+        if !succeeded && self.last_exit_code == 0 {
+            self.last_exit_code = 1;
+        }
+        if collected.push(result) {
+            return collected.finish();
+        }
+
+        if succeeded == run_on_success {
+            let result = self.execute(right);
+            if !result.is_success() && self.last_exit_code == 0 {
+                self.last_exit_code = 1;
+            }
+            if collected.push(result) {
+                return collected.finish();
+            }
+        }
+        collected.finish()
+    }
+
+    /// Whether a command's result counts as success for `&&`, `||`, `!` and
+    /// `اذا`: it didn't fail and didn't set a nonzero exit code
+    fn succeeded(&self, result: &CommandResult) -> bool {
+        result.is_success() && self.last_exit_code == 0
+    }
+
+    /// Print a command about to run, after expansion, when xtrace is on
+    fn trace(&self, name: &str, args: &[String]) {
+        if self.xtrace {
+            let mut line = format!("+ {}", name);
+            for arg in args {
+                line.push(' ');
+                line.push_str(arg);
+            }
+            self.print_error(&line);
+        }
+    }
+
+    /// Open the file for an output (`>`, `>|`, `2>`, `&>`) or append (`>>`,
+    /// `2>>`, `&>>`) redirect
+    fn open_output_redirect(&self, redir: &Redirect) -> std::io::Result<File> {
+        let path = self.resolve(&redir.target);
+        if redir.kind.appends() {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+        } else {
+            File::create(path)
+        }
     }
 
     fn execute_pipeline(&mut self, cmds: Vec<Command>) -> CommandResult {
@@ -186,37 +743,106 @@ impl Executor {
         }
 
         let mut input: Option<String> = None;
+        let outer = self.piped;
+        let last = cmds.len() - 1;
+        // Errors from earlier stages, which aren't piped
+        let mut reported = Vec::new();
 
-        for cmd in cmds.into_iter() {
+        for (i, cmd) in cmds.into_iter().enumerate() {
+            // Only the last stage's output can reach the terminal
+            self.piped = outer || i < last;
             let result = self.execute_with_input(cmd, input.take());
+            self.piped = outer;
 
-            match result {
-                CommandResult::Success(output) => {
-                    input = Some(output);
-                }
-                CommandResult::Error(_) | CommandResult::Exit(_) => {
-                    return result;
-                }
-                CommandResult::None => {
-                    // No output to pipe
+            if i == last {
+                reported.push(result);
+                break;
+            }
+            let failed = !result.is_success();
+            for part in result.parts() {
+                match part {
+                    CommandResult::Success(output) if !failed => {
+                        input.get_or_insert_with(String::new).push_str(output);
+                    }
+                    // A failed stage stops the pipeline; its output goes nowhere
+                    CommandResult::Success(_) | CommandResult::None => {}
+                    part => reported.push(part.clone()),
                 }
             }
+            if failed {
+                break;
+            }
         }
+        CommandResult::join(reported)
+    }
 
-        match input {
-            Some(output) => {
-                // Print final output with Arabic shaping and RTL alignment
+    /// Print output with Arabic shaping and RTL alignment
+    ///
+    /// A background job appends the lines, unshaped, to its buffer instead.
+    fn print_output(&self, output: &str) {
+        if let Some(capture) = &self.capture {
+            if let Ok(mut buffer) = capture.lock() {
                 for line in output.lines() {
-                    let shaped = shape_if_arabic(line);
-                    if self.use_rtl_padding && contains_arabic(&shaped) {
-                        println!("{}", right_align(&shaped));
-                    } else {
-                        println!("{}", shaped);
-                    }
+                    buffer.push_str(&self.stamp_line(line));
+                    buffer.push('\n');
                 }
-                CommandResult::None
             }
-            None => CommandResult::None,
+            return;
+        }
+        for line in output.lines() {
+            println!("{}", self.format_output_line(line));
+        }
+    }
+
+    /// Print a result's output and errors in the order they happened
+    fn print_result(&self, result: &CommandResult) {
+        for part in result.parts() {
+            match part {
+                CommandResult::Success(output) => self.print_output(output),
+                CommandResult::Error(error) if !error.is_silent() => self.print_error(&error.render(active_locale())),
+                _ => {}
+            }
+        }
+    }
+
+    /// Print error text on stderr, shaped; a background job appends it to
+    /// its buffer instead
+    pub(crate) fn print_error(&self, text: &str) {
+        commands::print_error(text, self.capture.as_deref());
+    }
+
+    /// Shape and align one line of output for the terminal
+    ///
+    /// Colored output from external commands (`ls --color`, `git`) passes
+    /// through untouched unless the line contains Arabic; only then is it
+    /// shaped and right-aligned, with the color escapes kept in place.
+    fn format_output_line(&self, line: &str) -> String {
+        if !contains_arabic(line) {
+            return self.stamp_line(line);
+        }
+        let shaped = self.stamp_line(&shape_if_arabic(line));
+        if self.use_rtl_padding {
+            right_align(&shaped)
+        } else {
+            shaped
+        }
+    }
+
+    /// Record a background job's final result in its buffer
+    ///
+    /// Returns the job's exit code.
+    fn capture_result(&mut self, result: &CommandResult) -> i32 {
+        for part in result.parts() {
+            match part {
+                CommandResult::Success(output) => self.print_output(output),
+                CommandResult::Error(error) if !error.is_silent() => self.print_error(&error.render(active_locale())),
+                _ => {}
+            }
+        }
+        match result.status() {
+            CommandResult::Exit(code) => *code,
+            CommandResult::Error(_) if self.last_exit_code == 0 => 1,
+            _ => self.last_exit_code,
         }
     }
 
@@ -225,15 +851,90 @@ impl Executor {
         name: &str,
         args: &[&str],
         input: Option<String>,
+        color: bool,
     ) -> CommandResult {
-        // Try builtin command first
-        if let Some(result) = commands::execute_builtin(name, args, input.as_deref()) {
-            self.last_exit_code = if result.is_success() { 0 } else { 1 };
+        if let Some(result) = self.execute_builtin(name, args, input.as_deref(), color) {
             return result;
         }
 
         // Fall back to external command
-        self.execute_external(name, args, input)
+        self.execute_external(name, args, input, None)
+    }
+
+    /// Run `name` if it is a builtin, recording its exit code
+    fn execute_builtin(&mut self, name: &str, args: &[&str], input: Option<&str>, color: bool) -> Option<CommandResult> {
+        // Builtins that change shell state need the executor itself. One
+        // that runs another command (`وقت`) keeps that command's exit code.
+        let previous = std::mem::replace(&mut self.last_exit_code, 0);
+        let result = match commands::execute_shell_builtin(self, name, args)
+            .or_else(|| {
+                let options = commands::BuiltinOptions {
+                    color,
+                    dry_run: self.dry_run,
+                    cwd: &self.cwd,
+                    stderr: self.capture.as_deref(),
+                };
+                commands::execute_builtin_with(name, args, input, options)
+            })
+        {
+            Some(result) => result,
+            None => {
+                self.last_exit_code = previous;
+                return None;
+            }
+        };
+        // A builtin may succeed with output yet report a nonzero status
+        // (`مقارنة` on differing files), so success keeps the code it set
+        if !result.is_success() && self.last_exit_code == 0 {
+            self.last_exit_code = 1;
+        }
+        Some(result)
+    }
+
+    /// Run a command with a `<>` redirect
+    ///
+    /// The target is opened for reading and writing without truncation
+    /// (created if missing). External commands get the file itself as stdin
+    /// and stdout, sharing one offset; builtins read its contents as input
+    /// and their output is written after what they read.
+    fn execute_read_write(&mut self, name: &str, args: &[&str], redir: &Redirect) -> CommandResult {
+        let open = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.resolve(&redir.target));
+        let mut file = match open {
+            Ok(file) => file,
+            Err(e) => {
+                return CommandError::file(FileOp::Open, &redir.target, e).into();
+            }
+        };
+
+        let mut content = String::new();
+        if let Err(e) = file.read_to_string(&mut content) {
+            return CommandError::file(FileOp::Read, &redir.target, e).into();
+        }
+
+        match self.execute_builtin(name, args, Some(&content), false) {
+            Some(CommandResult::Success(output)) => {
+                if let Err(e) = file.write_all(output.as_bytes()) {
+                    return CommandError::file(FileOp::Write, &redir.target, e).into();
+                }
+                CommandResult::None
+            }
+            Some(result) => result,
+            None => {
+                // The external command starts reading from the beginning
+                if let Err(e) = file.rewind() {
+                    return CommandError::file(FileOp::Read, &redir.target, e).into();
+                }
+                match self.execute_external(name, args, None, Some(&file)) {
+                    CommandResult::Success(output) if output.is_empty() => CommandResult::None,
+                    result => result,
+                }
+            }
+        }
     }
 
     fn execute_external(
@@ -241,17 +942,40 @@ impl Executor {
         name: &str,
         args: &[&str],
         input: Option<String>,
+        stdio: Option<&File>,
     ) -> CommandResult {
-        let mut cmd = ProcessCommand::new(name);
-        cmd.args(args);
+        let spawn = |program: &OsStr| {
+            let mut cmd = ProcessCommand::new(program);
+            cmd.args(args).current_dir(&self.cwd).envs(self.command_env.iter().cloned());
 
-        if input.is_some() {
-            cmd.stdin(Stdio::piped());
-        }
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
+            // A `<>` file is used directly as both stdin and stdout
+            if let Some(file) = stdio {
+                cmd.stdin(file.try_clone()?);
+                cmd.stdout(file.try_clone()?);
+            } else {
+                if input.is_some() {
+                    cmd.stdin(Stdio::piped());
+                } else if self.capture.is_some() {
+                    // Background jobs must not read from the terminal
+                    cmd.stdin(Stdio::null());
+                }
+                cmd.stdout(Stdio::piped());
+            }
+            cmd.stderr(Stdio::piped());
+            cmd.spawn()
+        };
 
-        match cmd.spawn() {
+        // Use the cached PATH lookup; if that binary is gone, forget it and
+        // let the OS search PATH afresh
+        let spawned = match self.command_cache.resolve(name) {
+            Some(program) => spawn(program.as_os_str()).or_else(|_| {
+                self.command_cache.forget(name);
+                spawn(OsStr::new(name))
+            }),
+            None => spawn(OsStr::new(name)),
+        };
+
+        match spawned {
             Ok(mut child) => {
                 // Write input if provided
                 if let Some(input_str) = input {
@@ -270,31 +994,664 @@ impl Executor {
                         } else {
                             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
                             if !stderr.is_empty() {
-                                CommandResult::Error(stderr)
+                                CommandError::Stderr(stderr).into()
                             } else {
-                                CommandResult::Error(format!(
-                                    "الأمر انتهى برمز: {} / Command exited with code: {}",
-                                    self.last_exit_code, self.last_exit_code
-                                ))
+                                CommandError::Exited(self.last_exit_code).into()
                             }
                         }
                     }
                     Err(e) => {
                         self.last_exit_code = 1;
-                        CommandResult::Error(format!(
-                            "خطأ: فشل في انتظار الأمر - {} / Error: Failed to wait for command - {}",
-                            e, e
-                        ))
+                        CommandError::failed((
+                            format!("فشل في انتظار الأمر - {}", e),
+                            format!("Failed to wait for command - {}", e),
+                        )).into()
                     }
                 }
             }
             Err(e) => {
                 self.last_exit_code = 127;
-                CommandResult::Error(format!(
-                    "خطأ: الأمر '{}' غير موجود - {} / Error: Command '{}' not found - {}",
-                    name, e, name, e
-                ))
+                let suggestion = (e.kind() == io::ErrorKind::NotFound)
+                    .then(|| suggest::suggest_command(name))
+                    .flatten();
+                CommandError::NotFound { name: name.to_string(), suggestion, source: e }.into()
             }
         }
     }
 }
+
+/// Joins the results of commands that run one after another
+///
+/// Results are kept in order so a sequence or loop can be piped or printed
+/// as a whole: an error from any command but the last is reported where it
+/// happened, between the output around it, and the remaining commands
+/// still run.
+#[derive(Default)]
+struct Collected {
+    results: Vec<CommandResult>,
+}
+
+impl Collected {
+    /// Record a result; returns whether it asked the shell to exit
+    fn push(&mut self, result: CommandResult) -> bool {
+        let exits = result.is_exit();
+        self.results.push(result);
+        exits
+    }
+
+    /// The combined result, ending with the last command's status
+    fn finish(self) -> CommandResult {
+        CommandResult::join(self.results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple(name: &str, args: &[&str], redirects: Vec<Redirect>) -> Command {
+        Command::Simple {
+            name: name.to_string(),
+            args: args.iter().map(|a| Word::from(*a)).collect(),
+            redirects,
+        }
+    }
+
+    #[test]
+    fn test_multiple_output_redirects_last_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        std::fs::write(&a, "old").unwrap();
+
+        let cmd = simple("اطبع", &["نص"], vec![
+            Redirect::new(RedirectKind::Out, a.to_string_lossy().to_string()),
+            Redirect::new(RedirectKind::Out, b.to_string_lossy().to_string()),
+        ]);
+        let result = Executor::default().execute(cmd);
+
+        assert_eq!(result, CommandResult::None);
+        assert_eq!(std::fs::read_to_string(&a).unwrap(), "");
+        assert_eq!(std::fs::read_to_string(&b).unwrap(), "نص\n");
+    }
+
+    #[test]
+    fn test_failed_command_still_truncates_and_creates_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("existing.txt");
+        let created = dir.path().join("created.txt");
+        std::fs::write(&existing, "old").unwrap();
+
+        let mut executor = Executor::default();
+        for out in [&existing, &created] {
+            let result = executor.execute_str(&format!("اقرأ /nonexistent/ocean-file > {}", out.display())).unwrap();
+            assert!(matches!(result, CommandResult::Error(CommandError::File { .. })), "{:?}", result);
+            assert_eq!(std::fs::read_to_string(out).unwrap(), "");
+        }
+    }
+
+    #[test]
+    fn test_multiple_input_redirects_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let x = dir.path().join("x.txt");
+        let y = dir.path().join("y.txt");
+        std::fs::write(&x, "x").unwrap();
+        std::fs::write(&y, "y").unwrap();
+
+        let cmd = simple("cat", &[], vec![
+            Redirect::new(RedirectKind::In, x.to_string_lossy().to_string()),
+            Redirect::new(RedirectKind::In, y.to_string_lossy().to_string()),
+        ]);
+        let result = Executor::default().execute(cmd);
+
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("Multiple input")));
+    }
+
+    #[test]
+    fn test_noclobber_refuses_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("keep.txt");
+        let target = file.to_string_lossy().to_string();
+        std::fs::write(&file, "old").unwrap();
+
+        let mut executor = Executor { noclobber: true, ..Executor::default() };
+        let result = executor.execute(simple("اطبع", &["جديد"], vec![Redirect::new(RedirectKind::Out, target.clone())]));
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("use >|")));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "old");
+
+        // `>>` and `>|` still write; `>` can create new files
+        executor.execute(simple("اطبع", &["أ"], vec![Redirect::new(RedirectKind::Append, target.clone())]));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "oldأ\n");
+        executor.execute(simple("اطبع", &["ب"], vec![Redirect::new(RedirectKind::Clobber, target)]));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "ب\n");
+        let new = dir.path().join("new.txt");
+        executor.execute(simple("اطبع", &["ج"], vec![Redirect::new(RedirectKind::Out, new.to_string_lossy().to_string())]));
+        assert_eq!(std::fs::read_to_string(&new).unwrap(), "ج\n");
+    }
+
+    #[test]
+    fn test_read_write_redirect_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("rw.txt");
+        std::fs::write(&file, "سطر\n").unwrap();
+
+        let cmd = simple("cat", &[], vec![Redirect::new(RedirectKind::ReadWrite, file.to_string_lossy().to_string())]);
+        assert_eq!(Executor::default().execute(cmd), CommandResult::None);
+        // The output lands after what was read, nothing is truncated
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "سطر\nسطر\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_write_redirect_external() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("rw.txt");
+        std::fs::write(&file, "abc\n").unwrap();
+        let target = file.to_string_lossy().to_string();
+
+        // The reply is written at the offset left by the read
+        let result = run(&format!("sh -c 'read line; echo \"got $line\"' <> {}", target));
+        assert_eq!(result, CommandResult::None);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "abc\ngot abc\n");
+
+        // A missing target is created
+        let created = dir.path().join("new.txt");
+        let cmd = simple("true", &[], vec![Redirect::new(RedirectKind::ReadWrite, created.to_string_lossy().to_string())]);
+        assert_eq!(Executor::default().execute(cmd), CommandResult::None);
+        assert!(created.exists());
+    }
+
+    #[test]
+    fn test_read_write_redirect_not_combined() {
+        let cmd = simple("cat", &[], vec![
+            Redirect::new(RedirectKind::ReadWrite, "a".to_string()),
+            Redirect::new(RedirectKind::Out, "b".to_string()),
+        ]);
+        let result = Executor::default().execute(cmd);
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("cannot be combined")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_external_commands_use_executor_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = Executor::default();
+        executor.set_cwd(dir.path()).unwrap();
+
+        let result = executor.execute_external("sh", &["-c", "pwd -P"], None, None);
+        let expected = format!("{}\n", dir.path().canonicalize().unwrap().display());
+        assert_eq!(result, CommandResult::Success(expected));
+    }
+
+    #[test]
+    fn test_executors_keep_their_own_cwd() {
+        let run = |executor: &mut Executor, line: &str| {
+            let tokens = crate::lexer::Lexer::new(line).tokenize();
+            executor.execute(crate::parser::Parser::new(tokens).parse().unwrap())
+        };
+        let process_cwd = std::env::current_dir().unwrap();
+        let (first_dir, second_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let mut first = Executor::default();
+        first.set_cwd(first_dir.path()).unwrap();
+        let mut second = Executor::default();
+        second.set_cwd(second_dir.path()).unwrap();
+
+        // Each redirect, builtin and glob lands in its own executor's directory
+        run(&mut first, "echo one > out.txt");
+        run(&mut second, "echo two > out.txt");
+        run(&mut first, "touch first.tmp");
+        run(&mut second, "touch second.tmp");
+        assert_eq!(run(&mut first, "cat out.txt"), CommandResult::Success("one\n".to_string()));
+        assert_eq!(run(&mut second, "cat out.txt"), CommandResult::Success("two\n".to_string()));
+        assert_eq!(run(&mut first, "echo *.tmp"), CommandResult::Success("first.tmp\n".to_string()));
+        assert_eq!(run(&mut second, "echo *.tmp"), CommandResult::Success("second.tmp\n".to_string()));
+        assert_eq!(std::env::current_dir().unwrap(), process_cwd);
+    }
+
+    #[test]
+    fn test_background_output_is_buffered_in_the_job() {
+        let mut executor = Executor::default();
+        let tokens = crate::lexer::Lexer::new("اطبع مرحبا &").tokenize();
+        let cmd = crate::parser::Parser::new(tokens).parse().unwrap();
+
+        assert_eq!(executor.execute(cmd), CommandResult::None);
+        let jobs = executor.jobs.wait_all();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].output(), "مرحبا\n");
+        assert_eq!(jobs[0].notice(crate::utils::Locale::Both), "[1] انتهى / Done  اطبع مرحبا");
+    }
+
+    #[test]
+    fn test_background_failure_is_reported_with_its_exit_code() {
+        let mut executor = Executor::default();
+        let cmd = Command::Background(Box::new(simple("sh", &["-c", "exit 3"], vec![])));
+
+        assert_eq!(executor.execute(cmd), CommandResult::None);
+        assert_eq!(executor.last_exit_code, 0);
+        let jobs = executor.jobs.wait_all();
+        assert_eq!(jobs[0].status, Some(3));
+    }
+
+    #[test]
+    fn test_background_stderr_is_buffered_in_the_job() {
+        let mut executor = Executor::default();
+        let tokens = crate::lexer::Lexer::new("eprintln oops &").tokenize();
+        let cmd = crate::parser::Parser::new(tokens).parse().unwrap();
+
+        assert_eq!(executor.execute(cmd), CommandResult::None);
+        let jobs = executor.jobs.wait_all();
+        assert_eq!(jobs[0].output(), "oops\n");
+    }
+
+    #[test]
+    fn test_globs_expand_before_builtins() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.tmp", "b.tmp", "keep.txt"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let pattern = format!("{}/*.tmp", dir.path().display());
+
+        for name in ["اعرض", "ls"] {
+            let result = run(&format!("{} --json {}", name, pattern));
+            let output = result.output().unwrap().to_string();
+            assert!(output.contains("a.tmp") && output.contains("b.tmp"));
+            assert!(!output.contains("keep.txt"));
+        }
+
+        assert_eq!(run(&format!("احذف {}", pattern)), CommandResult::None);
+        assert!(!dir.path().join("a.tmp").exists() && !dir.path().join("b.tmp").exists());
+        assert!(dir.path().join("keep.txt").exists());
+
+        // No matches: rm gets the literal pattern and reports it missing
+        let result = run(&format!("rm {}", pattern));
+        assert!(matches!(result, CommandResult::Error(msg) if msg.to_string().contains("*.tmp")));
+        assert!(dir.path().join("keep.txt").exists());
+
+        // Quoted patterns are never expanded
+        std::fs::write(dir.path().join("c.tmp"), "").unwrap();
+        assert!(!run(&format!("rm \"{}\"", pattern)).is_success());
+        assert!(dir.path().join("c.tmp").exists());
+    }
+
+    #[test]
+    fn test_colored_ascii_output_passes_through() {
+        let executor = Executor::new(true);
+        for line in ["\x1b[01;34msrc\x1b[0m", "\x1b[32m+ added\x1b[m", "plain"] {
+            assert_eq!(executor.format_output_line(line), line);
+        }
+    }
+
+    #[test]
+    fn test_colored_arabic_output_is_shaped_and_aligned() {
+        let executor = Executor::new(true);
+        let line = executor.format_output_line("\x1b[01;34mمجلد\x1b[0m");
+        assert!(line.starts_with(' '));
+        assert_eq!(line.trim_start(), format!("\x1b[01;34m{}\x1b[0m", shape_if_arabic("مجلد")));
+    }
+
+    #[test]
+    fn test_execute_str_runs_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let mut executor = Executor::default();
+
+        let line = format!("اطبع 'ocean shell' | بدل a-z A-Z | قص -d ' ' -f2 > {}", out.display());
+        assert_eq!(executor.execute_str(&line).unwrap(), CommandResult::None);
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "SHELL\n");
+
+        let error = executor.execute_str("اطبع |").unwrap_err();
+        assert!(error.to_string().contains("اطبع |"));
+
+        let error = executor.execute_str("اطبع \"abc").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "خطأ نحوي / Parse error [1:6]: نص غير مكتمل / Unterminated string\n  اطبع \"abc\n       ^^^^"
+        );
+    }
+
+    #[test]
+    fn test_redirect_to_quoted_target_with_spaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("has space.txt");
+        let mut executor = Executor::default();
+
+        let line = format!("اطبع x > \"{}\" ; echo y >> '{}'", out.display(), out.display());
+        assert_eq!(executor.execute_str(&line).unwrap(), CommandResult::None);
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "x\ny\n");
+    }
+
+    #[test]
+    fn test_assignment_sets_shell_variable() {
+        let mut executor = Executor::default();
+        assert_eq!(executor.execute_str("اسم=محيط ; عدد=$(اطبع ٣)").unwrap(), CommandResult::None);
+        assert_eq!(executor.variables.get("اسم").map(String::as_str), Some("محيط"));
+        assert_eq!(executor.variables.get("عدد").map(String::as_str), Some("٣"));
+        assert_eq!(executor.execute_str("اطبع $اسم").unwrap(), CommandResult::Success("محيط\n".to_string()));
+
+        // Values aren't split or globbed
+        executor.execute_str("x=\"a   b\" ; y=*").unwrap();
+        assert_eq!(executor.variables.get("x").map(String::as_str), Some("a   b"));
+        assert_eq!(executor.variables.get("y").map(String::as_str), Some("*"));
+    }
+
+    #[test]
+    fn test_prefix_assignment_applies_to_one_command() {
+        let mut executor = Executor::default();
+        executor.execute_str("A=outer").unwrap();
+        let result = executor.execute_str("A=inner B=2 اطبع $A $B").unwrap();
+        assert_eq!(result, CommandResult::Success("outer\n".to_string()));
+        assert_eq!(executor.variables.get("A").map(String::as_str), Some("outer"));
+        assert!(!executor.variables.contains_key("B"));
+
+        #[cfg(unix)]
+        {
+            let result = executor.execute_str("OCEAN_TEST_VALUE=محيط sh -c 'echo $OCEAN_TEST_VALUE'").unwrap();
+            assert_eq!(result, CommandResult::Success("محيط\n".to_string()));
+            // The command's environment still gets the inner value
+            let result = executor.execute_str("A=inner sh -c 'echo $A'").unwrap();
+            assert_eq!(result, CommandResult::Success("inner\n".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_errexit_stops_sequence_and_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let mut executor = Executor::default();
+
+        let line = format!("cat /nonexistent/ocean-file ; اطبع بعد >> {}", out.display());
+        assert!(executor.execute_str(&line).unwrap().is_success());
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "بعد\n");
+
+        executor.errexit = true;
+        assert!(!executor.execute_str(&line).unwrap().is_success());
+        let line = format!("لكل x في أ ب ; نفذ cat /nonexistent/$x ; انتهى ; اطبع بعد >> {}", out.display());
+        assert!(!executor.execute_str(&line).unwrap().is_success());
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "بعد\n");
+    }
+
+    #[test]
+    fn test_tee_mid_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let (saved, end) = (dir.path().join("saved.txt"), dir.path().join("end.txt"));
+        let line = format!("اطبع تفاح | حفظ {} | بدل ت ط > {}", saved.display(), end.display());
+        Executor::default().execute_str(&line).unwrap();
+        assert_eq!(std::fs::read_to_string(&saved).unwrap(), "تفاح\n");
+        assert_eq!(std::fs::read_to_string(&end).unwrap(), "طفاح\n");
+    }
+
+    #[test]
+    fn test_and_or_exit_code_is_last_command_run() {
+        let mut executor = Executor::default();
+        executor.execute_str("false || true").unwrap();
+        assert_eq!(executor.last_exit_code, 0);
+        executor.execute_str("true && false").unwrap();
+        assert_ne!(executor.last_exit_code, 0);
+        executor.execute_str("false && true").unwrap();
+        assert_ne!(executor.last_exit_code, 0);
+
+        // A redirect that can't open its file fails without running anything
+        let result = executor.execute_str("true ; اطبع x > /nonexistent/ocean-dir/out || اطبع $?").unwrap();
+        let [CommandResult::Error(CommandError::File { .. }), output] = result.parts() else {
+            panic!("expected the open error, then the output: {:?}", result);
+        };
+        assert_eq!(output, &CommandResult::Success("1\n".to_string()));
+    }
+
+    #[test]
+    fn test_and_or_keep_output_of_both_sides() {
+        let mut executor = Executor::default();
+        let result = executor.execute_str("اطبع أ && اطبع ب").unwrap();
+        assert_eq!(result, CommandResult::Success("أ\nب\n".to_string()));
+        let result = executor.execute_str("اختبار -f /nonexistent/ocean-file || اطبع بديل").unwrap();
+        assert_eq!(result, CommandResult::Success("بديل\n".to_string()));
+    }
+
+    #[test]
+    fn test_unknown_command_suggests_builtin() {
+        let mut executor = Executor::default();
+        let result = executor.execute_str("اطع مرحبا").unwrap();
+        let CommandResult::Error(error) = result else { panic!("expected an error") };
+        assert!(matches!(&error, CommandError::NotFound { suggestion: Some(s), .. } if s == "اطبع"));
+        assert!(error.to_string().contains("هل تقصد 'اطبع'؟") && error.to_string().contains("Did you mean 'اطبع'?"));
+        assert_eq!(executor.last_exit_code, 127);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_silent_external_failure_keeps_exit_code() {
+        let mut executor = Executor::default();
+        assert_eq!(executor.execute_str("false").unwrap(), CommandResult::Error(CommandError::Exited(1)));
+    }
+
+    #[test]
+    fn test_not_inverts_status() {
+        let mut executor = Executor::default();
+        let result = executor.execute_str("! اختبار -f /nonexistent/ocean-file && اطبع مفقود").unwrap();
+        assert_eq!(result, CommandResult::Success("مفقود\n".to_string()));
+
+        let result = executor.execute_str("! cat /nonexistent/ocean-file").unwrap();
+        assert!(matches!(result.parts(), [CommandResult::Error(_), CommandResult::None]));
+        assert!(result.is_success());
+        assert_eq!(executor.last_exit_code, 0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let line = format!("! اطبع نص > {} || echo failed", out.display());
+        assert_eq!(executor.execute_str(&line).unwrap(), CommandResult::Success("failed\n".to_string()));
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "نص\n");
+        assert_eq!(executor.execute_str("! true").unwrap(), CommandResult::Error(CommandError::Silent));
+        assert_eq!(executor.last_exit_code, 1);
+
+        // errexit ignores negated commands
+        executor.errexit = true;
+        assert_eq!(executor.execute_str("! true ; echo after").unwrap(), CommandResult::Success("after\n".to_string()));
+    }
+
+    #[test]
+    fn test_stderr_and_combined_redirects() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("log.txt");
+        let err = dir.path().join("err.txt");
+        let mut executor = Executor::default();
+
+        // `2>` alone leaves output on stdout and still creates the file
+        let line = format!("اطبع نص 2> {}", err.display());
+        assert_eq!(executor.execute_str(&line).unwrap(), CommandResult::Success("نص\n".to_string()));
+        assert_eq!(std::fs::read_to_string(&err).unwrap(), "");
+
+        for _ in 0..2 {
+            let line = format!("cat /nonexistent/ocean-file 2>> {}", err.display());
+            assert_eq!(executor.execute_str(&line).unwrap(), CommandResult::Error(CommandError::Silent));
+            assert_eq!(executor.last_exit_code, 1);
+        }
+        let errors = std::fs::read_to_string(&err).unwrap();
+        assert_eq!(errors.matches("Cannot read").count(), 2);
+
+        let line = format!("اطبع أ &> {} ; اقرأ /nonexistent/ocean-file &>> {}", log.display(), log.display());
+        executor.execute_str(&line).unwrap();
+        let logged = std::fs::read_to_string(&log).unwrap();
+        assert!(logged.starts_with("أ\n") && logged.contains("Cannot read") && logged.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_search_output_is_plain_when_redirected_or_piped() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, "ocean\nland\n").unwrap();
+        let out = dir.path().join("out.txt");
+
+        for line in [
+            format!("ابحث ocean {} > {}", notes.display(), out.display()),
+            format!("grep ocean {} | cut -c 1-100 > {}", notes.display(), out.display()),
+        ] {
+            let mut executor = Executor { use_color: true, ..Executor::default() };
+            assert_eq!(executor.execute_str(&line).unwrap(), CommandResult::None);
+            assert_eq!(std::fs::read_to_string(&out).unwrap(), "1:ocean\n");
+        }
+    }
+
+    #[test]
+    fn test_diff_colors_from_theme_on_terminal() {
+        use crate::utils::{active_theme, paint};
+
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+        std::fs::write(&a, "أ\nب\n").unwrap();
+        std::fs::write(&b, "أ\nج\n").unwrap();
+
+        let theme = active_theme();
+        let mut executor = Executor { use_color: true, ..Executor::default() };
+        let result = executor.execute_str(&format!("diff {} {}", a.display(), b.display())).unwrap();
+        let expected = format!("{}\n{}\n", paint("-ب", theme.error, false), paint("+ج", theme.success, false));
+        assert_eq!(result, CommandResult::Success(expected));
+    }
+
+    fn run(input: &str) -> CommandResult {
+        let tokens = crate::lexer::Lexer::new(input).tokenize();
+        let cmd = crate::parser::Parser::new(tokens).parse().unwrap();
+        Executor::default().execute(cmd)
+    }
+
+    #[test]
+    fn test_sequence_joins_output() {
+        assert_eq!(run("اطبع أ ; اطبع ب"), CommandResult::Success("أ\nب\n".to_string()));
+        assert_eq!(run("اطبع أ\nاطبع ب\n"), CommandResult::Success("أ\nب\n".to_string()));
+    }
+
+    #[test]
+    fn test_sequence_keeps_errors_in_order() {
+        let result = run("اطبع أ ; اقرأ /nonexistent/ocean-file ; اطبع ب");
+        let [first, CommandResult::Error(CommandError::File { .. }), last] = result.parts() else {
+            panic!("expected output, the error, then output: {:?}", result);
+        };
+        assert_eq!(first, &CommandResult::Success("أ\n".to_string()));
+        assert_eq!(last, &CommandResult::Success("ب\n".to_string()));
+        assert!(result.is_success());
+    }
+
+    #[test]
+    fn test_loop_output_goes_through_pipe() {
+        // The errors are reported; only the output reaches the next stage
+        let result = run("لكل x في أ ب ; نفذ اقرأ /nonexistent/ocean-file ; اطبع $x ; انتهى | رأس -n 1");
+        let [CommandResult::Error(_), CommandResult::Error(_), output] = result.parts() else {
+            panic!("expected both errors, then the piped output: {:?}", result);
+        };
+        assert_eq!(output, &CommandResult::Success("أ\n".to_string()));
+    }
+
+    #[test]
+    fn test_for_loop_arabic() {
+        let result = run("لكل x في أ ب ج ; نفذ اطبع $x ; انتهى");
+        assert_eq!(result, CommandResult::Success("أ\nب\nج\n".to_string()));
+    }
+
+    #[test]
+    fn test_for_loop_english_nested() {
+        let result = run("for x in 1 2 ; do for y in a b ; do echo $x$y ; done ; done");
+        assert_eq!(result, CommandResult::Success("1a\n1b\n2a\n2b\n".to_string()));
+    }
+
+    #[test]
+    fn test_for_loop_over_glob() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("one.txt"), "1\n").unwrap();
+        std::fs::write(dir.path().join("two.txt"), "2\n").unwrap();
+        std::fs::write(dir.path().join("skip.md"), "x\n").unwrap();
+
+        let result = run(&format!("لكل ملف في {}/*.txt ; نفذ اقرأ $ملف ; انتهى", dir.path().display()));
+        assert_eq!(result, CommandResult::Success("1\n2\n".to_string()));
+    }
+
+    #[test]
+    fn test_for_loop_keeps_variable() {
+        let tokens = crate::lexer::Lexer::new("for x in a b ; do echo $x ; done").tokenize();
+        let cmd = crate::parser::Parser::new(tokens).parse().unwrap();
+        let mut executor = Executor::default();
+        executor.execute(cmd);
+        assert_eq!(executor.variable("x").as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_while_loop_counts_with_test() {
+        let mut executor = Executor::default();
+        let result = executor
+            .execute_str("n=x ; طالما اختبار $n != xxxx ; نفذ اطبع $n ; n=${n}x ; انتهى")
+            .unwrap();
+        assert_eq!(result, CommandResult::Success("x\nxx\nxxx\n".to_string()));
+        assert_eq!(executor.variable("n").as_deref(), Some("xxxx"));
+        assert_eq!(executor.last_exit_code, 0);
+    }
+
+    #[test]
+    fn test_while_loop_false_condition_runs_nothing() {
+        let mut executor = Executor::default();
+        let result = executor.execute_str("while test a = b ; do echo never ; done").unwrap();
+        assert_eq!(result, CommandResult::None);
+        assert_eq!(executor.last_exit_code, 0);
+    }
+
+    #[test]
+    fn test_select_reprompts_until_end_of_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let tokens = crate::lexer::Lexer::new(&format!("اطبع $x >> {}", out.display())).tokenize();
+        let body = crate::parser::Parser::new(tokens).parse().unwrap();
+        let items: Vec<String> = ["أ", "ب", "ج"].iter().map(|s| s.to_string()).collect();
+
+        let mut executor = Executor::default();
+        let mut input = io::Cursor::new("2\n9\nلا\n\n١\n");
+        executor.execute_select("x", &items, &body, &mut input);
+
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "ب\nأ\n");
+        assert_eq!(executor.variable("x").as_deref(), Some("أ"));
+        assert_eq!(executor.last_exit_code, 0);
+    }
+
+    #[test]
+    fn test_if_else_arabic() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("x");
+        let cmd = |path: &std::path::Path| {
+            format!("اذا اختبار -f {} ; ثم اطبع موجود ; والا اطبع مفقود ; انتهى", path.display())
+        };
+
+        assert_eq!(run(&cmd(&file)), CommandResult::Success("مفقود\n".to_string()));
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(run(&cmd(&file)), CommandResult::Success("موجود\n".to_string()));
+    }
+
+    #[test]
+    fn test_if_without_else_english() {
+        assert_eq!(run("if test a = b ; then echo same ; fi"), CommandResult::None);
+        assert_eq!(run("if test a = a ; then echo same ; fi"), CommandResult::Success("same\n".to_string()));
+    }
+
+    #[test]
+    fn test_nested_if() {
+        let result = run("اذا اختبار 1 -lt 2 ; ثم اذا اختبار -z x ; ثم اطبع أ ; والا اطبع ب ; انتهى ; انتهى");
+        assert_eq!(result, CommandResult::Success("ب\n".to_string()));
+    }
+
+    #[test]
+    fn test_if_inside_for() {
+        let result = run("for x in 1 2 3 ; do if test $x != 2 ; then echo $x ; fi ; done");
+        assert_eq!(result, CommandResult::Success("1\n3\n".to_string()));
+    }
+
+    #[test]
+    fn test_for_loop_splits_unquoted_variable() {
+        let mut executor = Executor::default();
+        executor.variables.insert("items".to_string(), "a b c".to_string());
+        let run_with = |executor: &mut Executor, input: &str| {
+            let tokens = crate::lexer::Lexer::new(input).tokenize();
+            executor.execute(crate::parser::Parser::new(tokens).parse().unwrap())
+        };
+
+        let split = run_with(&mut executor, "for x in $items ; do echo [$x] ; done");
+        assert_eq!(split, CommandResult::Success("[a]\n[b]\n[c]\n".to_string()));
+
+        let whole = run_with(&mut executor, "for x in \"$items\" ; do echo [$x] ; done");
+        assert_eq!(whole, CommandResult::Success("[a b c]\n".to_string()));
+    }
+}
@@ -0,0 +1,113 @@
+//! # Command Suggestions (اقتراح الأوامر)
+//!
+//! When a command isn't found, the closest builtin or `PATH` command by edit
+//! distance is offered instead (`اطع` → `اطبع`). Distances count characters,
+//! not bytes, so an Arabic typo costs the same as a Latin one.
+
+use super::command_cache::is_executable;
+use crate::commands::BUILTINS;
+
+use std::ffi::OsString;
+
+/// The closest known command to `name`, if one is near enough
+pub fn suggest_command(name: &str) -> Option<String> {
+    suggest_in(name, std::env::var_os("PATH"))
+}
+
+/// [`suggest_command`] against the given `PATH` value
+fn suggest_in(name: &str, path: Option<OsString>) -> Option<String> {
+    let typed: Vec<char> = name.chars().collect();
+    if typed.is_empty() || name.contains(std::path::is_separator) {
+        return None;
+    }
+    // Short names only tolerate one typo, so `ل` doesn't match everything
+    let limit = (typed.len() / 3).clamp(1, 2);
+
+    let builtins = BUILTINS
+        .iter()
+        .flat_map(|spec| [spec.arabic, spec.english].into_iter().chain(spec.aliases.iter().copied()))
+        .map(str::to_string);
+
+    // Builtins come first, so they win ties with `PATH` commands
+    let mut best: Option<(usize, String)> = None;
+    for candidate in builtins.chain(path_commands(path)) {
+        let distance = edit_distance(&typed, &candidate.chars().collect::<Vec<_>>());
+        if distance <= limit && best.as_ref().is_none_or(|(d, _)| distance < *d) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
+
+/// Names of the executables in each `PATH` directory
+fn path_commands(path: Option<OsString>) -> impl Iterator<Item = String> {
+    path.map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter(|entry| is_executable(&entry.path()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+}
+
+/// Edits (insert, delete, substitute, swap two neighbours) turning `a` into `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // rows[i][j] is the distance between a[..i] and b[..j]
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: &str, b: &str) -> usize {
+        edit_distance(&a.chars().collect::<Vec<_>>(), &b.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(distance("اطع", "اطبع"), 1);
+        assert_eq!(distance("ecoh", "echo"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "ls"), 2);
+    }
+
+    #[test]
+    fn test_suggests_near_builtin() {
+        assert_eq!(suggest_in("اطع", None).as_deref(), Some("اطبع"));
+        assert_eq!(suggest_in("ecoh", None).as_deref(), Some("echo"));
+        assert_eq!(suggest_in("ocean-no-such-command", None), None);
+        assert_eq!(suggest_in("./اطع", None), None);
+    }
+
+    #[test]
+    fn test_suggests_path_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = dir.path().join("oceantool");
+        std::fs::write(&tool, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let path = Some(dir.path().as_os_str().to_owned());
+        assert_eq!(suggest_in("oceantol", path).as_deref(), Some("oceantool"));
+    }
+}
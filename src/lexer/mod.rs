@@ -8,6 +8,28 @@ pub mod token;
 pub use token::{Token, TokenKind, Span};
 
 use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::utils::{Locale, Message};
+
+use std::fmt;
+
+/// A lexing failure, such as an unterminated string, and where it starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub message: Message,
+    pub span: Span,
+    /// The text the error covers, from its start to where lexing gave up
+    pub lexeme: String,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}:{}]: {}", self.span.line, self.span.grapheme_column, self.message.render(Locale::Both))
+    }
+}
+
+impl std::error::Error for LexError {}
 
 /// Lexer for tokenizing shell commands
 ///
@@ -15,8 +37,11 @@ use unicode_normalization::UnicodeNormalization;
 /// - Arabic and English text
 /// - Quoted strings with escape sequences
 /// - Arabic quotation marks («»)
-/// - Pipe and redirection operators
+/// - Pipe and redirection operators, including `2>`, `2>>`, `&>` and `&>>`
 /// - Command chaining (&&, ||, ;)
+/// - Command substitution (`$(...)`) kept whole inside a word
+/// - Arabic and English keywords (لكل/for, طالما/while, اختر/select, في/in, نفذ/do, اذا/if, ثم/then,
+///   والا/else, انتهى/done) and a standalone `!`
 pub struct Lexer {
     source: Vec<char>,
     position: usize,
@@ -24,6 +49,8 @@ pub struct Lexer {
     line: usize,
     column: usize,
     token_start_column: usize,
+    line_start: usize,
+    token_line_start: usize,
 }
 
 impl Lexer {
@@ -37,6 +64,8 @@ impl Lexer {
             line: 1,
             column: 1,
             token_start_column: 1,
+            line_start: 0,
+            token_line_start: 0,
         }
     }
 
@@ -55,10 +84,27 @@ impl Lexer {
         tokens
     }
 
+    /// Tokenize the whole input, failing on the first error token
+    ///
+    /// Plain [`Lexer::tokenize`] leaves error tokens for the parser, which
+    /// reports them as unexpected; this reports the lexer's own message.
+    pub fn tokenize_checked(&mut self) -> Result<Vec<Token>, LexError> {
+        let tokens = self.tokenize();
+        match tokens.iter().find(|t| matches!(t.kind, TokenKind::Error(_))) {
+            Some(Token { kind: TokenKind::Error(message), span, lexeme }) => Err(LexError {
+                message: message.clone(),
+                span: *span,
+                lexeme: lexeme.clone(),
+            }),
+            _ => Ok(tokens),
+        }
+    }
+
     pub fn next_token(&mut self) -> Token {
         self.skip_whitespace();
         self.token_start = self.position;
         self.token_start_column = self.column;
+        self.token_line_start = self.line_start;
 
         if self.is_at_end() {
             return self.make_token(TokenKind::Eof);
@@ -86,6 +132,12 @@ impl Lexer {
             '&' => {
                 if self.match_char('&') {
                     self.make_token(TokenKind::And)
+                } else if self.match_char('>') {
+                    if self.match_char('>') {
+                        self.make_token(TokenKind::AppendBoth)
+                    } else {
+                        self.make_token(TokenKind::RedirectBoth)
+                    }
                 } else {
                     self.make_token(TokenKind::Background)
                 }
@@ -94,20 +146,38 @@ impl Lexer {
             '>' => {
                 if self.match_char('>') {
                     self.make_token(TokenKind::Append)
+                } else if self.match_char('|') {
+                    self.make_token(TokenKind::Clobber)
                 } else {
                     self.make_token(TokenKind::RedirectOut)
                 }
             }
 
-            '<' => self.make_token(TokenKind::RedirectIn),
+            '<' => {
+                if self.match_char('>') {
+                    self.make_token(TokenKind::ReadWrite)
+                } else {
+                    self.make_token(TokenKind::RedirectIn)
+                }
+            }
+
+            // `2>` and `2>>` only as a word of their own: `a2>b` is `a2 > b`
+            '2' if self.peek() == '>' && self.at_word_boundary() => {
+                self.advance();
+                if self.match_char('>') {
+                    self.make_token(TokenKind::AppendErr)
+                } else {
+                    self.make_token(TokenKind::RedirectErr)
+                }
+            }
 
             ';' => self.make_token(TokenKind::Semicolon),
 
             // Arabic semicolon (؛)
             '\u{061B}' => self.make_token(TokenKind::Semicolon),
 
-            // Comments (skip to end of line)
-            '#' => {
+            // Comments (skip to end of line), only at the start of a word
+            '#' if self.at_word_boundary() => {
                 self.skip_line();
                 self.next_token()
             }
@@ -117,20 +187,68 @@ impl Lexer {
         }
     }
 
+    /// Scan an unquoted word
+    ///
+    /// A backslash before a character that would end the word (whitespace,
+    /// an operator or a quote) makes it part of the word, so `a\>b` is the
+    /// single word `a>b`. Any other backslash, including one at the end of
+    /// input, is kept literally.
     fn scan_word(&mut self, first: char) -> Token {
         let mut value = String::new();
-        value.push(first);
+        let mut c = first;
 
-        while !self.is_at_end() {
-            let c = self.peek();
-            if self.is_word_char(c) {
+        loop {
+            if c == '\\' && !self.is_at_end() && !self.is_word_char(self.peek()) {
                 value.push(self.advance());
+            } else if c == '$' && self.peek() == '(' {
+                value.push(c);
+                if !self.scan_substitution(&mut value) {
+                    return self.make_error(("استبدال أمر غير مكتمل", "Unterminated command substitution"));
+                }
             } else {
+                value.push(c);
+            }
+
+            if self.is_at_end() || !self.is_word_char(self.peek()) {
                 break;
             }
+            c = self.advance();
         }
 
-        self.make_token(TokenKind::Word(value))
+        match TokenKind::keyword(&value) {
+            Some(keyword) => self.make_token(keyword),
+            None => self.make_token(TokenKind::Word(value)),
+        }
+    }
+
+    /// Copy a `$(...)` command substitution into `value` verbatim
+    ///
+    /// Spaces and operators inside belong to the substitution; nested
+    /// parentheses and quotes are skipped over, so `$(اطبع "a)b")` ends at
+    /// the last `)`. Returns false if the input ends first.
+    fn scan_substitution(&mut self, value: &mut String) -> bool {
+        let mut depth = 0;
+        let mut quote = None;
+        while !self.is_at_end() {
+            let c = self.advance();
+            value.push(c);
+            match (quote, c) {
+                (Some(q), _) if c == q => quote = None,
+                (Some('\''), _) => {}
+                (_, '\\') if !self.is_at_end() => value.push(self.advance()),
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        false
     }
 
     /// Scan a quoted string
@@ -143,16 +261,13 @@ impl Lexer {
 
         let mut value = String::new();
 
+        // Strings may span lines; they're only unterminated at end of input
         while !self.is_at_end() && self.peek() != closing {
-            if self.peek() == '\n' {
-                // Unterminated string at end of line
-                return self.make_error("نص غير مكتمل / Unterminated string");
-            }
-
-            if self.peek() == '\\' {
+            // Single quotes are fully literal (POSIX): no escape processing
+            if self.peek() == '\\' && opening != '\'' {
                 self.advance(); // consume backslash
                 if self.is_at_end() {
-                    return self.make_error("تسلسل هروب غير مكتمل / Unterminated escape");
+                    return self.make_error(("تسلسل هروب غير مكتمل", "Unterminated escape"));
                 }
                 let escaped = self.advance();
                 value.push(match escaped {
@@ -171,7 +286,7 @@ impl Lexer {
         }
 
         if self.is_at_end() {
-            return self.make_error("نص غير مكتمل / Unterminated string");
+            return self.make_error(("نص غير مكتمل", "Unterminated string"));
         }
 
         self.advance(); // consume closing quote
@@ -187,13 +302,19 @@ impl Lexer {
             '|' | '&' | '>' | '<' | ';' |
             // Quotes
             '"' | '\'' | '«' | '»' |
-            // Comments
-            '#' |
             // Arabic semicolon
             '\u{061B}'
         )
     }
 
+    /// Whether the current token starts a new word, so `#` begins a comment
+    /// (`color#fff` and `"a"#b` keep their `#`)
+    fn at_word_boundary(&self) -> bool {
+        self.token_start == 0
+            || matches!(self.source[self.token_start - 1],
+                ' ' | '\t' | '\n' | '\r' | '|' | '&' | '>' | '<' | ';' | '\u{061B}')
+    }
+
     #[allow(dead_code)]
     fn is_arabic_letter(&self, c: char) -> bool {
         matches!(c,
@@ -253,6 +374,7 @@ impl Lexer {
         if c == '\n' {
             self.line += 1;
             self.column = 1;
+            self.line_start = self.position;
         } else {
             self.column += 1;
         }
@@ -271,20 +393,40 @@ impl Lexer {
 
     fn make_token(&self, kind: TokenKind) -> Token {
         let lexeme: String = self.source[self.token_start..self.position].iter().collect();
-        Token::new(
-            kind,
-            Span::new(self.token_start, self.position, self.line, self.token_start_column),
-            lexeme,
-        )
+        Token::new(kind, self.token_span(), lexeme)
     }
 
-    fn make_error(&self, message: &str) -> Token {
+    fn make_error(&self, message: (&str, &str)) -> Token {
         Token::new(
-            TokenKind::Error(message.to_string()),
-            Span::new(self.token_start, self.position, self.line, self.token_start_column),
+            TokenKind::Error(message.into()),
+            self.token_span(),
             self.source[self.token_start..self.position].iter().collect(),
         )
     }
+
+    /// Span of the current token, with its column also counted in graphemes
+    fn token_span(&self) -> Span {
+        let prefix: String = self.source[self.token_line_start..self.token_start].iter().collect();
+        let grapheme_column = prefix.graphemes(true).count() + 1;
+        Span::new(self.token_start, self.position, self.line, self.token_start_column, grapheme_column)
+    }
+}
+
+/// Whether `input` needs another line before it can run
+///
+/// True when the lexer reports an error token (an unterminated string or
+/// escape) or the input ends with a dangling `|`, `&&` or `||`. The REPL
+/// uses this to prompt for continuation lines.
+pub fn is_incomplete(input: &str) -> bool {
+    let tokens = Lexer::new(input).tokenize();
+    if tokens.iter().any(|t| matches!(t.kind, TokenKind::Error(_))) {
+        return true;
+    }
+    let last = tokens
+        .iter()
+        .rev()
+        .find(|t| !matches!(t.kind, TokenKind::Newline | TokenKind::Eof));
+    matches!(last.map(|t| &t.kind), Some(TokenKind::Pipe | TokenKind::And | TokenKind::Or))
 }
 
 #[cfg(test)]
@@ -345,6 +487,52 @@ mod tests {
         assert!(tokens.iter().any(|t| matches!(t.kind, TokenKind::Append)));
     }
 
+    #[test]
+    fn test_clobber_operator() {
+        let tokens = Lexer::new("اطبع نص >| ملف").tokenize();
+        assert!(tokens.iter().any(|t| matches!(t.kind, TokenKind::Clobber)));
+        assert!(!tokens.iter().any(|t| matches!(t.kind, TokenKind::Pipe)));
+    }
+
+    #[test]
+    fn test_read_write_operator() {
+        let tokens = Lexer::new("cat <> ملف").tokenize();
+        assert!(tokens.iter().any(|t| matches!(t.kind, TokenKind::ReadWrite)));
+        assert!(!tokens.iter().any(|t| matches!(t.kind, TokenKind::RedirectIn | TokenKind::RedirectOut)));
+    }
+
+    /// The kinds of all tokens before `Eof`
+    fn kinds(input: &str) -> Vec<TokenKind> {
+        let mut tokens = Lexer::new(input).tokenize();
+        tokens.pop();
+        tokens.into_iter().map(|t| t.kind).collect()
+    }
+
+    fn word(text: &str) -> TokenKind {
+        TokenKind::Word(text.to_string())
+    }
+
+    #[test]
+    fn test_escaped_operators_are_literal() {
+        assert_eq!(kinds("اطبع a\\>b"), [word("اطبع"), word("a>b")]);
+        assert_eq!(kinds("echo \\| x"), [word("echo"), word("|"), word("x")]);
+        assert_eq!(kinds("echo a\\&\\;b"), [word("echo"), word("a&;b")]);
+    }
+
+    #[test]
+    fn test_escaped_space_joins_words() {
+        assert_eq!(kinds("اقرأ ملف\\ جديد"), [word("اقرأ"), word("ملف جديد")]);
+        assert_eq!(kinds("echo \\ "), [word("echo"), word(" ")]);
+    }
+
+    #[test]
+    fn test_other_backslashes_are_kept() {
+        // Before an ordinary character and at the end of input
+        assert_eq!(kinds("echo a\\b"), [word("echo"), word("a\\b")]);
+        assert_eq!(kinds("echo a\\"), [word("echo"), word("a\\")]);
+        assert_eq!(kinds("echo \\"), [word("echo"), word("\\")]);
+    }
+
     #[test]
     fn test_and_operator() {
         let mut lexer = Lexer::new("انشئ مجلد && انتقل مجلد");
@@ -383,6 +571,24 @@ mod tests {
         let tokens = lexer.tokenize();
 
         assert!(matches!(&tokens[1].kind, TokenKind::String(s) if s == "سطر1\nسطر2"));
+
+        // Arabic quotes process escapes like double quotes; single quotes don't
+        let mut lexer = Lexer::new(r"اطبع «أ\tب» 'أ\tب'");
+        let tokens = lexer.tokenize();
+
+        assert!(matches!(&tokens[1].kind, TokenKind::String(s) if s == "أ\tب"));
+        assert!(matches!(&tokens[2].kind, TokenKind::String(s) if s == r"أ\tب"));
+    }
+
+    #[test]
+    fn test_single_quotes_are_literal() {
+        let mut lexer = Lexer::new(r"اطبع 'a\nb' 'c\' d");
+        let tokens = lexer.tokenize();
+
+        assert!(matches!(&tokens[1].kind, TokenKind::String(s) if s == r"a\nb"));
+        // A backslash can't escape the closing quote
+        assert!(matches!(&tokens[2].kind, TokenKind::String(s) if s == r"c\"));
+        assert!(matches!(&tokens[3].kind, TokenKind::Word(w) if w == "d"));
     }
 
     #[test]
@@ -394,6 +600,29 @@ mod tests {
         assert_eq!(tokens.len(), 3);
     }
 
+    #[test]
+    fn test_hash_inside_word_is_not_comment() {
+        let mut lexer = Lexer::new("اطبع color#fff");
+        let tokens = lexer.tokenize();
+
+        assert_eq!(tokens.len(), 3);
+        assert!(matches!(&tokens[1].kind, TokenKind::Word(w) if w == "color#fff"));
+    }
+
+    #[test]
+    fn test_standalone_comment() {
+        let mut lexer = Lexer::new("# comment");
+        let tokens = lexer.tokenize();
+        assert_eq!(tokens.len(), 1);
+        assert!(matches!(tokens[0].kind, TokenKind::Eof));
+
+        // After an operator `#` still starts a comment
+        let mut lexer = Lexer::new("اطبع أ;# تعليق");
+        let tokens = lexer.tokenize();
+        assert_eq!(tokens.len(), 4);
+        assert!(matches!(tokens[2].kind, TokenKind::Semicolon));
+    }
+
     #[test]
     fn test_mixed_arabic_english() {
         let mut lexer = Lexer::new("ls -la | grep test");
@@ -403,4 +632,121 @@ mod tests {
         assert!(matches!(&tokens[1].kind, TokenKind::Word(s) if s == "-la"));
         assert!(matches!(tokens[2].kind, TokenKind::Pipe));
     }
+
+    #[test]
+    fn test_keywords() {
+        let mut lexer = Lexer::new("لكل x في a ; نفذ اطبع ; انتهى");
+        let tokens = lexer.tokenize();
+
+        assert!(matches!(tokens[0].kind, TokenKind::For));
+        assert!(matches!(tokens[2].kind, TokenKind::In));
+        assert!(matches!(tokens[5].kind, TokenKind::Do));
+        assert!(matches!(tokens[8].kind, TokenKind::End));
+    }
+
+    #[test]
+    fn test_quoted_keyword_is_string() {
+        let mut lexer = Lexer::new("for \"for\"");
+        let tokens = lexer.tokenize();
+
+        assert!(matches!(tokens[0].kind, TokenKind::For));
+        assert!(matches!(&tokens[1].kind, TokenKind::String(s) if s == "for"));
+    }
+
+    #[test]
+    fn test_if_keywords() {
+        let mut lexer = Lexer::new("إذا أ ; ثم ب ; وإلا ج ; fi");
+        let tokens = lexer.tokenize();
+
+        assert!(matches!(tokens[0].kind, TokenKind::If));
+        assert!(matches!(tokens[3].kind, TokenKind::Then));
+        assert!(matches!(tokens[6].kind, TokenKind::Else));
+        assert!(matches!(tokens[9].kind, TokenKind::End));
+    }
+
+    #[test]
+    fn test_stderr_and_combined_redirects() {
+        assert_eq!(kinds("cmd 2> e 2>> f"), [word("cmd"), TokenKind::RedirectErr, word("e"), TokenKind::AppendErr, word("f")]);
+        assert_eq!(kinds("cmd &> a &>>b"), [word("cmd"), TokenKind::RedirectBoth, word("a"), TokenKind::AppendBoth, word("b")]);
+        // `2` inside a word, or spaced from `>`, is an ordinary argument
+        assert_eq!(kinds("echo a2>b 2 > c"), [
+            word("echo"), word("a2"), TokenKind::RedirectOut, word("b"),
+            word("2"), TokenKind::RedirectOut, word("c"),
+        ]);
+        assert_eq!(kinds("sleep 1 & echo"), [word("sleep"), word("1"), TokenKind::Background, word("echo")]);
+    }
+
+    #[test]
+    fn test_command_substitution_is_one_word() {
+        assert_eq!(kinds("اطبع $(اطبع a | بدل a b)x ;"), [
+            word("اطبع"), word("$(اطبع a | بدل a b)x"), TokenKind::Semicolon,
+        ]);
+        assert_eq!(kinds("echo $(echo \"a)b\" $(pwd))"), [word("echo"), word("$(echo \"a)b\" $(pwd))")]);
+        assert!(matches!(kinds("echo $(pwd")[1], TokenKind::Error(_)));
+        assert!(is_incomplete("echo $(pwd"));
+    }
+
+    #[test]
+    fn test_tokenize_checked_reports_position() {
+        let error = Lexer::new("اطبع «نص").tokenize_checked().unwrap_err();
+        assert!(error.message.en.contains("Unterminated string"));
+        assert_eq!((error.span.line, error.span.grapheme_column), (1, 6));
+        assert_eq!(error.lexeme, "«نص");
+        assert!(Lexer::new("اطبع «نص»").tokenize_checked().is_ok());
+    }
+
+    #[test]
+    fn test_standalone_bang() {
+        assert_eq!(kinds("! اختبار"), [TokenKind::Bang, word("اختبار")]);
+        assert_eq!(kinds("!x a!"), [word("!x"), word("a!")]);
+        assert_eq!(kinds("\"!\""), [TokenKind::String("!".to_string())]);
+    }
+
+    #[test]
+    fn test_grapheme_columns_with_combining_marks() {
+        // Harakat are combining marks: they add chars but not graphemes
+        let mut lexer = Lexer::new("اطبعُ مَرحَباً |");
+        let tokens = lexer.tokenize();
+
+        assert_eq!((tokens[1].span.column, tokens[1].span.grapheme_column), (7, 6));
+        assert_eq!((tokens[2].span.column, tokens[2].span.grapheme_column), (16, 12));
+    }
+
+    #[test]
+    fn test_grapheme_column_resets_per_line() {
+        let mut lexer = Lexer::new("أَ\nبُ جـ");
+        let tokens = lexer.tokenize();
+
+        assert!(matches!(tokens[1].kind, TokenKind::Newline));
+        assert_eq!(tokens[2].span.grapheme_column, 1);
+        assert_eq!(tokens[3].span.grapheme_column, 3);
+    }
+
+    #[test]
+    fn test_string_spans_lines() {
+        let mut lexer = Lexer::new("اطبع \"سطر1\nسطر2\"");
+        let tokens = lexer.tokenize();
+
+        assert!(matches!(&tokens[1].kind, TokenKind::String(s) if s == "سطر1\nسطر2"));
+    }
+
+    #[test]
+    fn test_is_incomplete() {
+        // Open quotes of every kind
+        assert!(is_incomplete("اطبع \"غير مكتمل"));
+        assert!(is_incomplete("echo 'open"));
+        assert!(is_incomplete("اطبع «نص"));
+        assert!(is_incomplete("اطبع \"a\\"));
+        assert!(!is_incomplete("اطبع \"سطر1\nسطر2\""));
+
+        // Dangling operators, also after continuation lines
+        assert!(is_incomplete("اقرأ ملف |"));
+        assert!(is_incomplete("اختبار -f ملف &&"));
+        assert!(is_incomplete("cmd ||\n"));
+        assert!(!is_incomplete("اقرأ ملف |\nابحث كلمة"));
+
+        assert!(!is_incomplete("اطبع مرحبا"));
+        assert!(!is_incomplete("اطبع أ ;"));
+        assert!(!is_incomplete(""));
+    }
 }
@@ -2,6 +2,8 @@
 //!
 //! Defines all token types for the Ocean shell lexer.
 
+use crate::utils::{Locale, Message};
+
 use std::fmt;
 
 /// Position in source code for error reporting
@@ -10,12 +12,16 @@ pub struct Span {
     pub start: usize,
     pub end: usize,
     pub line: usize,
+    /// Column counted in chars
     pub column: usize,
+    /// Column counted in grapheme clusters, so combining marks such as Arabic
+    /// harakat don't push the position past the character they decorate
+    pub grapheme_column: usize,
 }
 
 impl Span {
-    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
-        Self { start, end, line, column }
+    pub fn new(start: usize, end: usize, line: usize, column: usize, grapheme_column: usize) -> Self {
+        Self { start, end, line, column, grapheme_column }
     }
 }
 
@@ -64,6 +70,24 @@ pub enum TokenKind {
     /// Append output: >>
     Append,
 
+    /// Redirect output even with noclobber set: >|
+    Clobber,
+
+    /// Open a file for both reading and writing: <>
+    ReadWrite,
+
+    /// Redirect error output: 2>
+    RedirectErr,
+
+    /// Append error output: 2>>
+    AppendErr,
+
+    /// Redirect output and error output: &>
+    RedirectBoth,
+
+    /// Append output and error output: &>>
+    AppendBoth,
+
     // ═══════════════════════════════════════════════════════════
     // Logical Operators (العوامل المنطقية)
     // ═══════════════════════════════════════════════════════════
@@ -84,6 +108,40 @@ pub enum TokenKind {
     /// Background execution: &
     Background,
 
+    // ═══════════════════════════════════════════════════════════
+    // Keywords (الكلمات المفتاحية)
+    // ═══════════════════════════════════════════════════════════
+
+    /// Loop start: لكل / for
+    For,
+
+    /// Conditional loop start: طالما / while
+    While,
+
+    /// Menu start: اختر / قائمة / select
+    Select,
+
+    /// Loop item list: في / in
+    In,
+
+    /// Loop body start: نفذ / do
+    Do,
+
+    /// Conditional start: اذا / if
+    If,
+
+    /// Conditional branch: ثم / then
+    Then,
+
+    /// Conditional alternative: والا / else
+    Else,
+
+    /// End of a compound command: انتهى / done / fi
+    End,
+
+    /// Negate a pipeline's status: !
+    Bang,
+
     // ═══════════════════════════════════════════════════════════
     // Special Tokens (رموز خاصة)
     // ═══════════════════════════════════════════════════════════
@@ -95,7 +153,36 @@ pub enum TokenKind {
     Eof,
 
     /// Error token for error recovery
-    Error(String),
+    Error(Message),
+}
+
+impl TokenKind {
+    /// Look up the keyword token for an unquoted word, in Arabic or English
+    pub fn keyword(word: &str) -> Option<TokenKind> {
+        match word {
+            "لكل" | "for" => Some(TokenKind::For),
+            "طالما" | "while" => Some(TokenKind::While),
+            "اختر" | "قائمة" | "select" => Some(TokenKind::Select),
+            "في" | "in" => Some(TokenKind::In),
+            "نفذ" | "do" => Some(TokenKind::Do),
+            "اذا" | "إذا" | "if" => Some(TokenKind::If),
+            "ثم" | "then" => Some(TokenKind::Then),
+            "والا" | "وإلا" | "else" => Some(TokenKind::Else),
+            "انتهى" | "done" | "fi" => Some(TokenKind::End),
+            "!" => Some(TokenKind::Bang),
+            _ => None,
+        }
+    }
+
+    /// Whether this token is a keyword (usable as a plain word in arguments)
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::For | TokenKind::While | TokenKind::Select | TokenKind::In | TokenKind::Do
+                | TokenKind::If | TokenKind::Then | TokenKind::Else
+                | TokenKind::End | TokenKind::Bang
+        )
+    }
 }
 
 impl fmt::Display for TokenKind {
@@ -107,13 +194,29 @@ impl fmt::Display for TokenKind {
             TokenKind::RedirectOut => write!(f, ">"),
             TokenKind::RedirectIn => write!(f, "<"),
             TokenKind::Append => write!(f, ">>"),
+            TokenKind::Clobber => write!(f, ">|"),
+            TokenKind::ReadWrite => write!(f, "<>"),
+            TokenKind::RedirectErr => write!(f, "2>"),
+            TokenKind::AppendErr => write!(f, "2>>"),
+            TokenKind::RedirectBoth => write!(f, "&>"),
+            TokenKind::AppendBoth => write!(f, "&>>"),
             TokenKind::And => write!(f, "&&"),
             TokenKind::Or => write!(f, "||"),
             TokenKind::Semicolon => write!(f, ";"),
             TokenKind::Background => write!(f, "&"),
+            TokenKind::For => write!(f, "لكل/for"),
+            TokenKind::While => write!(f, "طالما/while"),
+            TokenKind::Select => write!(f, "اختر/select"),
+            TokenKind::In => write!(f, "في/in"),
+            TokenKind::Do => write!(f, "نفذ/do"),
+            TokenKind::If => write!(f, "اذا/if"),
+            TokenKind::Then => write!(f, "ثم/then"),
+            TokenKind::Else => write!(f, "والا/else"),
+            TokenKind::End => write!(f, "انتهى/done"),
+            TokenKind::Bang => write!(f, "!"),
             TokenKind::Newline => write!(f, "\\n"),
             TokenKind::Eof => write!(f, "EOF"),
-            TokenKind::Error(msg) => write!(f, "Error: {}", msg),
+            TokenKind::Error(msg) => write!(f, "Error: {}", msg.render(Locale::Both)),
         }
     }
 }
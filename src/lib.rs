@@ -9,6 +9,8 @@
 //! 1. **Lexer** - Tokenizes input with Arabic support
 //! 2. **Parser** - Builds an AST from tokens
 //! 3. **Executor** - Executes commands from the AST
+//!
+//! [`run`] and [`eval`] chain the three stages for embedders.
 
 pub mod lexer;
 pub mod parser;
@@ -17,5 +19,37 @@ pub mod commands;
 pub mod utils;
 
 pub use lexer::Lexer;
-pub use parser::{Parser, Command};
+pub use parser::{Parser, Command, ParseError};
 pub use executor::{Executor, CommandResult};
+
+/// Lex, parse and execute `input` with an existing executor
+///
+/// State such as the last exit code and shell variables carries over between
+/// calls. Parse errors include the offending source line. A pipeline's
+/// result is that of its last stage.
+///
+/// ```
+/// use ocean::{CommandResult, Executor};
+///
+/// let mut executor = Executor::default();
+/// let result = ocean::run(&mut executor, "اطبع مرحبا").unwrap();
+/// assert_eq!(result, CommandResult::Success("مرحبا\n".to_string()));
+///
+/// let result = ocean::run(&mut executor, "اطبع بحر محيط | ابحث محيط").unwrap();
+/// assert_eq!(result, CommandResult::Success("بحر محيط\n".to_string()));
+///
+/// assert!(ocean::run(&mut executor, "اطبع |").is_err());
+/// ```
+pub fn run(executor: &mut Executor, input: &str) -> Result<CommandResult, ParseError> {
+    executor.execute_str(input)
+}
+
+/// Run `input` in a fresh executor
+///
+/// ```
+/// let result = ocean::eval("echo hello").unwrap();
+/// assert_eq!(result.output(), Some("hello\n"));
+/// ```
+pub fn eval(input: &str) -> Result<CommandResult, ParseError> {
+    run(&mut Executor::default(), input)
+}
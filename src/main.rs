@@ -18,6 +18,18 @@
 //! - `امسح` (clear) - Clear screen
 //! - `خروج` (exit) - Exit shell
 //! - `مساعدة` (help) - Show help
+//!
+//! ## Usage
+//! - `ocean` - Start the interactive shell
+//! - `ocean -c "<command>"` - Run a single command and exit with its status
+//! - `ocean <script>` - Run a script file line by line
+//! - `ocean --time` - Prefix output lines with a timestamp (same as `سجل تشغيل`)
+//! - `ocean --version` / `ocean --help` - Print version or usage and exit
+//! - `ocean --no-color` - Disable colors (also honors `NO_COLOR` and non-TTY stdout)
+//! - `ocean --dry-run` - File commands print what they would do (same as `اضبط -d`)
+//!
+//! Set `OCEAN_PROMPT` to customize the prompt (see [`utils::prompt`]) and
+//! `OCEAN_HISTSIZE` to change how many history entries are kept.
 
 mod commands;
 mod lexer;
@@ -27,21 +39,328 @@ mod utils;
 mod repl;
 
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use rustyline::error::ReadlineError;
-use rustyline::{Config, Editor};
+use rustyline::{CompletionType, Config, Editor};
 
-use lexer::Lexer;
-use parser::Parser;
-use executor::{Executor, CommandResult};
+use executor::{Executor, CommandResult, CommandError, FileOp, Job};
 use repl::OceanHelper;
-use utils::{shape_arabic, shape_if_arabic, contains_arabic, enable_rtl_mode, right_align, colored_prompt};
+use utils::{shape_arabic, shape_if_arabic, contains_arabic, enable_rtl_mode, right_align, colored_prompt, colored_error, load_theme_from_env, init_color, invalidate_terminal_width};
+use utils::{active_locale, load_locale_from_env, Message, install_interrupt_handler, clear_interrupt, normalize_digits};
+use utils::{render_prompt, prompt_template, exit_status_marker, PromptContext};
 
 const SHELL_NAME: &str = "محيط";
 const VERSION: &str = "0.1.0";
 
+/// Environment variable limiting how many history entries are kept
+const HISTSIZE_ENV_VAR: &str = "OCEAN_HISTSIZE";
+/// History entries kept when `OCEAN_HISTSIZE` is unset
+const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+/// How the shell was asked to run
+enum Mode {
+    /// Interactive REPL
+    Interactive,
+    /// Run a single command string and exit (`-c`)
+    Command(String),
+    /// Run a script file and exit (`ocean script.ocean`)
+    Script(PathBuf),
+    /// Print the version and exit (`--version`)
+    Version,
+    /// Print usage and exit (`--help`)
+    Help,
+}
+
+/// Usage summary for `ocean --help`
+const USAGE: &str = "\
+محيط (Ocean) - الصدفة العربية / Arabic Shell
+
+الاستخدام / Usage:
+  ocean [options]              Start the interactive shell
+  ocean [options] <script>     Run a script file line by line
+  ocean [options] -c <command> Run a single command and exit
+
+الخيارات / Options:
+  -c, --command <command>  Run <command> and exit with its status
+  -t, --time               Prefix output lines with a timestamp
+      --dry-run            File commands print what they would do instead (like اضبط -d)
+      --no-color           Disable colors (also NO_COLOR, or when not a terminal)
+  -V, --version            Print version information and exit
+  -h, --help               Print this help and exit
+
+Inside the shell, type مساعدة (help) for the list of commands.
+";
+
+/// Options parsed from the command line
+struct Options {
+    mode: Mode,
+    /// Start with timestamped output (`--time`), like `سجل تشغيل`
+    timestamps: bool,
+    /// Disable colored output (`--no-color`)
+    no_color: bool,
+    /// Start with file commands only describing changes (`--dry-run`), like `اضبط -d`
+    dry_run: bool,
+}
+
+/// The history size named by an `OCEAN_HISTSIZE` value
+///
+/// Unset or empty means [`DEFAULT_HISTORY_SIZE`]; Arabic-Indic digits are
+/// accepted. Returns a warning for anything that isn't a count.
+fn history_size(value: Option<&str>) -> Result<usize, Message> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(DEFAULT_HISTORY_SIZE),
+        Some(value) => normalize_digits(value).parse().map_err(|_| {
+            Message::warning((
+                format!("قيمة {} غير صالحة '{}'", HISTSIZE_ENV_VAR, value),
+                format!("Invalid {} '{}'", HISTSIZE_ENV_VAR, value),
+            ))
+        }),
+    }
+}
+
+/// Parse command-line arguments into a run mode and options
+fn parse_args(args: &[String]) -> Result<Options, CommandError> {
+    let mut mode = Mode::Interactive;
+    let mut timestamps = false;
+    let mut no_color = false;
+    let mut dry_run = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-t" | "--time" => timestamps = true,
+            "--no-color" => no_color = true,
+            "--dry-run" => dry_run = true,
+            "-V" | "--version" => {
+                mode = Mode::Version;
+                break;
+            }
+            "-h" | "--help" => {
+                mode = Mode::Help;
+                break;
+            }
+            "-c" | "--command" => match iter.next() {
+                Some(command) => mode = Mode::Command(command.clone()),
+                None => {
+                    return Err(CommandError::failed((
+                        format!("الخيار '{}' يتطلب أمراً", arg),
+                        format!("option '{}' requires a command", arg),
+                    )));
+                }
+            },
+            _ if !arg.starts_with('-') => {
+                // First positional argument is the script; the rest are ignored
+                mode = Mode::Script(PathBuf::from(arg));
+                break;
+            }
+            _ => {
+                return Err(CommandError::failed((
+                    format!("خيار غير معروف '{}'", arg),
+                    format!("Unknown option '{}'", arg),
+                )));
+            }
+        }
+    }
+    Ok(Options { mode, timestamps, no_color, dry_run })
+}
+
 fn main() {
+    // Chosen first so every later message is printed in it
+    let locale_warning = load_locale_from_env().err();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", shape_if_arabic(&error.render(active_locale())));
+            std::process::exit(2);
+        }
+    };
+
+    init_color(options.no_color);
+    for warning in locale_warning.into_iter().chain(load_theme_from_env().err()) {
+        eprintln!("{}", shape_if_arabic(&warning.render(active_locale())));
+    }
+
+    match options.mode {
+        Mode::Version => print!("{}", commands::version_text()),
+        Mode::Help => print!("{}", shape_if_arabic(USAGE)),
+        Mode::Interactive => run_interactive(options.timestamps, options.dry_run),
+        Mode::Command(command) => {
+            let mut executor = Executor::new(false);
+            executor.timestamps = options.timestamps;
+            executor.dry_run = options.dry_run;
+            std::process::exit(run_command(&mut executor, &command));
+        }
+        Mode::Script(path) => {
+            let mut executor = Executor::new(false);
+            executor.timestamps = options.timestamps;
+            executor.dry_run = options.dry_run;
+            std::process::exit(run_file(&mut executor, &path));
+        }
+    }
+}
+
+/// Print a command result in non-interactive mode (errors go to stderr)
+fn print_result(result: &CommandResult, executor: &Executor) {
+    for part in result.parts() {
+        match part {
+            CommandResult::Success(output) => {
+                for line in output.lines() {
+                    println!("{}", executor.stamp_line(&shape_if_arabic(line)));
+                }
+            }
+            CommandResult::Error(error) if !error.is_silent() => {
+                eprintln!("{}", shape_if_arabic(&error.render(active_locale())))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Lex, parse, and execute a command string once, printing its output
+///
+/// Returns the exit code the process should report.
+fn run_command(executor: &mut Executor, input: &str) -> i32 {
+    let result = match executor.execute_str(input) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{}", shape_if_arabic(&e.render(active_locale())));
+            return 2;
+        }
+    };
+
+    print_result(&result, executor);
+    let code = exit_code(&result, executor);
+    report_jobs(executor.jobs.wait_all(), false);
+    code
+}
+
+/// Execute a script file
+///
+/// The whole file is parsed first, so loops and conditions may span lines
+/// (`لكل ... نفذ` on one line, `انتهى` on a later one); a parse error runs
+/// nothing and is reported with its line number. Blank lines, `#` comments,
+/// and a leading `#!` shebang are skipped. Execution stops at the first
+/// failing command, unless it handles its own failure with `||`. Returns
+/// the exit code of the last executed command. Intended to be shared with
+/// an rc-file loader.
+fn run_file(executor: &mut Executor, path: &Path) -> i32 {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            let error = CommandError::file(FileOp::Read, path.display(), e);
+            eprintln!("{}", shape_if_arabic(&error.render(active_locale())));
+            return 127;
+        }
+    };
+
+    let commands = match Executor::parse(&content) {
+        Ok(parser::Command::Sequence(commands)) => commands,
+        Ok(command) => vec![command],
+        Err(e) => {
+            eprintln!("{}:{}: {}", path.display(), e.line, shape_if_arabic(&e.render(active_locale())));
+            return 2;
+        }
+    };
+
+    let mut code = 0;
+    for ast in commands {
+        let handles_failure = matches!(ast, parser::Command::Or(_, _));
+        let result = executor.execute(ast);
+        print_result(&result, executor);
+        code = exit_code(&result, executor);
+
+        if result.is_exit() || (!result.is_success() && !handles_failure) {
+            break;
+        }
+    }
+
+    report_jobs(executor.jobs.wait_all(), false);
+    code
+}
+
+/// Print the buffered output of finished background jobs, each followed by
+/// its `[N] Done` notice
+fn report_jobs(jobs: Vec<Job>, use_padding: bool) {
+    for job in jobs {
+        for line in job.output().lines() {
+            print_rtl_line(&shape_if_arabic(line), use_padding);
+        }
+        print_rtl_line(&shape_if_arabic(&job.notice(active_locale())), use_padding);
+    }
+}
+
+/// Map a command result to a process exit code
+fn exit_code(result: &CommandResult, executor: &Executor) -> i32 {
+    match result.status() {
+        CommandResult::Exit(code) => *code,
+        CommandResult::Error(_) if executor.last_exit_code == 0 => 1,
+        _ => executor.last_exit_code,
+    }
+}
+
+/// What a REPL loop does after handling a line
+#[derive(Debug, PartialEq)]
+enum LoopAction {
+    /// Show the next prompt
+    Continue,
+    /// Leave the shell with this exit code
+    Exit(i32),
+}
+
+/// Execute one line of interactive input and print its result
+///
+/// Shared by the rustyline and fallback loops so the two can't drift apart.
+/// Errors are printed in the theme's error color; the exit code is kept in
+/// step with the result for the next prompt.
+fn process_line(executor: &mut Executor, input: &str, use_padding: bool) -> LoopAction {
+    // Skip empty input
+    if input.trim().is_empty() {
+        return LoopAction::Continue;
+    }
+
+    clear_interrupt();
+    let result = match executor.execute_str(input) {
+        Ok(result) => result,
+        Err(e) => {
+            print_rtl_line(&e.render(active_locale()), use_padding);
+            executor.last_exit_code = 2;
+            return LoopAction::Continue;
+        }
+    };
+
+    // Keep the status shown in the next prompt in step with the result
+    executor.last_exit_code = exit_code(&result, executor);
+    for part in result.parts() {
+        match part {
+            CommandResult::Exit(code) => {
+                print_rtl_line(&shape_arabic("مع السلامة! (Goodbye!)"), use_padding);
+                return LoopAction::Exit(*code);
+            }
+            CommandResult::Success(output) => {
+                // Print each line with RTL alignment if needed
+                for line in output.lines() {
+                    print_rtl_line(&executor.stamp_line(&shape_if_arabic(line)), use_padding);
+                }
+            }
+            CommandResult::Error(error) if !error.is_silent() => {
+                // Print errors in the theme's error color (aligned first so BiDi reordering
+                // doesn't have to step around color codes)
+                let error_msg = shape_if_arabic(&error.render(active_locale()));
+                let error_msg = if use_padding && contains_arabic(&error_msg) {
+                    right_align(&error_msg)
+                } else {
+                    error_msg
+                };
+                println!("{}", colored_error(&error_msg));
+            }
+            _ => {}
+        }
+    }
+    LoopAction::Continue
+}
+
+fn run_interactive(timestamps: bool, dry_run: bool) {
     // Try to enable VTE RTL mode, track if we need padding fallback
     let vte_rtl_supported = enable_rtl_mode();
     let use_padding = !vte_rtl_supported;
@@ -51,11 +370,29 @@ fn main() {
 
     // Create executor with RTL padding setting
     let mut executor = Executor::new(use_padding);
+    executor.timestamps = timestamps;
+    executor.dry_run = dry_run;
+
+    // Ctrl+C stops the running command or loop, not the shell
+    install_interrupt_handler();
 
-    // Initialize rustyline with auto-completion
+    let max_history = history_size(env::var(HISTSIZE_ENV_VAR).ok().as_deref()).unwrap_or_else(|warning| {
+        eprintln!("{}", warning.render(active_locale()));
+        DEFAULT_HISTORY_SIZE
+    });
+
+    // Initialize rustyline with auto-completion; a repeated line or one
+    // starting with a space isn't added to history. List completion inserts
+    // a lone match, so Tab after `انتقل مج` gives `مجلد/` and the next Tab
+    // lists what is inside it
     let config = Config::builder()
+        .completion_type(CompletionType::List)
         .auto_add_history(true)
-        .build();
+        .history_ignore_space(true)
+        .max_history_size(max_history)
+        .and_then(|builder| builder.history_ignore_dups(true))
+        .map(|builder| builder.build())
+        .unwrap_or_else(|_| Config::builder().completion_type(CompletionType::List).auto_add_history(true).build());
 
     let mut rl: Editor<OceanHelper, _> = match Editor::with_config(config) {
         Ok(editor) => editor,
@@ -68,26 +405,26 @@ fn main() {
     };
 
     // Set the completion helper
-    rl.set_helper(Some(OceanHelper::new()));
+    rl.set_helper(Some(OceanHelper::with_completions(executor.completions.clone())));
 
     // Load history from file
-    let history_path = dirs::home_dir()
-        .map(|h| h.join(".ocean_history"))
-        .unwrap_or_else(|| PathBuf::from(".ocean_history"));
+    let history_path = executor.history_file.clone();
     let _ = rl.load_history(&history_path);
 
     // Main REPL loop
     loop {
+        // Background output is printed here, never over a prompt being edited
+        report_jobs(executor.jobs.take_finished(), use_padding);
+
         // Build colored prompt
-        let cwd = env::current_dir()
-            .map(|p| shorten_path(&p))
-            .unwrap_or_else(|_| "?".to_string());
+        let cwd = shorten_path(executor.cwd());
 
+        let prompt = build_prompt(&cwd, executor.last_exit_code, true);
         let prompt = if use_padding {
             // For RTL terminals, use right-aligned prompt
-            right_align(&colored_prompt(&shape_arabic(SHELL_NAME), &cwd))
+            right_align(&prompt)
         } else {
-            colored_prompt(&shape_arabic(SHELL_NAME), &cwd)
+            prompt
         };
 
         // Read input using rustyline
@@ -108,49 +445,25 @@ fn main() {
             }
         };
 
-        // Skip empty input
-        if input.trim().is_empty() {
-            continue;
-        }
+        // rustyline owns SIGWINCH while reading, so refresh the width cache
+        invalidate_terminal_width();
 
-        // Tokenize
-        let mut lexer = Lexer::new(&input);
-        let tokens = lexer.tokenize();
+        let action = process_line(&mut executor, &input, use_padding);
 
-        // Parse
-        let mut parser = Parser::new(tokens);
-        let ast = match parser.parse() {
-            Ok(cmd) => cmd,
-            Err(e) => {
-                print_rtl_line(&e.to_string(), use_padding);
-                continue;
-            }
-        };
+        // `انتقل` moves only the executor, so tell the completer where it is
+        if let Some(helper) = rl.helper_mut() {
+            helper.set_cwd(executor.cwd());
+        }
 
-        // Execute
-        let result = executor.execute(ast);
-        match result {
-            CommandResult::Exit(code) => {
-                print_rtl_line(&shape_arabic("مع السلامة! (Goodbye!)"), use_padding);
-                // Save history before exit
-                let _ = rl.save_history(&history_path);
-                std::process::exit(code);
-            }
-            CommandResult::Success(output) => {
-                if !output.is_empty() {
-                    // Print each line with RTL alignment if needed
-                    for line in output.lines() {
-                        print_rtl_line(&shape_if_arabic(line), use_padding);
-                    }
-                }
-            }
-            CommandResult::Error(msg) => {
-                // Print errors in red
-                use colored::Colorize;
-                let error_msg = shape_if_arabic(&msg).red().to_string();
-                print_rtl_line(&error_msg, use_padding);
-            }
-            CommandResult::None => {}
+        // `امسح_ذاكرة` emptied the file; forget the entries held here too
+        if std::mem::take(&mut executor.history_cleared) {
+            let _ = rl.clear_history();
+        }
+
+        if let LoopAction::Exit(code) = action {
+            // Save history before exit
+            let _ = rl.save_history(&history_path);
+            std::process::exit(code);
         }
     }
 
@@ -162,12 +475,12 @@ fn run_basic_repl(executor: &mut Executor, use_padding: bool) {
     use std::io::{self, Write};
 
     loop {
+        report_jobs(executor.jobs.take_finished(), use_padding);
+
         // Print prompt with current directory
-        let cwd = env::current_dir()
-            .map(|p| shorten_path(&p))
-            .unwrap_or_else(|_| "?".to_string());
+        let cwd = shorten_path(executor.cwd());
 
-        let prompt = format!("{} [{}]> ", shape_arabic(SHELL_NAME), cwd);
+        let prompt = build_prompt(&cwd, executor.last_exit_code, false);
 
         if use_padding {
             print!("{}", right_align(&prompt));
@@ -189,43 +502,25 @@ fn run_basic_repl(executor: &mut Executor, use_padding: bool) {
             Err(_) => continue,
         }
 
-        let input = input.trim();
-        if input.is_empty() {
-            continue;
+        if let LoopAction::Exit(code) = process_line(executor, input.trim(), use_padding) {
+            std::process::exit(code);
         }
+    }
+}
 
-        // Tokenize
-        let mut lexer = Lexer::new(input);
-        let tokens = lexer.tokenize();
-
-        // Parse
-        let mut parser = Parser::new(tokens);
-        let ast = match parser.parse() {
-            Ok(cmd) => cmd,
-            Err(e) => {
-                print_rtl_line(&e.to_string(), use_padding);
-                continue;
-            }
-        };
-
-        // Execute
-        let result = executor.execute(ast);
-        match result {
-            CommandResult::Exit(code) => {
-                print_rtl_line(&shape_arabic("مع السلامة! (Goodbye!)"), use_padding);
-                std::process::exit(code);
-            }
-            CommandResult::Success(output) => {
-                if !output.is_empty() {
-                    for line in output.lines() {
-                        print_rtl_line(&shape_if_arabic(line), use_padding);
-                    }
-                }
-            }
-            CommandResult::Error(msg) => {
-                print_rtl_line(&shape_if_arabic(&msg), use_padding);
-            }
-            CommandResult::None => {}
+/// Build the interactive prompt from `OCEAN_PROMPT`, or the default one
+fn build_prompt(cwd: &str, last_exit_code: i32, colored: bool) -> String {
+    match prompt_template() {
+        Some(template) => shape_if_arabic(&render_prompt(
+            &template,
+            &PromptContext::new(cwd.to_string(), last_exit_code),
+        )),
+        None if colored => colored_prompt(&shape_arabic(SHELL_NAME), cwd, last_exit_code),
+        None => {
+            let status = exit_status_marker(last_exit_code)
+                .map(|marker| format!(" {}", marker))
+                .unwrap_or_default();
+            format!("{} [{}]{}> ", shape_arabic(SHELL_NAME), cwd, status)
         }
     }
 }
@@ -273,7 +568,7 @@ fn print_welcome(use_padding: bool) {
 }
 
 
-fn shorten_path(path: &PathBuf) -> String {
+fn shorten_path(path: &Path) -> String {
     if let Some(home) = dirs::home_dir() {
         if let Ok(relative) = path.strip_prefix(&home) {
             return format!("~/{}", relative.display());
@@ -282,3 +577,41 @@ fn shorten_path(path: &PathBuf) -> String {
     path.display().to_string()
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_size() {
+        assert_eq!(history_size(None), Ok(DEFAULT_HISTORY_SIZE));
+        assert_eq!(history_size(Some("")), Ok(DEFAULT_HISTORY_SIZE));
+        assert_eq!(history_size(Some(" 50 ")), Ok(50));
+        assert_eq!(history_size(Some("٢٠٠")), Ok(200));
+        assert_eq!(history_size(Some("0")), Ok(0));
+        assert!(history_size(Some("كثير")).unwrap_err().en.contains("Warning: Invalid OCEAN_HISTSIZE 'كثير'"));
+        assert!(history_size(Some("-5")).is_err());
+    }
+
+    #[test]
+    fn test_process_line_exit() {
+        let mut executor = Executor::default();
+        assert_eq!(process_line(&mut executor, "خروج", false), LoopAction::Exit(0));
+        assert_eq!(process_line(&mut executor, "exit", false), LoopAction::Exit(0));
+    }
+
+    #[test]
+    fn test_process_line_tracks_exit_code() {
+        let mut executor = Executor::default();
+        assert_eq!(process_line(&mut executor, "   ", false), LoopAction::Continue);
+        assert_eq!(executor.last_exit_code, 0);
+
+        assert_eq!(process_line(&mut executor, "اطبع |", false), LoopAction::Continue);
+        assert_eq!(executor.last_exit_code, 2);
+
+        assert_eq!(process_line(&mut executor, "اقرأ /nonexistent/ocean-file", false), LoopAction::Continue);
+        assert_eq!(executor.last_exit_code, 1);
+
+        process_line(&mut executor, "اطبع مرحبا", false);
+        assert_eq!(executor.last_exit_code, 0);
+    }
+}
@@ -11,7 +11,7 @@ pub enum Command {
     /// Example: `اطبع مرحبا > output.txt`
     Simple {
         name: String,
-        args: Vec<String>,
+        args: Vec<Word>,
         redirects: Vec<Redirect>,
     },
 
@@ -35,10 +35,112 @@ pub enum Command {
     /// Example: `sleep 10 &`
     Background(Box<Command>),
 
+    /// Negation: succeed if the pipeline fails and fail if it succeeds
+    /// Example: `! اختبار -f x && اطبع مفقود`
+    Not(Box<Command>),
+
+    /// Loop: run the body once per item, with the item in a shell variable
+    /// Example: `لكل x في a b c ; نفذ اطبع $x ; انتهى`
+    For {
+        var: String,
+        items: Vec<Word>,
+        body: Box<Command>,
+    },
+
+    /// Loop: run the body for as long as `cond` succeeds
+    /// Example: `طالما اختبار -f قفل ; نفذ نم 1 ; انتهى`
+    While {
+        cond: Box<Command>,
+        body: Box<Command>,
+    },
+
+    /// Menu: print the items numbered, bind the chosen one and run the
+    /// body, until the input ends
+    /// Example: `اختر x في a b c ; نفذ اطبع $x ; انتهى`
+    Select {
+        var: String,
+        items: Vec<Word>,
+        body: Box<Command>,
+    },
+
+    /// Conditional: run `then_branch` if `cond` succeeds, else `else_branch`
+    /// Example: `اذا اختبار -f x ; ثم اطبع موجود ; والا اطبع مفقود ; انتهى`
+    If {
+        cond: Box<Command>,
+        then_branch: Box<Command>,
+        else_branch: Option<Box<Command>>,
+    },
+
+    /// Variable assignments, alone or prefixing a command
+    /// Example: `اسم=محيط` or `A=1 B=2 اطبع $A`
+    Assign {
+        assignments: Vec<Assignment>,
+        command: Option<Box<Command>>,
+    },
+
     /// Empty command (for blank lines)
     Empty,
 }
 
+/// One `NAME=VALUE` assignment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub name: String,
+    pub value: Word,
+}
+
+/// A command argument, remembering how it was quoted
+///
+/// Quoting decides which expansions the executor applies: unquoted words
+/// get variable and glob expansion, double-quoted ones only variables, and
+/// single-quoted ones none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    pub text: String,
+    pub quoting: Quoting,
+}
+
+impl Word {
+    pub fn new(text: String, quoting: Quoting) -> Self {
+        Self { text, quoting }
+    }
+}
+
+impl From<&str> for Word {
+    fn from(text: &str) -> Self {
+        Self::new(text.to_string(), Quoting::Unquoted)
+    }
+}
+
+impl From<String> for Word {
+    fn from(text: String) -> Self {
+        Self::new(text, Quoting::Unquoted)
+    }
+}
+
+impl PartialEq<&str> for Word {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// How a word was quoted in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quoting {
+    /// Bare word: `file.txt`
+    Unquoted,
+    /// Double or Arabic quotes: `"text"`, `«نص»`
+    Double,
+    /// Single quotes: `'text'`
+    Single,
+}
+
 /// I/O Redirection
 #[derive(Debug, Clone, PartialEq)]
 pub struct Redirect {
@@ -61,6 +163,35 @@ pub enum RedirectKind {
     In,
     /// Append output: >> (الحق)
     Append,
+    /// Forced output: >| (overwrites even with noclobber)
+    Clobber,
+    /// Read-write: <> (the file is both stdin and stdout)
+    ReadWrite,
+    /// Error output: 2>
+    ErrOut,
+    /// Append error output: 2>>
+    ErrAppend,
+    /// Output and error output: &>
+    BothOut,
+    /// Append output and error output: &>>
+    BothAppend,
+}
+
+impl RedirectKind {
+    /// Whether the command's output goes to the target
+    pub fn writes_stdout(self) -> bool {
+        matches!(self, Self::Out | Self::Append | Self::Clobber | Self::BothOut | Self::BothAppend)
+    }
+
+    /// Whether the command's error text goes to the target
+    pub fn writes_stderr(self) -> bool {
+        matches!(self, Self::ErrOut | Self::ErrAppend | Self::BothOut | Self::BothAppend)
+    }
+
+    /// Whether the target is appended to rather than truncated
+    pub fn appends(self) -> bool {
+        matches!(self, Self::Append | Self::ErrAppend | Self::BothAppend)
+    }
 }
 
 impl fmt::Display for RedirectKind {
@@ -69,6 +200,12 @@ impl fmt::Display for RedirectKind {
             RedirectKind::Out => write!(f, ">"),
             RedirectKind::In => write!(f, "<"),
             RedirectKind::Append => write!(f, ">>"),
+            RedirectKind::Clobber => write!(f, ">|"),
+            RedirectKind::ReadWrite => write!(f, "<>"),
+            RedirectKind::ErrOut => write!(f, "2>"),
+            RedirectKind::ErrAppend => write!(f, "2>>"),
+            RedirectKind::BothOut => write!(f, "&>"),
+            RedirectKind::BothAppend => write!(f, "&>>"),
         }
     }
 }
@@ -103,6 +240,41 @@ impl fmt::Display for Command {
             Command::Background(cmd) => {
                 write!(f, "{} &", cmd)
             }
+            Command::Not(cmd) => {
+                write!(f, "! {}", cmd)
+            }
+            Command::For { var, items, body } => {
+                write!(f, "لكل {} في", var)?;
+                for item in items {
+                    write!(f, " {}", item)?;
+                }
+                write!(f, " ; نفذ {} ; انتهى", body)
+            }
+            Command::While { cond, body } => {
+                write!(f, "طالما {} ; نفذ {} ; انتهى", cond, body)
+            }
+            Command::Select { var, items, body } => {
+                write!(f, "اختر {} في", var)?;
+                for item in items {
+                    write!(f, " {}", item)?;
+                }
+                write!(f, " ; نفذ {} ; انتهى", body)
+            }
+            Command::If { cond, then_branch, else_branch } => {
+                write!(f, "اذا {} ; ثم {}", cond, then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    write!(f, " ; والا {}", else_branch)?;
+                }
+                write!(f, " ; انتهى")
+            }
+            Command::Assign { assignments, command } => {
+                let strs: Vec<String> = assignments.iter().map(|a| format!("{}={}", a.name, a.value)).collect();
+                write!(f, "{}", strs.join(" "))?;
+                match command {
+                    Some(command) => write!(f, " {}", command),
+                    None => Ok(()),
+                }
+            }
             Command::Empty => Ok(()),
         }
     }
@@ -116,7 +288,7 @@ mod tests {
     fn test_simple_command_display() {
         let cmd = Command::Simple {
             name: "اطبع".to_string(),
-            args: vec!["مرحبا".to_string()],
+            args: vec!["مرحبا".into()],
             redirects: vec![],
         };
         assert_eq!(cmd.to_string(), "اطبع مرحبا");
@@ -126,7 +298,7 @@ mod tests {
     fn test_redirect_display() {
         let cmd = Command::Simple {
             name: "اطبع".to_string(),
-            args: vec!["نص".to_string()],
+            args: vec!["نص".into()],
             redirects: vec![Redirect::new(RedirectKind::Out, "output.txt".to_string())],
         };
         assert_eq!(cmd.to_string(), "اطبع نص > output.txt");
@@ -137,15 +309,44 @@ mod tests {
         let cmd = Command::Pipeline(vec![
             Command::Simple {
                 name: "اقرأ".to_string(),
-                args: vec!["ملف".to_string()],
+                args: vec!["ملف".into()],
                 redirects: vec![],
             },
             Command::Simple {
                 name: "ابحث".to_string(),
-                args: vec!["كلمة".to_string()],
+                args: vec!["كلمة".into()],
                 redirects: vec![],
             },
         ]);
         assert_eq!(cmd.to_string(), "اقرأ ملف | ابحث كلمة");
     }
+
+    #[test]
+    fn test_for_display() {
+        let cmd = Command::For {
+            var: "x".to_string(),
+            items: vec!["أ".into(), "ب".into()],
+            body: Box::new(Command::Simple {
+                name: "اطبع".to_string(),
+                args: vec!["$x".into()],
+                redirects: vec![],
+            }),
+        };
+        assert_eq!(cmd.to_string(), "لكل x في أ ب ; نفذ اطبع $x ; انتهى");
+    }
+
+    #[test]
+    fn test_if_display() {
+        let simple = |name: &str| Command::Simple {
+            name: name.to_string(),
+            args: vec![],
+            redirects: vec![],
+        };
+        let cmd = Command::If {
+            cond: Box::new(simple("أ")),
+            then_branch: Box::new(simple("ب")),
+            else_branch: Some(Box::new(simple("ج"))),
+        };
+        assert_eq!(cmd.to_string(), "اذا أ ; ثم ب ; والا ج ; انتهى");
+    }
 }
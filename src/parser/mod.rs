@@ -6,42 +6,117 @@
 //!
 //! ```text
 //! command_line  = sequence
-//! sequence      = and_or (';' and_or)*
+//! sequence      = and_or ((';' | NEWLINE) and_or)*
 //! and_or        = pipeline (('&&' | '||') pipeline)*
-//! pipeline      = simple_cmd ('|' simple_cmd)*
+//! pipeline      = command ('|' command)*
+//! command       = for_loop | while_loop | select_cmd | if_cmd | simple_cmd
+//! for_loop      = 'لكل' WORD 'في' word* ';' 'نفذ' sequence ';' 'انتهى'
+//! while_loop    = 'طالما' sequence ';' 'نفذ' sequence ';' 'انتهى'
+//! select_cmd    = 'اختر' WORD 'في' word* ';' 'نفذ' sequence ';' 'انتهى'
+//! if_cmd        = 'اذا' sequence ';' 'ثم' sequence [';' 'والا' sequence] ';' 'انتهى'
 //! simple_cmd    = word (word | redirect)* ['&']
-//! redirect      = ('>' | '>>' | '<') word
-//! word          = WORD | STRING
+//! redirect      = ('>' | '>>' | '>|' | '<' | '<>' | '2>' | '2>>' | '&>' | '&>>') word
+//! word          = WORD | STRING | KEYWORD
 //! ```
+//!
+//! Keywords also have English spellings (`for`, `while`, `select`, `in`, `do`, `if`, `then`,
+//! `else`, `done`/`fi`) and are
+//! plain words anywhere but command position.
 
 pub mod ast;
 
-pub use ast::{Command, Redirect, RedirectKind};
+pub use ast::{Assignment, Command, Redirect, RedirectKind, Word, Quoting};
+
+use crate::lexer::{LexError, Token, TokenKind};
+use crate::utils::{Locale, Message};
 
-use crate::lexer::{Token, TokenKind};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// Parser error
 #[derive(Debug, Clone)]
 pub struct ParseError {
-    pub message: String,
+    pub message: Message,
     pub line: usize,
-    pub column: usize,
+    /// Column in grapheme clusters, as users see it
+    pub grapheme_column: usize,
+    /// Length of the offending token in grapheme clusters
+    pub width: usize,
+    /// The offending input line, echoed with a caret under the token
+    pub source_line: Option<String>,
 }
 
 impl ParseError {
-    pub fn new(message: String, line: usize, column: usize) -> Self {
-        Self { message, line, column }
+    pub fn new(message: impl Into<Message>, token: &Token) -> Self {
+        Self {
+            message: message.into(),
+            line: token.span.line,
+            grapheme_column: token.span.grapheme_column,
+            width: token.lexeme.graphemes(true).count().max(1),
+            source_line: None,
+        }
+    }
+
+    /// Attach the original input so the error can point at the token
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source_line = source.lines().nth(self.line.saturating_sub(1)).map(str::to_string);
+        self
+    }
+
+    /// The caret line under the offending token, matching the echoed input's
+    /// display widths (combining marks take no space, tabs are kept)
+    fn caret_line(&self, source_line: &str) -> String {
+        let graphemes: Vec<&str> = source_line.graphemes(true).collect();
+        let start = (self.grapheme_column - 1).min(graphemes.len());
+        let mut caret: String = graphemes[..start]
+            .iter()
+            .map(|g| if *g == "\t" { "\t".to_string() } else { " ".repeat(g.width()) })
+            .collect();
+        let token_width: usize = graphemes[start..].iter().take(self.width).map(|g| g.width()).sum();
+        caret.push_str(&"^".repeat(token_width.max(1)));
+        caret
+    }
+
+    /// The error in Arabic, English, or both, with the caret line if the
+    /// source is attached
+    pub fn render(&self, locale: Locale) -> String {
+        let location = format!("[{}:{}]", self.line, self.grapheme_column);
+        let mut text = match locale {
+            Locale::Arabic => format!("خطأ نحوي {}: {}", location, self.message.ar),
+            Locale::English => format!("Parse error {}: {}", location, self.message.en),
+            Locale::Both => format!("خطأ نحوي / Parse error {}: {}", location, self.message.render(locale)),
+        };
+        if let Some(source_line) = &self.source_line {
+            text.push_str(&format!("\n  {}\n  {}", source_line, self.caret_line(source_line)));
+        }
+        text
     }
 }
 
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "خطأ نحوي / Parse error [{}:{}]: {}", self.line, self.column, self.message)
+        f.write_str(&self.render(Locale::Both))
     }
 }
 
 impl std::error::Error for ParseError {}
 
+/// A lexer error is reported like a parse error, with a caret under the
+/// text it covers
+impl From<LexError> for ParseError {
+    fn from(error: LexError) -> Self {
+        let token = Token::new(TokenKind::Error(error.message.clone()), error.span, error.lexeme);
+        ParseError::new(error.message, &token)
+    }
+}
+
+/// Whether `name` can be assigned to: a letter or `_`, then letters, digits
+/// or `_`
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
 /// Result type for parsing
 pub type ParseResult<T> = Result<T, ParseError>;
 
@@ -74,23 +149,24 @@ impl Parser {
         if !self.is_at_end() {
             let token = self.peek();
             return Err(ParseError::new(
-                format!("رمز غير متوقع / Unexpected token: {}", token.kind),
-                token.span.line,
-                token.span.column,
+                ("رمز غير متوقع".to_string(), format!("Unexpected token: {}", token.kind)),
+                token,
             ));
         }
 
         Ok(cmd)
     }
 
-    /// Parse a sequence of commands (separated by ;)
+    /// Parse a sequence of commands separated by `;` or newlines, so pasted
+    /// lines run one after another
     fn parse_sequence(&mut self) -> ParseResult<Command> {
         let mut commands = vec![self.parse_and_or()?];
 
-        while self.check(&TokenKind::Semicolon) {
+        while self.check(&TokenKind::Semicolon) || self.check(&TokenKind::Newline) {
             self.advance();
             self.skip_newlines();
-            if self.is_at_end() || self.check(&TokenKind::Eof) {
+            // A keyword like انتهى/done closes the enclosing compound command
+            if self.is_at_end() || self.check(&TokenKind::Eof) || self.check_terminator() {
                 break;
             }
             commands.push(self.parse_and_or()?);
@@ -126,14 +202,20 @@ impl Parser {
         Ok(left)
     }
 
-    /// Parse a pipeline (commands connected by |)
+    /// Parse a pipeline (commands connected by |), negated by a leading `!`
     fn parse_pipeline(&mut self) -> ParseResult<Command> {
-        let mut commands = vec![self.parse_simple_command()?];
+        if self.check(&TokenKind::Bang) {
+            self.advance();
+            let pipeline = self.parse_pipeline()?;
+            return Ok(Command::Not(Box::new(pipeline)));
+        }
+
+        let mut commands = vec![self.parse_command()?];
 
         while self.check(&TokenKind::Pipe) {
             self.advance();
             self.skip_newlines();
-            commands.push(self.parse_simple_command()?);
+            commands.push(self.parse_command()?);
         }
 
         if commands.len() == 1 {
@@ -143,8 +225,98 @@ impl Parser {
         }
     }
 
-    /// Parse a simple command with arguments and redirections
+    /// Parse a compound command or a simple command
+    fn parse_command(&mut self) -> ParseResult<Command> {
+        match self.peek().kind {
+            TokenKind::For | TokenKind::Select => self.parse_for(),
+            TokenKind::While => self.parse_while(),
+            TokenKind::If => self.parse_if(),
+            _ => self.parse_simple_command(),
+        }
+    }
+
+    /// Parse a for loop: `لكل x في a b c ; نفذ ... ; انتهى`, or a menu
+    /// with the same shape: `اختر x في a b c ; نفذ ... ; انتهى`
+    fn parse_for(&mut self) -> ParseResult<Command> {
+        let select = self.advance().kind == TokenKind::Select; // consume لكل/اختر
+
+        let var = self.expect_word()?;
+        self.expect_keyword(&TokenKind::In)?;
+
+        let mut items = Vec::new();
+        while let Some(word) = self.try_word() {
+            items.push(word);
+        }
+
+        self.expect_separator()?;
+        self.expect_keyword(&TokenKind::Do)?;
+        self.skip_newlines();
+        let body = self.parse_sequence()?;
+        self.expect_keyword(&TokenKind::End)?;
+
+        let body = Box::new(body);
+        if select {
+            Ok(Command::Select { var: var.text, items, body })
+        } else {
+            Ok(Command::For { var: var.text, items, body })
+        }
+    }
+
+    /// Parse a conditional loop: `طالما ... ; نفذ ... ; انتهى`
+    fn parse_while(&mut self) -> ParseResult<Command> {
+        self.advance(); // consume طالما
+
+        self.skip_newlines();
+        let cond = self.parse_sequence()?;
+        self.expect_keyword(&TokenKind::Do)?;
+        self.skip_newlines();
+        let body = self.parse_sequence()?;
+        self.expect_keyword(&TokenKind::End)?;
+
+        Ok(Command::While { cond: Box::new(cond), body: Box::new(body) })
+    }
+
+    /// Parse a conditional: `اذا ... ; ثم ... ; والا ... ; انتهى`
+    fn parse_if(&mut self) -> ParseResult<Command> {
+        self.advance(); // consume اذا
+
+        self.skip_newlines();
+        let cond = self.parse_sequence()?;
+        self.expect_keyword(&TokenKind::Then)?;
+        self.skip_newlines();
+        let then_branch = self.parse_sequence()?;
+
+        let else_branch = if self.check(&TokenKind::Else) {
+            self.advance();
+            self.skip_newlines();
+            Some(Box::new(self.parse_sequence()?))
+        } else {
+            None
+        };
+        self.expect_keyword(&TokenKind::End)?;
+
+        Ok(Command::If {
+            cond: Box::new(cond),
+            then_branch: Box::new(then_branch),
+            else_branch,
+        })
+    }
+
+    /// Parse a simple command with arguments and redirections, after any
+    /// `NAME=VALUE` assignments
     fn parse_simple_command(&mut self) -> ParseResult<Command> {
+        let mut assignments = Vec::new();
+        while let Some(assignment) = self.try_assignment() {
+            assignments.push(assignment);
+        }
+        if !assignments.is_empty() {
+            let command = match Self::word_from(self.peek()) {
+                Some(_) if !self.peek().kind.is_keyword() => Some(Box::new(self.parse_simple_command()?)),
+                _ => None,
+            };
+            return Ok(Command::Assign { assignments, command });
+        }
+
         let name = self.expect_word()?;
         let mut args = Vec::new();
         let mut redirects = Vec::new();
@@ -159,7 +331,7 @@ impl Parser {
             }
         }
 
-        let mut cmd = Command::Simple { name, args, redirects };
+        let mut cmd = Command::Simple { name: name.text, args, redirects };
 
         // Check for background operator
         if self.check(&TokenKind::Background) {
@@ -170,69 +342,167 @@ impl Parser {
         Ok(cmd)
     }
 
+    /// Consume a `NAME=VALUE` word in command position
+    ///
+    /// NAME must be an identifier (Arabic letters allowed) written without
+    /// quotes or escapes; `NAME="..."` takes the quoted string right after
+    /// the `=` as its value.
+    fn try_assignment(&mut self) -> Option<Assignment> {
+        let token = self.peek();
+        let TokenKind::Word(text) = &token.kind else { return None };
+        let (name, value) = text.split_once('=')?;
+        if !is_identifier(name) || !token.lexeme.starts_with(&format!("{}=", name)) {
+            return None;
+        }
+
+        let (name, value, end) = (name.to_string(), value.to_string(), token.span.end);
+        self.advance();
+        let quoted = matches!(self.peek().kind, TokenKind::String(_)) && self.peek().span.start == end;
+        let value = match Self::word_from(self.peek()) {
+            Some(word) if value.is_empty() && quoted => {
+                self.advance();
+                word
+            }
+            _ => Word::new(value, Quoting::Unquoted),
+        };
+        Some(Assignment { name, value })
+    }
+
     /// Parse a redirection
     fn parse_redirect(&mut self) -> ParseResult<Redirect> {
         let kind = match &self.peek().kind {
             TokenKind::RedirectOut => RedirectKind::Out,
             TokenKind::RedirectIn => RedirectKind::In,
             TokenKind::Append => RedirectKind::Append,
+            TokenKind::Clobber => RedirectKind::Clobber,
+            TokenKind::ReadWrite => RedirectKind::ReadWrite,
+            TokenKind::RedirectErr => RedirectKind::ErrOut,
+            TokenKind::AppendErr => RedirectKind::ErrAppend,
+            TokenKind::RedirectBoth => RedirectKind::BothOut,
+            TokenKind::AppendBoth => RedirectKind::BothAppend,
             _ => {
                 let token = self.peek();
-                return Err(ParseError::new(
-                    "متوقع عامل إعادة توجيه / Expected redirect operator".to_string(),
-                    token.span.line,
-                    token.span.column,
-                ));
+                return Err(ParseError::new(("متوقع عامل إعادة توجيه", "Expected redirect operator"), token));
             }
         };
 
-        self.advance();
+        let operator = self.advance().clone();
+
+        // The target must be a filename, not EOF or another operator
+        match &self.peek().kind {
+            TokenKind::Word(_) | TokenKind::String(_) => {}
+            TokenKind::Eof | TokenKind::Newline => {
+                return Err(ParseError::new(
+                    (
+                        format!("لا يوجد ملف بعد عامل إعادة التوجيه '{}'", operator.kind),
+                        format!("missing redirect target after '{}'", operator.kind),
+                    ),
+                    &operator,
+                ));
+            }
+            other => {
+                return Err(ParseError::new(
+                    (
+                        format!("متوقع اسم ملف بعد '{}' لكن وجد '{}'", operator.kind, other),
+                        format!("Expected filename after '{}', got '{}'", operator.kind, other),
+                    ),
+                    &operator,
+                ));
+            }
+        }
         let target = self.expect_word()?;
 
-        Ok(Redirect::new(kind, target))
+        Ok(Redirect::new(kind, target.text))
     }
 
     /// Check if current token is a redirect operator
     fn check_redirect(&self) -> bool {
         matches!(
             self.peek().kind,
-            TokenKind::RedirectOut | TokenKind::RedirectIn | TokenKind::Append
+            TokenKind::RedirectOut
+                | TokenKind::RedirectIn
+                | TokenKind::Append
+                | TokenKind::Clobber
+                | TokenKind::ReadWrite
+                | TokenKind::RedirectErr
+                | TokenKind::AppendErr
+                | TokenKind::RedirectBoth
+                | TokenKind::AppendBoth
         )
     }
 
+    /// Check if current token closes a compound command (or a `طالما` condition)
+    fn check_terminator(&self) -> bool {
+        matches!(self.peek().kind, TokenKind::Do | TokenKind::Then | TokenKind::Else | TokenKind::End)
+    }
+
+    /// Expect and consume a specific keyword token
+    fn expect_keyword(&mut self, keyword: &TokenKind) -> ParseResult<()> {
+        if self.check(keyword) {
+            self.advance();
+            return Ok(());
+        }
+        let token = self.peek();
+        Err(ParseError::new(
+            (
+                format!("متوقع '{}' لكن وجد '{}'", keyword, token.kind),
+                format!("Expected '{}', got '{}'", keyword, token.kind),
+            ),
+            token,
+        ))
+    }
+
+    /// Expect a `;` or newline ending a clause, skipping extra newlines
+    fn expect_separator(&mut self) -> ParseResult<()> {
+        if self.check(&TokenKind::Semicolon) || self.check(&TokenKind::Newline) {
+            self.advance();
+            self.skip_newlines();
+            return Ok(());
+        }
+        let token = self.peek();
+        Err(ParseError::new(
+            (format!("متوقع ';' لكن وجد '{}'", token.kind), format!("Expected ';', got '{}'", token.kind)),
+            token,
+        ))
+    }
+
     /// Expect and consume a word token
-    fn expect_word(&mut self) -> ParseResult<String> {
+    fn expect_word(&mut self) -> ParseResult<Word> {
         let token = self.peek().clone();
-        match &token.kind {
-            TokenKind::Word(s) => {
+        match Self::word_from(&token) {
+            Some(word) if !token.kind.is_keyword() => {
                 self.advance();
-                Ok(s.clone())
-            }
-            TokenKind::String(s) => {
-                self.advance();
-                Ok(s.clone())
+                Ok(word)
             }
             _ => Err(ParseError::new(
-                format!("متوقع كلمة / Expected word, got: {}", token.kind),
-                token.span.line,
-                token.span.column,
+                ("متوقع كلمة".to_string(), format!("Expected word, got: {}", token.kind)),
+                &token,
             )),
         }
     }
 
     /// Try to consume a word token (returns None if not a word)
-    fn try_word(&mut self) -> Option<String> {
-        match &self.peek().kind {
-            TokenKind::Word(s) => {
-                let s = s.clone();
-                self.advance();
-                Some(s)
-            }
+    ///
+    /// Keywords count as plain words here, so `اطبع انتهى` prints "انتهى".
+    fn try_word(&mut self) -> Option<Word> {
+        let word = Self::word_from(self.peek())?;
+        self.advance();
+        Some(word)
+    }
+
+    /// Build a word from a word, string, or keyword token
+    fn word_from(token: &Token) -> Option<Word> {
+        match &token.kind {
+            TokenKind::Word(s) => Some(Word::new(s.clone(), Quoting::Unquoted)),
             TokenKind::String(s) => {
-                let s = s.clone();
-                self.advance();
-                Some(s)
+                let quoting = if token.lexeme.starts_with('\'') {
+                    Quoting::Single
+                } else {
+                    Quoting::Double
+                };
+                Some(Word::new(s.clone(), quoting))
             }
+            kind if kind.is_keyword() => Some(Word::new(token.lexeme.clone(), Quoting::Unquoted)),
             _ => None,
         }
     }
@@ -360,6 +630,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_clobber() {
+        let cmd = parse("اطبع نص >| log.txt").unwrap();
+        match cmd {
+            Command::Simple { redirects, .. } => {
+                assert_eq!(redirects.len(), 1);
+                assert_eq!(redirects[0].kind, RedirectKind::Clobber);
+                assert_eq!(redirects[0].target, "log.txt");
+            }
+            _ => panic!("Expected simple command with forced redirect"),
+        }
+    }
+
+    #[test]
+    fn test_read_write() {
+        let cmd = parse("cmd <> file").unwrap();
+        match cmd {
+            Command::Simple { name, args, redirects } => {
+                assert_eq!(name, "cmd");
+                assert!(args.is_empty());
+                assert_eq!(redirects, vec![Redirect::new(RedirectKind::ReadWrite, "file".to_string())]);
+            }
+            _ => panic!("Expected simple command with read-write redirect"),
+        }
+    }
+
+    #[test]
+    fn test_combined_redirect() {
+        match parse("make &> log").unwrap() {
+            Command::Simple { name, redirects, .. } => {
+                assert_eq!(name, "make");
+                assert_eq!(redirects, vec![Redirect::new(RedirectKind::BothOut, "log".to_string())]);
+            }
+            _ => panic!("Expected simple command with &> redirect"),
+        }
+        assert!(matches!(parse("make &>> log").unwrap(),
+            Command::Simple { redirects, .. } if redirects[0].kind == RedirectKind::BothAppend));
+    }
+
+    #[test]
+    fn test_stderr_append() {
+        match parse("cmd arg 2>> err > out").unwrap() {
+            Command::Simple { args, redirects, .. } => {
+                assert_eq!(args, vec!["arg"]);
+                assert_eq!(redirects, vec![
+                    Redirect::new(RedirectKind::ErrAppend, "err".to_string()),
+                    Redirect::new(RedirectKind::Out, "out".to_string()),
+                ]);
+            }
+            _ => panic!("Expected simple command with 2>> redirect"),
+        }
+        assert!(parse("cmd 2>").is_err());
+    }
+
     #[test]
     fn test_and_operator() {
         let cmd = parse("انشئ مجلد && انتقل مجلد").unwrap();
@@ -383,6 +707,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_newlines_separate_commands() {
+        let cmd = parse("اطبع أ\nاطبع ب\n\necho c\n").unwrap();
+        match cmd {
+            Command::Sequence(cmds) => {
+                let names: Vec<_> = cmds
+                    .iter()
+                    .map(|c| match c {
+                        Command::Simple { name, .. } => name.as_str(),
+                        _ => panic!("Expected simple command"),
+                    })
+                    .collect();
+                assert_eq!(names, ["اطبع", "اطبع", "echo"]);
+            }
+            _ => panic!("Expected sequence"),
+        }
+
+        // Newlines also separate the clauses of compound commands
+        let cmd = parse("لكل x في أ ب\nنفذ\n  اطبع $x\n  اطبع .\nانتهى\nاطبع بعد").unwrap();
+        match cmd {
+            Command::Sequence(cmds) => {
+                assert!(matches!(&cmds[0], Command::For { body, .. } if matches!(**body, Command::Sequence(ref b) if b.len() == 2)));
+                assert_eq!(cmds.len(), 2);
+            }
+            _ => panic!("Expected sequence"),
+        }
+    }
+
     #[test]
     fn test_background() {
         let cmd = parse("sleep 10 &").unwrap();
@@ -415,9 +767,258 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_missing_redirect_target() {
+        let err = parse("اطبع نص >").unwrap_err();
+        assert!(err.message.en.contains("missing redirect target"));
+        // Points at the operator, not at EOF
+        assert_eq!(err.grapheme_column, 9);
+    }
+
+    #[test]
+    fn test_redirect_target_is_operator() {
+        let err = parse("اطبع نص > | اقرأ").unwrap_err();
+        assert!(err.message.en.contains("Expected filename after '>'"));
+        assert_eq!(err.grapheme_column, 9);
+
+        let err = parse("اقرأ < ;").unwrap_err();
+        assert!(err.message.en.contains("Expected filename after '<'"));
+    }
+
+    #[test]
+    fn test_error_caret_with_combining_marks() {
+        let input = "اطبعُ مَرحَباً >";
+        let err = parse(input).unwrap_err().with_source(input);
+        assert_eq!(err.grapheme_column, 12);
+
+        let rendered = err.to_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines[0].contains("[1:12]"));
+        assert_eq!(lines[1], format!("  {}", input));
+        assert_eq!(lines[2], format!("  {}^", " ".repeat(11)));
+    }
+
+    #[test]
+    fn test_error_renders_in_each_locale() {
+        let input = "اطبع |";
+        let err = parse(input).unwrap_err().with_source(input);
+        let english = err.render(Locale::English);
+        assert!(english.starts_with("Parse error [1:7]: Expected word, got: EOF\n  اطبع |"));
+        assert!(err.render(Locale::Arabic).starts_with("خطأ نحوي [1:7]: متوقع كلمة\n"));
+        assert_eq!(err.render(Locale::Both), err.to_string());
+    }
+
+    #[test]
+    fn test_error_caret_spans_token() {
+        let input = "لكل س في أ ب ; نفذ اطبع ; وإلا";
+        let err = parse(input).unwrap_err().with_source(input);
+        let rendered = err.to_string();
+        assert!(rendered.ends_with(&format!("  {}^^^^", " ".repeat(26))));
+    }
+
+    #[test]
+    fn test_error_without_source_has_no_caret() {
+        let err = parse("اطبع >").unwrap_err();
+        assert_eq!(err.to_string().lines().count(), 1);
+    }
+
     #[test]
     fn test_empty_input() {
         let cmd = parse("").unwrap();
         assert!(matches!(cmd, Command::Empty));
     }
+
+    #[test]
+    fn test_for_loop() {
+        let cmd = parse("لكل x في أ ب ج ; نفذ اطبع $x ; انتهى").unwrap();
+        match cmd {
+            Command::For { var, items, body } => {
+                assert_eq!(var, "x");
+                assert_eq!(items, vec!["أ", "ب", "ج"]);
+                assert!(matches!(*body, Command::Simple { ref name, .. } if name == "اطبع"));
+            }
+            _ => panic!("Expected for loop"),
+        }
+    }
+
+    #[test]
+    fn test_for_loop_english_with_sequence_body() {
+        let cmd = parse("for f in a b ; do echo $f ; echo done ; done").unwrap();
+        match cmd {
+            Command::For { body, .. } => match *body {
+                Command::Sequence(cmds) => {
+                    assert_eq!(cmds.len(), 2);
+                    // A keyword in argument position is a plain word
+                    assert!(matches!(&cmds[1], Command::Simple { args, .. } if args == &vec!["done"]));
+                }
+                _ => panic!("Expected sequence body"),
+            },
+            _ => panic!("Expected for loop"),
+        }
+    }
+
+    #[test]
+    fn test_nested_for_loop() {
+        let cmd = parse("لكل x في 1 2 ; نفذ لكل y في أ ; نفذ اطبع $x$y ; انتهى ; انتهى").unwrap();
+        match cmd {
+            Command::For { body, .. } => assert!(matches!(*body, Command::For { .. })),
+            _ => panic!("Expected for loop"),
+        }
+    }
+
+    #[test]
+    fn test_while_loop() {
+        let cmd = parse("طالما اختبار -f قفل ; نفذ نم 1 ; انتهى").unwrap();
+        match cmd {
+            Command::While { cond, body } => {
+                assert!(matches!(*cond, Command::Simple { ref name, .. } if name == "اختبار"));
+                assert!(matches!(*body, Command::Simple { ref name, .. } if name == "نم"));
+            }
+            _ => panic!("Expected while loop"),
+        }
+        assert!(parse("while true ; do echo x").unwrap_err().message.en.contains("Expected 'انتهى/done'"));
+    }
+
+    #[test]
+    fn test_select_menu() {
+        let cmd = parse("اختر x في أ ب ; نفذ اطبع $x ; انتهى").unwrap();
+        match cmd {
+            Command::Select { var, items, .. } => {
+                assert_eq!(var, "x");
+                assert_eq!(items, vec!["أ", "ب"]);
+            }
+            _ => panic!("Expected select"),
+        }
+        assert!(matches!(parse("select x in a ; do echo $x ; done").unwrap(), Command::Select { .. }));
+    }
+
+    #[test]
+    fn test_for_loop_missing_done() {
+        let err = parse("لكل x في أ ; نفذ اطبع $x").unwrap_err();
+        assert!(err.message.en.contains("Expected 'انتهى/done'"));
+    }
+
+    #[test]
+    fn test_quoting_kinds() {
+        let cmd = parse(r#"اطبع أ "ب" 'ج' «د»"#).unwrap();
+        match cmd {
+            Command::Simple { args, .. } => {
+                let quoting: Vec<Quoting> = args.iter().map(|a| a.quoting).collect();
+                assert_eq!(quoting, vec![Quoting::Unquoted, Quoting::Double, Quoting::Single, Quoting::Double]);
+            }
+            _ => panic!("Expected simple command"),
+        }
+    }
+
+    #[test]
+    fn test_if_else() {
+        let cmd = parse("اذا اختبار -f x ; ثم اطبع موجود ; والا اطبع مفقود ; انتهى").unwrap();
+        match cmd {
+            Command::If { cond, then_branch, else_branch } => {
+                assert!(matches!(*cond, Command::Simple { ref name, .. } if name == "اختبار"));
+                assert!(matches!(*then_branch, Command::Simple { ref args, .. } if args == &vec!["موجود"]));
+                assert!(else_branch.is_some());
+            }
+            _ => panic!("Expected if"),
+        }
+    }
+
+    #[test]
+    fn test_if_without_else_english() {
+        let cmd = parse("if test -d x ; then echo yes ; fi").unwrap();
+        assert!(matches!(cmd, Command::If { else_branch: None, .. }));
+    }
+
+    #[test]
+    fn test_nested_if() {
+        let cmd = parse("اذا أ ; ثم اذا ب ; ثم ج ; انتهى ; والا د ; انتهى").unwrap();
+        match cmd {
+            Command::If { then_branch, else_branch, .. } => {
+                assert!(matches!(*then_branch, Command::If { .. }));
+                assert!(else_branch.is_some());
+            }
+            _ => panic!("Expected if"),
+        }
+    }
+
+    #[test]
+    fn test_if_missing_then() {
+        let err = parse("اذا أ ; اطبع ب ; انتهى").unwrap_err();
+        assert!(err.message.en.contains("Expected 'ثم/then'"));
+    }
+
+    #[test]
+    fn test_negated_pipeline() {
+        let cmd = parse("! اقرأ ملف | ابحث كلمة && اطبع لا").unwrap();
+        match cmd {
+            Command::And(left, _) => match *left {
+                Command::Not(inner) => assert!(matches!(*inner, Command::Pipeline(ref cmds) if cmds.len() == 2)),
+                other => panic!("Expected not, got {:?}", other),
+            },
+            _ => panic!("Expected and"),
+        }
+        assert!(matches!(parse("! ! true").unwrap(), Command::Not(inner) if matches!(*inner, Command::Not(_))));
+    }
+
+    #[test]
+    fn test_bang_as_argument() {
+        match parse("اختبار ! -f x").unwrap() {
+            Command::Simple { args, .. } => assert_eq!(args, vec!["!", "-f", "x"]),
+            _ => panic!("Expected simple command"),
+        }
+        assert!(parse("اطبع أ | ! اطبع ب").is_err());
+    }
+
+    #[test]
+    fn test_assignment_in_command_position() {
+        match parse("اسم=قيمة").unwrap() {
+            Command::Assign { assignments, command } => {
+                assert_eq!(assignments, vec![Assignment { name: "اسم".to_string(), value: "قيمة".into() }]);
+                assert!(command.is_none());
+            }
+            other => panic!("Expected assignment, got {:?}", other),
+        }
+
+        // `=` in an argument stays part of the word
+        match parse("اطبع a=b").unwrap() {
+            Command::Simple { name, args, .. } => {
+                assert_eq!(name, "اطبع");
+                assert_eq!(args, vec!["a=b"]);
+            }
+            other => panic!("Expected simple command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_assignments_prefixing_a_command() {
+        match parse("A=1 B= C=\"x y\" اطبع $A > out").unwrap() {
+            Command::Assign { assignments, command } => {
+                let names: Vec<&str> = assignments.iter().map(|a| a.name.as_str()).collect();
+                assert_eq!(names, vec!["A", "B", "C"]);
+                assert_eq!(assignments[1].value, "");
+                assert_eq!(assignments[2].value, Word::new("x y".to_string(), Quoting::Double));
+                match command.as_deref() {
+                    Some(Command::Simple { name, args, redirects }) => {
+                        assert_eq!(name, "اطبع");
+                        assert_eq!(args, &vec!["$A"]);
+                        assert_eq!(redirects.len(), 1);
+                    }
+                    other => panic!("Expected simple command, got {:?}", other),
+                }
+            }
+            other => panic!("Expected assignment, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_non_identifier_is_not_assignment() {
+        for input in ["1a=b", "=b", "a-b=c", "\"a=b\"", "a\\=b"] {
+            assert!(matches!(parse(input).unwrap(), Command::Simple { .. }), "{}", input);
+        }
+        // A separated quoted string is the command, not the value
+        assert!(matches!(
+            parse("A= \"اطبع\"").unwrap(),
+            Command::Assign { command: Some(_), .. }
+        ));
+    }
 }
@@ -2,15 +2,27 @@
 //!
 //! Provides tab completion for:
 //! - Built-in commands (Arabic and English)
-//! - File and directory paths
+//! - File and directory paths, filtered by what each command accepts, under
+//!   `~` or a leading `$VAR` from the environment
+//! - Words registered for a command's arguments with `اكمل` (complete)
+//!
+//! The helper also asks for continuation lines while input is incomplete,
+//! and suggests the rest of the line from history (accepted with →).
 
 use rustyline::completion::{Completer, Pair};
 use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
-use rustyline::validate::Validator;
+use rustyline::history::{History, SearchDirection};
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
 use rustyline::{Context, Helper};
 use std::borrow::Cow;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::commands::builtin_commands;
+use crate::executor::CustomCompletions;
+use crate::lexer::is_incomplete;
+use crate::utils::expand_tilde_from;
 
 /// Ocean shell helper combining completion, hints, and highlighting
 #[derive(Default)]
@@ -19,25 +31,61 @@ pub struct OceanHelper {
 }
 
 impl OceanHelper {
-    pub fn new() -> Self {
+    /// A helper that also offers the words registered in `completions`
+    pub fn with_completions(completions: CustomCompletions) -> Self {
         Self {
-            completer: OceanCompleter::new(),
+            completer: OceanCompleter { custom: completions, ..OceanCompleter::new() },
         }
     }
+
+    /// Complete relative paths in `cwd`, the shell's directory after `انتقل`
+    pub fn set_cwd(&mut self, cwd: &Path) {
+        self.completer.cwd = cwd.to_path_buf();
+    }
 }
 
 impl Helper for OceanHelper {}
 
-impl Validator for OceanHelper {}
+impl Validator for OceanHelper {
+    /// Ask for a continuation line while a quote or trailing operator is open
+    fn validate(&self, ctx: &mut ValidationContext<'_>) -> rustyline::Result<ValidationResult> {
+        if is_incomplete(ctx.input()) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
 
 impl Hinter for OceanHelper {
     type Hint = String;
 
-    fn hint(&self, _line: &str, _pos: usize, _ctx: &Context<'_>) -> Option<String> {
-        None
+    /// Suggest the rest of the most recent history entry the line starts
+    /// with, while the cursor is at the end
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+        history_hint(line, ctx.history())
     }
 }
 
+/// The text completing `line` to its most recent match in `history`
+///
+/// Lines are compared in NFC, so Arabic typed with a different mark order
+/// still matches the stored command.
+fn history_hint(line: &str, history: &dyn History) -> Option<String> {
+    if line.trim().is_empty() {
+        return None;
+    }
+    let line: String = line.nfc().collect();
+    (0..history.len()).rev().find_map(|index| {
+        let entry = history.get(index, SearchDirection::Reverse).ok()??.entry;
+        let entry: String = entry.nfc().collect();
+        entry.strip_prefix(line.as_str()).filter(|rest| !rest.is_empty()).map(str::to_string)
+    })
+}
+
 impl Highlighter for OceanHelper {
     fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
         Cow::Owned(format!("\x1b[90m{}\x1b[0m", hint))
@@ -57,10 +105,54 @@ impl Completer for OceanHelper {
     }
 }
 
+/// What kind of candidates a command argument accepts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArgKind {
+    /// Files and directories (the default)
+    Paths,
+    /// Directories only
+    Directories,
+    /// Regular files only
+    Files,
+    /// Not a path (a mode, pattern, user, ...): no completion
+    Nothing,
+}
+
+/// Per-command argument completion: (names, first argument, later arguments)
+///
+/// Commands not listed here complete every argument as a path.
+const COMPLETION_POLICIES: &[(&[&str], ArgKind, ArgKind)] = &[
+    (&["انتقل", "cd"], ArgKind::Directories, ArgKind::Directories),
+    (&["انشئ", "mkdir"], ArgKind::Directories, ArgKind::Directories),
+    (&["اقرأ", "cat"], ArgKind::Files, ArgKind::Files),
+    (&["صلاحيات", "chmod"], ArgKind::Nothing, ArgKind::Paths),
+    (&["مالك", "chown"], ArgKind::Nothing, ArgKind::Paths),
+    (&["ابحث", "grep", "search"], ArgKind::Nothing, ArgKind::Paths),
+    (&["بدل", "tr"], ArgKind::Nothing, ArgKind::Nothing),
+    (&["نم", "sleep"], ArgKind::Nothing, ArgKind::Nothing),
+    (&["كرر_نص", "مكرر", "yes"], ArgKind::Nothing, ArgKind::Nothing),
+    (&["اقتل", "kill"], ArgKind::Nothing, ArgKind::Nothing),
+];
+
+/// Look up how argument `index` (0-based) of `command` should complete
+fn arg_kind(command: &str, index: usize) -> ArgKind {
+    COMPLETION_POLICIES
+        .iter()
+        .find(|(names, _, _)| names.contains(&command))
+        .map(|&(_, first, rest)| if index == 0 { first } else { rest })
+        .unwrap_or(ArgKind::Paths)
+}
+
 /// Auto-completer for Ocean shell commands and file paths
 pub struct OceanCompleter {
     /// List of built-in commands (Arabic, English)
-    commands: Vec<(&'static str, &'static str)>,
+    commands: &'static [(&'static str, &'static str)],
+    /// Argument words registered at runtime with `اكمل`
+    custom: CustomCompletions,
+    /// The directory `~/` completes in
+    home: Option<PathBuf>,
+    /// The directory relative paths complete in
+    cwd: PathBuf,
 }
 
 impl Default for OceanCompleter {
@@ -73,26 +165,10 @@ impl OceanCompleter {
     /// Create a new completer with all built-in commands
     pub fn new() -> Self {
         Self {
-            commands: vec![
-                ("خروج", "exit"),
-                ("مساعدة", "help"),
-                ("اطبع", "echo"),
-                ("امسح", "clear"),
-                ("اين", "pwd"),
-                ("انتقل", "cd"),
-                ("اعرض", "ls"),
-                ("اقرأ", "cat"),
-                ("انشئ", "mkdir"),
-                ("المس", "touch"),
-                ("احذف", "rm"),
-                ("انسخ", "cp"),
-                ("انقل", "mv"),
-                ("ابحث", "grep"),
-                ("صلاحيات", "chmod"),
-                ("مالك", "chown"),
-                ("رابط", "ln"),
-                ("اصدار", "version"),
-            ],
+            commands: builtin_commands(),
+            custom: CustomCompletions::default(),
+            home: dirs::home_dir(),
+            cwd: std::env::current_dir().unwrap_or_default(),
         }
     }
 
@@ -101,7 +177,7 @@ impl OceanCompleter {
         let mut matches = Vec::new();
         let partial_lower = partial.to_lowercase();
 
-        for (ar, en) in &self.commands {
+        for (ar, en) in self.commands {
             // Match Arabic commands
             if ar.starts_with(partial) {
                 matches.push(Pair {
@@ -121,9 +197,27 @@ impl OceanCompleter {
         matches
     }
 
-    /// Complete a file or directory path
-    fn complete_path(&self, partial: &str) -> Vec<Pair> {
+    /// Complete one of the words registered with `اكمل` for `command`
+    fn complete_custom(&self, command: &str, partial: &str) -> Vec<Pair> {
+        let Ok(table) = self.custom.read() else {
+            return Vec::new();
+        };
+        let partial: String = partial.nfc().collect();
+        table
+            .get(command)
+            .into_iter()
+            .flatten()
+            .filter(|word| word.nfc().collect::<String>().starts_with(&partial))
+            .map(|word| Pair { display: word.clone(), replacement: word.clone() })
+            .collect()
+    }
+
+    /// Complete a file or directory path, keeping only candidates of `kind`
+    fn complete_path(&self, partial: &str, kind: ArgKind) -> Vec<Pair> {
         let mut matches = Vec::new();
+        if kind == ArgKind::Nothing {
+            return matches;
+        }
 
         // Handle empty input - list current directory
         let (dir_path, prefix) = if partial.is_empty() {
@@ -131,33 +225,34 @@ impl OceanCompleter {
         } else if partial.ends_with('/') || partial.ends_with('\\') {
             (Path::new(partial), "")
         } else {
+            // A bare name's parent is empty, which means the current directory
             let path = Path::new(partial);
             (
-                path.parent().unwrap_or(Path::new(".")),
+                path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new(".")),
                 path.file_name().and_then(|s| s.to_str()).unwrap_or(""),
             )
         };
 
-        // Handle ~ expansion
+        // Handle ~ and ~user expansion of the directory part
         let expanded_dir = if partial.starts_with('~') {
-            if let Some(home) = dirs::home_dir() {
-                if partial == "~" || partial == "~/" {
-                    home
-                } else {
-                    home.join(partial.trim_start_matches("~/"))
-                        .parent()
-                        .unwrap_or(&home)
-                        .to_path_buf()
-                }
-            } else {
-                dir_path.to_path_buf()
+            match partial.rsplit_once('/') {
+                Some((dir, _)) => expand_tilde_from(dir, self.home.clone()),
+                None => expand_tilde_from(partial, self.home.clone()),
+            }
+        } else if partial.starts_with('$') {
+            // `$VAR` on its own is a variable name, not a directory yet
+            match partial.rsplit_once('/') {
+                Some((dir, _)) => expand_variable(dir, |name| std::env::var(name).ok()),
+                None => return matches,
             }
         } else {
             dir_path.to_path_buf()
         };
 
+        let prefix_nfc: String = prefix.nfc().collect();
+
         // Read directory entries
-        if let Ok(entries) = std::fs::read_dir(&expanded_dir) {
+        if let Ok(entries) = std::fs::read_dir(self.cwd.join(&expanded_dir)) {
             for entry in entries.filter_map(|e| e.ok()) {
                 let name = entry.file_name().to_string_lossy().to_string();
 
@@ -166,8 +261,16 @@ impl OceanCompleter {
                     continue;
                 }
 
-                if name.starts_with(prefix) {
-                    let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                // Compare in NFC (as the lexer sees input), so decomposed and
+                // composed spellings of the same Arabic name match
+                if name.nfc().collect::<String>().starts_with(&prefix_nfc) {
+                    // Follows symlinks, so a link to a directory counts as one
+                    let is_dir = entry.path().is_dir();
+                    match kind {
+                        ArgKind::Directories if !is_dir => continue,
+                        ArgKind::Files if is_dir => continue,
+                        _ => {}
+                    }
 
                     // Build the full replacement path
                     let replacement = if partial.is_empty() || partial == "." {
@@ -240,6 +343,8 @@ impl OceanCompleter {
             } else {
                 words.last().copied().unwrap_or("")
             };
+            let arg_index = if line_to_cursor.ends_with(' ') { words.len() - 1 } else { words.len() - 2 };
+            let kind = arg_kind(words[0], arg_index);
             let start = if line_to_cursor.ends_with(' ') {
                 pos
             } else {
@@ -248,11 +353,33 @@ impl OceanCompleter {
                     .map(|i| i + 1)
                     .unwrap_or(0)
             };
-            Ok((start, self.complete_path(partial)))
+            // Registered words come first, then matching paths
+            let mut matches = self.complete_custom(words[0], partial);
+            matches.extend(self.complete_path(partial, kind));
+            Ok((start, matches))
         }
     }
 }
 
+/// Expand a leading `$NAME` or `${NAME}`, looking the name up with `lookup`
+/// (the environment)
+///
+/// Only used to find the directory to scan; replacements keep the variable
+/// as typed. An unset variable leaves the path unchanged.
+fn expand_variable(path: &str, lookup: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let Some(rest) = path.strip_prefix('$') else {
+        return PathBuf::from(path);
+    };
+    let (name, tail) = match rest.strip_prefix('{') {
+        Some(braced) => braced.split_once('}').unwrap_or(("", "")),
+        None => rest.split_at(rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len())),
+    };
+    match lookup(name) {
+        Some(value) if !name.is_empty() => PathBuf::from(format!("{}{}", value, tail)),
+        _ => PathBuf::from(path),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +398,77 @@ mod tests {
         assert!(matches.iter().any(|p| p.replacement == "اطبع"));
     }
 
+    /// Everything `execute_builtin` dispatches must also be completable
+    #[test]
+    fn test_every_builtin_completes() {
+        let completer = OceanCompleter::new();
+        for spec in crate::commands::BUILTINS {
+            for name in [spec.arabic, spec.english] {
+                let matches = completer.complete_command(name);
+                assert!(matches.iter().any(|p| p.replacement == name), "'{}' not completable", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tilde_path_completion() {
+        let home = tempfile::tempdir().unwrap();
+        std::fs::create_dir(home.path().join("notes")).unwrap();
+        std::fs::write(home.path().join("notes/ocean_note.txt"), "").unwrap();
+
+        let completer = OceanCompleter { home: Some(home.path().to_path_buf()), ..OceanCompleter::new() };
+        let matches = completer.complete_path("~/notes/ocean_", ArgKind::Paths);
+        assert!(matches.iter().any(|p| p.replacement == "~/notes/ocean_note.txt"));
+    }
+
+    #[test]
+    fn test_variable_path_completion() {
+        // Cargo sets this for test runs, so the environment isn't modified
+        assert_eq!(complete_line("cd $CARGO_MANIFEST_DIR/sr"), ["$CARGO_MANIFEST_DIR/src/"]);
+        assert_eq!(complete_line("اقرأ ${CARGO_MANIFEST_DIR}/Cargo.t"), ["${CARGO_MANIFEST_DIR}/Cargo.toml"]);
+        assert!(complete_line("cd $CARGO_MANIFEST_DI").is_empty());
+    }
+
+    #[test]
+    fn test_expand_variable() {
+        let lookup = |name: &str| (name == "DIR").then(|| "/tmp/ocean".to_string());
+        assert_eq!(expand_variable("$DIR/مجلد", lookup), PathBuf::from("/tmp/ocean/مجلد"));
+        assert_eq!(expand_variable("${DIR}x", lookup), PathBuf::from("/tmp/oceanx"));
+        assert_eq!(expand_variable("$UNSET/a", lookup), PathBuf::from("$UNSET/a"));
+        assert_eq!(expand_variable("${}/a", lookup), PathBuf::from("${}/a"));
+    }
+
+    #[test]
+    fn test_completion_policies_name_builtins() {
+        for (names, _, _) in COMPLETION_POLICIES {
+            for name in *names {
+                assert!(crate::commands::find_builtin(name).is_some(), "{} is not a builtin", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_completions_join_paths() {
+        let completions = CustomCompletions::default();
+        completions
+            .write()
+            .unwrap()
+            .insert("mycmd".to_string(), vec!["--foo".to_string(), "--bar".to_string(), "نص".to_string()]);
+        let completer = OceanCompleter { custom: completions, ..OceanCompleter::new() };
+        let history = rustyline::history::DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let complete = |line: &str| -> Vec<String> {
+            let (_, pairs) = completer.complete(line, line.len(), &ctx).unwrap();
+            pairs.into_iter().map(|p| p.replacement).collect()
+        };
+
+        assert_eq!(complete("mycmd --f"), ["--foo"]);
+        assert_eq!(complete("mycmd a --b"), ["--bar"]);
+        assert_eq!(complete("mycmd ن"), ["نص"]);
+        // Other commands don't see them
+        assert!(complete("otherCmd --f").is_empty());
+    }
+
     #[test]
     fn test_empty_command_completion() {
         let completer = OceanCompleter::new();
@@ -278,4 +476,116 @@ mod tests {
         // Should return all commands (18 pairs = 36 total)
         assert!(matches.len() >= 18);
     }
+
+    /// Complete `line` at its end and return the replacements
+    fn complete_line(line: &str) -> Vec<String> {
+        let history = rustyline::history::DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let (_, pairs) = OceanCompleter::new().complete(line, line.len(), &ctx).unwrap();
+        pairs.into_iter().map(|p| p.replacement).collect()
+    }
+
+    fn sample_dir() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("مجلد")).unwrap();
+        std::fs::write(dir.path().join("ملف.txt"), "").unwrap();
+        let base = format!("{}/", dir.path().display());
+        (dir, base)
+    }
+
+    #[test]
+    fn test_cd_completes_directories_only() {
+        let (_dir, base) = sample_dir();
+        for cmd in ["انتقل", "cd"] {
+            assert_eq!(complete_line(&format!("{} {}", cmd, base)), [format!("{}مجلد/", base)]);
+        }
+    }
+
+    #[test]
+    fn test_cd_completes_directory_prefix_step_by_step() {
+        let (dir, base) = sample_dir();
+        std::fs::create_dir(dir.path().join("مكتبة")).unwrap();
+        std::fs::create_dir(dir.path().join("مجلد/داخلي")).unwrap();
+        std::fs::write(dir.path().join("مجلد/ملاحظة.txt"), "").unwrap();
+
+        let history = rustyline::history::DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let line = format!("انتقل {}م", base);
+        let (_, pairs) = OceanCompleter::new().complete(&line, line.len(), &ctx).unwrap();
+        let mut shown: Vec<String> = pairs.into_iter().map(|p| p.display).collect();
+        shown.sort();
+        assert_eq!(shown, ["مجلد/", "مكتبة/"]);
+
+        // A lone match ends in `/`, so completing again lists its contents
+        assert_eq!(complete_line(&format!("cd {}مجل", base)), [format!("{}مجلد/", base)]);
+        assert_eq!(complete_line(&format!("cd {}مجلد/", base)), [format!("{}مجلد/داخلي/", base)]);
+    }
+
+    #[test]
+    fn test_cat_completes_files_only() {
+        let (_dir, base) = sample_dir();
+        assert_eq!(complete_line(&format!("اقرأ {}", base)), [format!("{}ملف.txt", base)]);
+    }
+
+    #[test]
+    fn test_chmod_mode_is_not_a_path() {
+        let (_dir, base) = sample_dir();
+        assert!(complete_line(&format!("صلاحيات {}", base)).is_empty());
+        // The file argument after the mode completes normally
+        assert_eq!(complete_line(&format!("chmod 755 {}", base)).len(), 2);
+    }
+
+    #[test]
+    fn test_unlisted_command_completes_all_paths() {
+        let (_dir, base) = sample_dir();
+        assert_eq!(complete_line(&format!("احذف {}", base)).len(), 2);
+    }
+
+    #[test]
+    fn test_history_hint_uses_most_recent_match() {
+        let mut history = rustyline::history::DefaultHistory::new();
+        for line in ["اطبع مرحبا", "اعرض -l", "اطبع سلام"] {
+            history.add(line).unwrap();
+        }
+
+        assert_eq!(history_hint("اطبع ", &history), Some("سلام".to_string()));
+        assert_eq!(history_hint("اعرض", &history), Some(" -l".to_string()));
+        // A complete match, an unknown line and blank input suggest nothing
+        assert_eq!(history_hint("اعرض -l", &history), None);
+        assert_eq!(history_hint("cat", &history), None);
+        assert_eq!(history_hint("  ", &history), None);
+
+        let helper = OceanHelper::default();
+        let ctx = Context::new(&history);
+        assert_eq!(helper.hint("اطبع م", 3, &ctx), None);
+        assert_eq!(helper.hint("اطبع م", "اطبع م".len(), &ctx), Some("رحبا".to_string()));
+    }
+
+    #[test]
+    fn test_history_hint_ignores_normalization_form() {
+        let mut history = rustyline::history::DefaultHistory::new();
+        // Stored with shadda before fatha; typed with fatha before shadda
+        history.add("اطبع \u{0645}\u{0651}\u{064E}رحبا").unwrap();
+        assert_eq!(history_hint("اطبع \u{0645}\u{064E}\u{0651}", &history), Some("رحبا".to_string()));
+    }
+
+    #[test]
+    fn test_path_completion_ignores_normalization_form() {
+        let dir = tempfile::tempdir().unwrap();
+        // "آمال" with آ decomposed into ا + madda (NFD) on disk
+        let decomposed = "\u{0627}\u{0653}مال.txt";
+        std::fs::write(dir.path().join(decomposed), "").unwrap();
+        std::fs::write(dir.path().join("\u{0622}خر.txt"), "").unwrap();
+        let base = format!("{}/", dir.path().display());
+
+        // Typed composed, matched decomposed; the on-disk name is kept
+        let matches = OceanCompleter::new().complete_path(&format!("{}\u{0622}م", base), ArgKind::Paths);
+        let replacements: Vec<String> = matches.into_iter().map(|p| p.replacement).collect();
+        assert_eq!(replacements, [format!("{}{}", base, decomposed)]);
+
+        // Typed decomposed, matched composed
+        let matches = OceanCompleter::new().complete_path(&format!("{}\u{0627}\u{0653}خ", base), ArgKind::Paths);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].display, "\u{0622}خر.txt");
+    }
 }
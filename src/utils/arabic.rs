@@ -4,16 +4,31 @@
 //!
 //! Arabic text requires special processing:
 //! 1. **Letter Shaping**: Arabic letters have different forms (initial, medial, final, isolated)
-//! 2. **RTL Alignment**: Text should start from the right side of the terminal
+//! 2. **BiDi Reordering**: Mixed Arabic/English runs must be put in visual order
+//!    on terminals that don't implement the Unicode Bidirectional Algorithm
+//! 3. **RTL Alignment**: Text should start from the right side of the terminal
 
 use arabic_reshaper::arabic_reshape;
 use crossterm::terminal;
+use unicode_bidi::{BidiInfo, Level};
 use unicode_width::UnicodeWidthStr;
+use std::borrow::Cow;
 use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
 
 /// VTE escape code for RTL auto-detection mode
 const VTE_RTL_AUTO: &str = "\x1b[?2501h";
 
+/// Width used when the terminal size cannot be queried
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// Cached terminal width (0 means the cache is invalid)
+static TERMINAL_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Guards one-time installation of the resize handler
+static RESIZE_HANDLER: Once = Once::new();
+
 /// Process Arabic text for correct terminal display
 ///
 /// This function reshapes Arabic letters to their connected forms.
@@ -62,15 +77,76 @@ fn is_arabic_char(c: char) -> bool {
     )
 }
 
+/// Convert Arabic-Indic digits (٠-٩, ۰-۹) and the Arabic decimal separator
+/// (٫) to ASCII, so numbers written in Arabic can be parsed
+pub fn normalize_digits(text: &str) -> Cow<'_, str> {
+    let is_arabic_numeral = |c: char| matches!(c, '\u{0660}'..='\u{0669}' | '\u{06F0}'..='\u{06F9}' | '\u{066B}');
+    if !text.chars().any(is_arabic_numeral) {
+        return Cow::Borrowed(text);
+    }
+
+    Cow::Owned(
+        text.chars()
+            .map(|c| match c {
+                '\u{0660}'..='\u{0669}' => char::from(b'0' + (c as u32 - 0x0660) as u8),
+                '\u{06F0}'..='\u{06F9}' => char::from(b'0' + (c as u32 - 0x06F0) as u8),
+                '\u{066B}' => '.',
+                c => c,
+            })
+            .collect(),
+    )
+}
+
 /// Process text, only applying Arabic shaping if Arabic characters are present
 ///
-/// This is an optimization to avoid processing pure ASCII/English text
+/// This is an optimization to avoid processing pure ASCII/English text.
+/// In colored text only the runs between ANSI escapes are shaped, so the
+/// escapes reach the terminal exactly as the command wrote them.
 pub fn shape_if_arabic(text: &str) -> String {
-    if contains_arabic(text) {
-        shape_arabic(text)
-    } else {
-        text.to_string()
+    if !contains_arabic(text) {
+        return text.to_string();
     }
+    if !text.contains('\x1b') {
+        return shape_arabic(text);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut start = 0;
+    for (escape_start, escape_end) in ansi_escapes(text) {
+        result.push_str(&shape_if_arabic(&text[start..escape_start]));
+        result.push_str(&text[escape_start..escape_end]);
+        start = escape_end;
+    }
+    result.push_str(&shape_if_arabic(&text[start..]));
+    result
+}
+
+/// Reorder a line from logical to visual order (Unicode BiDi Algorithm)
+///
+/// Uses a right-to-left base direction so that embedded English words and
+/// numbers keep their left-to-right order inside Arabic text. Each line is
+/// reordered independently. Text without Arabic, or already containing ANSI
+/// escapes (which must not be moved), is returned unchanged.
+pub fn reorder_bidi(text: &str) -> Cow<'_, str> {
+    if !contains_arabic(text) || text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            if line.is_empty() {
+                return String::new();
+            }
+            let info = BidiInfo::new(line, Some(Level::rtl()));
+            info.paragraphs
+                .iter()
+                .map(|para| info.reorder_line(para, para.range.clone()).into_owned())
+                .collect()
+        })
+        .collect();
+
+    Cow::Owned(lines.join("\n"))
 }
 
 // ============================================================================
@@ -98,20 +174,120 @@ pub fn enable_rtl_mode() -> bool {
 }
 
 /// Get terminal width, default to 80 if unavailable
+///
+/// The width is cached so that printing many lines doesn't query the
+/// terminal for each one. On Unix the cache is invalidated by SIGWINCH.
 pub fn get_terminal_width() -> usize {
-    terminal::size().map(|(w, _)| w as usize).unwrap_or(80)
+    RESIZE_HANDLER.call_once(install_resize_handler);
+
+    let cached = TERMINAL_WIDTH.load(Ordering::Relaxed);
+    if cached != 0 {
+        return cached;
+    }
+
+    let width = terminal::size()
+        .map(|(w, _)| w as usize)
+        .ok()
+        .filter(|&w| w > 0)
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH);
+    TERMINAL_WIDTH.store(width, Ordering::Relaxed);
+    width
+}
+
+/// Forget the cached terminal width so the next call re-queries it
+///
+/// Useful after the terminal may have been resized without a signal
+/// reaching us (e.g. while another handler owned SIGWINCH).
+pub fn invalidate_terminal_width() {
+    TERMINAL_WIDTH.store(0, Ordering::Relaxed);
+}
+
+#[cfg(unix)]
+extern "C" fn handle_sigwinch(_: nix::libc::c_int) {
+    // Only an atomic store here - must stay async-signal-safe
+    TERMINAL_WIDTH.store(0, Ordering::Relaxed);
+}
+
+/// Install a SIGWINCH handler that invalidates the width cache
+#[cfg(unix)]
+fn install_resize_handler() {
+    use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+    let action = SigAction::new(
+        SigHandler::Handler(handle_sigwinch),
+        SaFlags::SA_RESTART,
+        SigSet::empty(),
+    );
+    // SAFETY: the handler only performs an atomic store
+    let _ = unsafe { sigaction(Signal::SIGWINCH, &action) };
 }
 
+#[cfg(not(unix))]
+fn install_resize_handler() {}
+
 /// Calculate display width of text (handles Arabic correctly)
+///
+/// ANSI escape sequences (e.g. colors) are ignored since they take no space.
 pub fn display_width(text: &str) -> usize {
-    UnicodeWidthStr::width(text)
+    UnicodeWidthStr::width(strip_ansi(text).as_ref())
+}
+
+/// Remove ANSI CSI escape sequences (`ESC [ ... final`) from text
+///
+/// Returns the input unchanged (borrowed) when it contains no escapes.
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut start = 0;
+    for (escape_start, escape_end) in ansi_escapes(text) {
+        result.push_str(&text[start..escape_start]);
+        start = escape_end;
+    }
+    result.push_str(&text[start..]);
+    Cow::Owned(result)
+}
+
+/// Byte ranges of the ANSI escape sequences in text, in order
+fn ansi_escapes(text: &str) -> Vec<(usize, usize)> {
+    let mut escapes = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '\x1b' {
+            continue;
+        }
+
+        let mut end = text.len();
+        if matches!(chars.peek(), Some((_, '['))) {
+            chars.next();
+            // Parameter and intermediate bytes, then a single final byte
+            for (i, c) in chars.by_ref() {
+                if ('\u{40}'..='\u{7E}').contains(&c) {
+                    end = i + c.len_utf8();
+                    break;
+                }
+            }
+        } else if let Some((i, c)) = chars.next() {
+            // Two-character escape (e.g. ESC 7)
+            end = i + c.len_utf8();
+        }
+        escapes.push((start, end));
+    }
+    escapes
 }
 
 /// Right-align text for RTL display (fallback method for non-VTE terminals)
 ///
-/// Adds padding on the left to push text to the right side of the terminal.
+/// Reorders mixed-direction text into visual order, then adds padding on
+/// the left to push it to the right side of the terminal.
 pub fn right_align(text: &str) -> String {
+    let text = reorder_bidi(text);
+    let text = text.as_ref();
     let term_width = get_terminal_width();
+    // Measure visible characters only, colored text carries escape codes
     let text_width = display_width(text);
 
     if text_width >= term_width {
@@ -123,6 +299,8 @@ pub fn right_align(text: &str) -> String {
 }
 
 /// Format a line for RTL display (shape + optionally right-align)
+///
+/// Text may already be colored; escapes are skipped when measuring.
 #[allow(dead_code)]
 pub fn format_rtl(text: &str, use_padding: bool) -> String {
     let shaped = shape_arabic(text);
@@ -198,6 +376,67 @@ mod tests {
         assert!(display_width("محيط") > 0);
     }
 
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("plain"), "plain");
+        assert!(matches!(strip_ansi("plain"), Cow::Borrowed(_)));
+        assert_eq!(strip_ansi("\x1b[1;34mdir\x1b[0m/"), "dir/");
+        assert_eq!(strip_ansi("\x1b[31m"), "");
+    }
+
+    #[test]
+    fn test_shape_if_arabic_keeps_escapes() {
+        let colored = "\x1b[01;34mمجلد\x1b[0m/";
+        let shaped = shape_if_arabic(colored);
+        assert_eq!(strip_ansi(&shaped), shape_arabic("مجلد") + "/");
+        assert!(shaped.starts_with("\x1b[01;34m") && shaped.contains("\x1b[0m/"));
+
+        // Colored ASCII is returned byte for byte
+        assert_eq!(shape_if_arabic("\x1b[32mok\x1b[0m"), "\x1b[32mok\x1b[0m");
+    }
+
+    #[test]
+    fn test_display_width_ignores_colors() {
+        let arabic = "محيط";
+        let red = format!("\x1b[31m{}\x1b[0m", arabic);
+        assert!(red.len() > arabic.len());
+        assert_eq!(display_width(&red), display_width(arabic));
+    }
+
+    #[test]
+    fn test_right_align_colored_text() {
+        let plain = right_align("test");
+        let colored = right_align("\x1b[31mtest\x1b[0m");
+        let padding = |s: &str| s.len() - s.trim_start().len();
+        assert_eq!(padding(&plain), padding(&colored));
+    }
+
+    #[test]
+    fn test_reorder_bidi_english_passthrough() {
+        assert_eq!(reorder_bidi("file 123"), "file 123");
+        assert!(matches!(reorder_bidi("file 123"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_reorder_bidi_mixed_line() {
+        // Logical "file محيط 123": in RTL visual order the Arabic word is
+        // reversed and the English/number runs keep their own direction.
+        let visual = reorder_bidi("file محيط 123");
+        assert_eq!(visual, "123 طيحم file");
+    }
+
+    #[test]
+    fn test_reorder_bidi_keeps_escapes() {
+        let colored = "\x1b[31mمحيط abc\x1b[0m";
+        assert_eq!(reorder_bidi(colored), colored);
+    }
+
+    #[test]
+    fn test_reorder_bidi_multiline() {
+        let visual = reorder_bidi("ab\nمح");
+        assert_eq!(visual, "ab\nحم");
+    }
+
     #[test]
     fn test_get_terminal_width() {
         // Should return at least the default of 80
@@ -205,6 +444,16 @@ mod tests {
         assert!(width >= 80 || width > 0);
     }
 
+    #[test]
+    fn test_terminal_width_cache_invalidation() {
+        let width = get_terminal_width();
+        assert_eq!(TERMINAL_WIDTH.load(Ordering::Relaxed), width);
+
+        invalidate_terminal_width();
+        assert!(get_terminal_width() > 0);
+        assert_ne!(TERMINAL_WIDTH.load(Ordering::Relaxed), 0);
+    }
+
     #[test]
     fn test_format_rtl_with_padding() {
         let text = "Test";
@@ -220,4 +469,13 @@ mod tests {
         // Without padding, just shaped (English stays same)
         assert_eq!(formatted, text);
     }
+
+    #[test]
+    fn test_normalize_digits() {
+        assert_eq!(normalize_digits("١٢٣"), "123");
+        assert_eq!(normalize_digits("٢٫٥"), "2.5");
+        assert_eq!(normalize_digits("۴۲"), "42");
+        assert_eq!(normalize_digits("abc 7"), "abc 7");
+        assert!(matches!(normalize_digits("7"), Cow::Borrowed(_)));
+    }
 }
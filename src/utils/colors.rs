@@ -1,35 +1,158 @@
 //! # Color Utilities (أدوات الألوان)
 //!
 //! Provides color formatting for terminal output.
+//!
+//! All colors come from the active [`Theme`], selectable with the
+//! `OCEAN_THEME` environment variable or the `سمة` (theme) builtin.
+
+use colored::{Color, Colorize};
+use std::io::IsTerminal;
+use std::sync::RwLock;
+
+use super::prompt::exit_status_marker;
+use super::locale::Message;
+
+/// A set of colors used across the shell's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// English name (used by `OCEAN_THEME` and `سمة`)
+    pub name: &'static str,
+    /// Arabic name (also accepted by `سمة`)
+    pub arabic_name: &'static str,
+    pub directory: Color,
+    pub executable: Color,
+    pub symlink: Color,
+    pub readonly: Color,
+    pub prompt_name: Color,
+    pub prompt_cwd: Color,
+    pub error: Color,
+    pub success: Color,
+}
+
+/// Theme for dark terminal backgrounds (the default)
+pub const DARK_THEME: Theme = Theme {
+    name: "dark",
+    arabic_name: "داكن",
+    directory: Color::Blue,
+    executable: Color::Green,
+    symlink: Color::Magenta,
+    readonly: Color::Red,
+    prompt_name: Color::Cyan,
+    prompt_cwd: Color::Blue,
+    error: Color::Red,
+    success: Color::Green,
+};
+
+/// Theme for light terminal backgrounds
+pub const LIGHT_THEME: Theme = Theme {
+    name: "light",
+    arabic_name: "فاتح",
+    directory: Color::Blue,
+    executable: Color::Green,
+    symlink: Color::Cyan,
+    readonly: Color::Red,
+    prompt_name: Color::Magenta,
+    prompt_cwd: Color::Black,
+    error: Color::Red,
+    success: Color::Green,
+};
+
+/// All built-in themes
+pub const THEMES: &[Theme] = &[DARK_THEME, LIGHT_THEME];
+
+/// Environment variable selecting the theme at startup
+pub const THEME_ENV_VAR: &str = "OCEAN_THEME";
+
+static ACTIVE_THEME: RwLock<Theme> = RwLock::new(DARK_THEME);
+
+/// Find a built-in theme by its English or Arabic name
+pub fn theme_by_name(name: &str) -> Option<Theme> {
+    THEMES
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name) || t.arabic_name == name)
+        .copied()
+}
+
+/// Get the currently active theme
+pub fn active_theme() -> Theme {
+    ACTIVE_THEME.read().map(|t| *t).unwrap_or(DARK_THEME)
+}
 
-use colored::Colorize;
+/// Make a theme the active one
+pub fn set_theme(theme: Theme) {
+    if let Ok(mut active) = ACTIVE_THEME.write() {
+        *active = theme;
+    }
+}
+
+/// Decide once at startup whether output is colored
+///
+/// Color is off with `--no-color` (`disabled`), when `NO_COLOR` is set to a
+/// non-empty value, or when stdout isn't a terminal. This applies to `اعرض`,
+/// the prompt, and error messages alike.
+pub fn init_color(disabled: bool) {
+    colored::control::set_override(should_color(
+        disabled,
+        std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()),
+        std::io::stdout().is_terminal(),
+    ));
+}
+
+fn should_color(disabled: bool, no_color_env: bool, is_terminal: bool) -> bool {
+    !disabled && !no_color_env && is_terminal
+}
+
+/// Apply the theme named by `OCEAN_THEME`, if set
+///
+/// Returns a warning when the variable names an unknown theme.
+pub fn load_theme_from_env() -> Result<(), Message> {
+    match std::env::var(THEME_ENV_VAR) {
+        Ok(name) if !name.is_empty() => match theme_by_name(&name) {
+            Some(theme) => {
+                set_theme(theme);
+                Ok(())
+            }
+            None => Err(Message::warning((
+                format!("سمة غير معروفة '{}'", name),
+                format!("Unknown theme '{}'", name),
+            ))),
+        },
+        _ => Ok(()),
+    }
+}
 
 /// Format the shell prompt with colors
 ///
 /// # Arguments
 /// * `shell_name` - The shell name (shaped Arabic text)
 /// * `cwd` - Current working directory path
+/// * `last_exit_code` - Exit code of the previous command, shown when nonzero
 ///
 /// # Returns
 /// Colored prompt string
-pub fn colored_prompt(shell_name: &str, cwd: &str) -> String {
+pub fn colored_prompt(shell_name: &str, cwd: &str, last_exit_code: i32) -> String {
+    let theme = active_theme();
+    let status = exit_status_marker(last_exit_code)
+        .map(|marker| format!(" {}", marker.color(theme.error).bold()))
+        .unwrap_or_default();
     format!(
-        "{} [{}]> ",
-        shell_name.cyan().bold(),
-        cwd.blue()
+        "{} [{}]{}> ",
+        shell_name.color(theme.prompt_name).bold(),
+        cwd.color(theme.prompt_cwd),
+        status
     )
 }
 
-/// Format an error message in red
+/// Format an error message in the theme's error color
 #[allow(dead_code)]
 pub fn colored_error(msg: &str) -> String {
-    msg.red().to_string()
+    msg.color(active_theme().error).to_string()
 }
 
-/// Format a success message in green
+/// Format a success message in the theme's success color
 #[allow(dead_code)]
 pub fn colored_success(msg: &str) -> String {
-    msg.green().to_string()
+    msg.color(active_theme().success).to_string()
 }
 
 /// Colorize a file/directory entry for ls output
@@ -43,24 +166,45 @@ pub fn colored_success(msg: &str) -> String {
 /// Colorized name string
 #[allow(dead_code)]
 pub fn colorize_entry(name: &str, is_dir: bool, is_exec: bool) -> String {
-    if is_dir {
-        format!("{}/", name.blue().bold())
-    } else if is_exec {
-        name.green().bold().to_string()
-    } else {
-        name.to_string()
-    }
+    colorize_entry_full(name, is_dir, is_exec, false, false)
 }
 
-/// Colorize file entry with symlink support
-#[allow(dead_code)]
-pub fn colorize_entry_full(name: &str, is_dir: bool, is_exec: bool, is_symlink: bool) -> String {
+/// Wrap `text` in the escape codes for `color`, bold if asked
+///
+/// Unlike [`Colorize`], this ignores colored's global override; it's for
+/// output whose caller already decided per command whether to color it
+/// (`BuiltinOptions::color`).
+pub fn paint(text: &str, color: Color, bold: bool) -> String {
+    format!("\x1b[{}{}m{}\x1b[0m", if bold { "1;" } else { "" }, color.to_fg_str(), text)
+}
+
+/// Colorize file entry with symlink and read-only support
+pub fn colorize_entry_full(
+    name: &str,
+    is_dir: bool,
+    is_exec: bool,
+    is_symlink: bool,
+    is_readonly: bool,
+) -> String {
+    colorize_entry_themed(active_theme(), name, is_dir, is_exec, is_symlink, is_readonly)
+}
+
+fn colorize_entry_themed(
+    theme: Theme,
+    name: &str,
+    is_dir: bool,
+    is_exec: bool,
+    is_symlink: bool,
+    is_readonly: bool,
+) -> String {
     if is_symlink {
-        name.magenta().to_string()
+        name.color(theme.symlink).to_string()
     } else if is_dir {
-        format!("{}/", name.blue().bold())
+        format!("{}/", name.color(theme.directory).bold())
     } else if is_exec {
-        name.green().bold().to_string()
+        name.color(theme.executable).bold().to_string()
+    } else if is_readonly {
+        name.color(theme.readonly).to_string()
     } else {
         name.to_string()
     }
@@ -72,10 +216,26 @@ mod tests {
 
     #[test]
     fn test_colored_prompt() {
-        let prompt = colored_prompt("محيط", "~/test");
+        let prompt = colored_prompt("محيط", "~/test", 0);
         assert!(!prompt.is_empty());
         // Contains the actual text (without ANSI codes check)
         assert!(prompt.contains("[") && prompt.contains("]"));
+        assert!(!prompt.contains('✗'));
+    }
+
+    #[test]
+    fn test_colored_prompt_shows_failed_exit_code() {
+        let prompt = colored_prompt("محيط", "~/x", 2);
+        assert!(prompt.contains("✗2"));
+        assert!(prompt.ends_with("> "));
+    }
+
+    #[test]
+    fn test_should_color() {
+        assert!(should_color(false, false, true));
+        assert!(!should_color(true, false, true));
+        assert!(!should_color(false, true, true));
+        assert!(!should_color(false, false, false));
     }
 
     #[test]
@@ -84,4 +244,31 @@ mod tests {
         assert!(result.contains("mydir"));
         assert!(result.ends_with('/') || result.contains('/'));
     }
+
+    #[test]
+    fn test_theme_by_name() {
+        assert_eq!(theme_by_name("dark"), Some(DARK_THEME));
+        assert_eq!(theme_by_name("LIGHT"), Some(LIGHT_THEME));
+        assert_eq!(theme_by_name("فاتح"), Some(LIGHT_THEME));
+        assert_eq!(theme_by_name("neon"), None);
+    }
+
+    #[test]
+    fn test_symlink_uses_theme_color() {
+        // Forced on only for this test and restored after, since the
+        // override is global
+        colored::control::set_override(true);
+        let light = colorize_entry_themed(LIGHT_THEME, "link", false, false, true, false);
+        let dark = colorize_entry_themed(DARK_THEME, "link", false, false, true, false);
+        colored::control::unset_override();
+
+        assert_eq!(light, "\x1b[36mlink\x1b[0m");
+        assert_eq!(dark, "\x1b[35mlink\x1b[0m");
+    }
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(paint("ocean", Color::Red, true), "\x1b[1;31mocean\x1b[0m");
+        assert_eq!(paint("محيط", Color::Green, false), "\x1b[32mمحيط\x1b[0m");
+    }
 }
@@ -0,0 +1,49 @@
+//! # Interrupts (المقاطعة)
+//!
+//! In the interactive shell Ctrl+C must not kill the shell itself. A SIGINT
+//! handler records the interrupt instead, and long-running constructs such
+//! as `طالما` loops check the flag to stop early. Foreground external
+//! commands still receive the signal and stop as usual.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static INTERRUPT_HANDLER: Once = Once::new();
+
+/// Catch SIGINT for the rest of the process instead of exiting
+pub fn install_interrupt_handler() {
+    INTERRUPT_HANDLER.call_once(install);
+}
+
+/// Whether Ctrl+C was pressed since the last [`clear_interrupt`]
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Forget an earlier Ctrl+C, before running a new command line
+pub fn clear_interrupt() {
+    INTERRUPTED.store(false, Ordering::Relaxed);
+}
+
+#[cfg(unix)]
+extern "C" fn handle_sigint(_: nix::libc::c_int) {
+    // Only an atomic store here - must stay async-signal-safe
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+#[cfg(unix)]
+fn install() {
+    use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+    let action = SigAction::new(
+        SigHandler::Handler(handle_sigint),
+        SaFlags::SA_RESTART,
+        SigSet::empty(),
+    );
+    // SAFETY: the handler only performs an atomic store
+    let _ = unsafe { sigaction(Signal::SIGINT, &action) };
+}
+
+#[cfg(not(unix))]
+fn install() {}
@@ -0,0 +1,143 @@
+//! # Message Language (لغة الرسائل)
+//!
+//! Errors and warnings are written in Arabic and English and kept as a
+//! [`Message`] until they are printed. The active locale (`لغة` / lang, or
+//! `OCEAN_LANG`) then decides whether both are shown or only one; the
+//! default keeps both.
+
+use std::sync::RwLock;
+
+/// Environment variable selecting the message language at startup
+pub const LOCALE_ENV_VAR: &str = "OCEAN_LANG";
+
+/// Which language messages are printed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    Arabic,
+    English,
+    Both,
+}
+
+impl Locale {
+    /// English name, as accepted by `لغة` and `OCEAN_LANG`
+    pub fn name(self) -> &'static str {
+        match self {
+            Locale::Arabic => "ar",
+            Locale::English => "en",
+            Locale::Both => "both",
+        }
+    }
+
+    /// Arabic name (also accepted by `لغة`)
+    pub fn arabic_name(self) -> &'static str {
+        match self {
+            Locale::Arabic => "عربي",
+            Locale::English => "انجليزي",
+            Locale::Both => "كلاهما",
+        }
+    }
+
+    /// Pick the Arabic or English text, or join them as `ar / en`
+    pub fn choose(self, ar: &str, en: &str) -> String {
+        match self {
+            Locale::Arabic => ar.to_string(),
+            Locale::English => en.to_string(),
+            Locale::Both => format!("{} / {}", ar, en),
+        }
+    }
+}
+
+/// A message in Arabic and English
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub ar: String,
+    pub en: String,
+}
+
+impl Message {
+    /// A warning, prefixed `تحذير:` / `Warning:`
+    pub fn warning(message: impl Into<Message>) -> Self {
+        let message = message.into();
+        Self { ar: format!("تحذير: {}", message.ar), en: format!("Warning: {}", message.en) }
+    }
+
+    /// The message in Arabic, English, or both
+    pub fn render(&self, locale: Locale) -> String {
+        locale.choose(&self.ar, &self.en)
+    }
+}
+
+impl<A: Into<String>, E: Into<String>> From<(A, E)> for Message {
+    fn from((ar, en): (A, E)) -> Self {
+        Self { ar: ar.into(), en: en.into() }
+    }
+}
+
+/// Every locale, in the order `لغة` lists them
+pub const LOCALES: &[Locale] = &[Locale::Arabic, Locale::English, Locale::Both];
+
+static ACTIVE_LOCALE: RwLock<Locale> = RwLock::new(Locale::Both);
+
+/// Find a locale by its English or Arabic name
+pub fn locale_by_name(name: &str) -> Option<Locale> {
+    LOCALES
+        .iter()
+        .find(|l| l.name().eq_ignore_ascii_case(name) || l.arabic_name() == name)
+        .copied()
+}
+
+/// Get the currently active locale
+pub fn active_locale() -> Locale {
+    ACTIVE_LOCALE.read().map(|l| *l).unwrap_or(Locale::Both)
+}
+
+/// Make a locale the active one
+pub fn set_locale(locale: Locale) {
+    if let Ok(mut active) = ACTIVE_LOCALE.write() {
+        *active = locale;
+    }
+}
+
+/// Apply the locale named by `OCEAN_LANG`, if set
+///
+/// Returns a warning when the variable names an unknown locale.
+pub fn load_locale_from_env() -> Result<(), Message> {
+    match std::env::var(LOCALE_ENV_VAR) {
+        Ok(name) if !name.is_empty() => match locale_by_name(&name) {
+            Some(locale) => {
+                set_locale(locale);
+                Ok(())
+            }
+            None => Err(Message::warning((
+                format!("لغة غير معروفة '{}'", name),
+                format!("Unknown language '{}'", name),
+            ))),
+        },
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_in_each_locale() {
+        let message = Message::from(("تخطي 'x'", "Skipping 'x'"));
+        assert_eq!(message.render(Locale::Arabic), "تخطي 'x'");
+        assert_eq!(message.render(Locale::English), "Skipping 'x'");
+        assert_eq!(message.render(Locale::Both), "تخطي 'x' / Skipping 'x'");
+
+        let warning = Message::warning(message);
+        assert_eq!(warning.render(Locale::English), "Warning: Skipping 'x'");
+        assert_eq!(warning.render(Locale::Arabic), "تحذير: تخطي 'x'");
+    }
+
+    #[test]
+    fn test_locale_by_name() {
+        assert_eq!(locale_by_name("EN"), Some(Locale::English));
+        assert_eq!(locale_by_name("عربي"), Some(Locale::Arabic));
+        assert_eq!(locale_by_name("both"), Some(Locale::Both));
+        assert_eq!(locale_by_name("fr"), None);
+    }
+}
@@ -4,6 +4,10 @@
 
 pub mod arabic;
 pub mod colors;
+pub mod interrupt;
+pub mod locale;
+pub mod prompt;
+pub mod time;
 
 pub use arabic::{
     shape_arabic,
@@ -12,32 +16,108 @@ pub use arabic::{
     // RTL alignment functions
     enable_rtl_mode,
     right_align,
+    normalize_digits,
 };
 
 // Additional RTL functions available for future use
 #[allow(unused_imports)]
-pub use arabic::{get_terminal_width, display_width, format_rtl, println_rtl};
+pub use arabic::{get_terminal_width, invalidate_terminal_width, display_width, strip_ansi, reorder_bidi, format_rtl, println_rtl};
 
 // Color utilities
-pub use colors::colored_prompt;
+pub use colors::{colored_prompt, colored_error, colorize_entry_full, paint};
+pub use colors::{theme_by_name, active_theme, set_theme, load_theme_from_env, init_color, THEMES};
 #[allow(unused_imports)]
-pub use colors::{colored_error, colorize_entry};
+pub use colors::colorize_entry;
 
-use std::path::PathBuf;
+// Ctrl+C handling
+pub use interrupt::{install_interrupt_handler, interrupted, clear_interrupt};
+
+// Message language
+pub use locale::{Locale, LOCALES, Message, locale_by_name, active_locale, set_locale, load_locale_from_env};
+
+// Prompt templates
+pub use prompt::{render_prompt, prompt_template, exit_status_marker, PromptContext};
+
+// Time formatting
+pub use time::{now_timestamp, format_timestamp};
+
+use std::path::{Path, PathBuf};
 
 /// Expand ~ to home directory
+///
+/// Handles `~`, `~/path`, and (on Unix) `~user/path`. Unknown users are
+/// left as a literal path.
 pub fn expand_tilde(path: &str) -> PathBuf {
-    if path.starts_with('~') {
-        if let Some(home) = dirs::home_dir() {
-            return home.join(path[1..].trim_start_matches('/'));
+    expand_tilde_from(path, dirs::home_dir())
+}
+
+/// Expand ~ like [`expand_tilde`], taking a bare `~` to be `home`
+pub fn expand_tilde_from(path: &str, home: Option<PathBuf>) -> PathBuf {
+    if let Some(rest) = path.strip_prefix('~') {
+        let (user, tail) = rest.split_once('/').unwrap_or((rest, ""));
+        let home = if user.is_empty() {
+            home
+        } else {
+            user_home_dir(user)
+        };
+
+        if let Some(home) = home {
+            return home.join(tail.trim_start_matches('/'));
         }
     }
     PathBuf::from(path)
 }
 
+/// Collapse `.` and `..` textually, the way `cd -L` builds `PWD`
+///
+/// `a/link/..` becomes `a` even if `link` is a symlink elsewhere, and
+/// trailing slashes disappear. `..` never climbs above the root.
+pub fn normalize_logical(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(normalized.components().next_back(), Some(Component::Normal(_))) {
+                    normalized.pop();
+                } else if !normalized.has_root() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Look up another user's home directory
+#[cfg(unix)]
+fn user_home_dir(user: &str) -> Option<PathBuf> {
+    match nix::unistd::User::from_name(user) {
+        Ok(Some(user)) => Some(user.dir),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn user_home_dir(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+/// The command history file, `~/.ocean_history`
+///
+/// Falls back to the current directory when there is no home directory.
+pub fn history_path() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".ocean_history"))
+        .unwrap_or_else(|| PathBuf::from(".ocean_history"))
+}
+
 /// Shorten path for display (replace home with ~)
 #[allow(dead_code)]
-pub fn shorten_path(path: &PathBuf) -> String {
+pub fn shorten_path(path: &Path) -> String {
     if let Some(home) = dirs::home_dir() {
         if let Ok(relative) = path.strip_prefix(&home) {
             return format!("~/{}", relative.display());
@@ -45,3 +125,114 @@ pub fn shorten_path(path: &PathBuf) -> String {
     }
     path.display().to_string()
 }
+
+/// Format a byte count, either raw or human-readable (`1.5 KB`, `3.0 MB`)
+///
+/// Human-readable sizes use powers of 1024.
+pub fn format_size(bytes: u64, human: bool) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+
+    if !human || bytes < 1024 {
+        return if human { format!("{} B", bytes) } else { bytes.to_string() };
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Render Unix mode bits symbolically, `ls -l` style (`drwxr-xr-x`)
+///
+/// The first character is the file type (`-`, `d`, `l`, `p`, `s`, `c`,
+/// `b`); setuid/setgid/sticky bits show as `s`/`S` and `t`/`T`.
+pub fn format_mode(mode: u32) -> String {
+    let kind = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o010000 => 'p',
+        0o140000 => 's',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        _ => '-',
+    };
+
+    let mut out = String::from(kind);
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        out.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_logical() {
+        let cases = [
+            ("/a/b/../c", "/a/c"),
+            ("/a/./b/", "/a/b"),
+            ("/a/b/../../..", "/"),
+            ("a/../../b", "../b"),
+            ("/a//b///", "/a/b"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize_logical(Path::new(input)), PathBuf::from(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_format_mode() {
+        assert_eq!(format_mode(0o100644), "-rw-r--r--");
+        assert_eq!(format_mode(0o040755), "drwxr-xr-x");
+        assert_eq!(format_mode(0o120777), "lrwxrwxrwx");
+        assert_eq!(format_mode(0o104755), "-rwsr-xr-x");
+        assert_eq!(format_mode(0o041777), "drwxrwxrwt");
+        assert_eq!(format_mode(0o100600 | 0o2000), "-rw---S---");
+    }
+
+    #[test]
+    fn test_expand_tilde_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_tilde("~"), home.join(""));
+        assert_eq!(expand_tilde("~/x"), home.join("x"));
+    }
+
+    #[test]
+    fn test_expand_tilde_unknown_user() {
+        assert_eq!(expand_tilde("~nobody123/docs"), PathBuf::from("~nobody123/docs"));
+    }
+
+    #[test]
+    fn test_expand_tilde_plain_path() {
+        assert_eq!(expand_tilde("a/~b"), PathBuf::from("a/~b"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_tilde_user() {
+        let root = nix::unistd::User::from_name("root").unwrap().unwrap();
+        assert_eq!(expand_tilde("~root/docs"), root.dir.join("docs"));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(1536, false), "1536");
+        assert_eq!(format_size(512, true), "512 B");
+        assert_eq!(format_size(1536, true), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024, true), "3.0 MB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024, true), "5.0 GB");
+    }
+}
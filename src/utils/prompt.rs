@@ -0,0 +1,156 @@
+//! # Prompt Templates (قوالب المحث)
+//!
+//! Renders a PS1-like prompt template from the `OCEAN_PROMPT` variable.
+//!
+//! Supported escapes:
+//! - `\w` - current directory (home shortened to `~`)
+//! - `\u` - user name
+//! - `\h` - host name
+//! - `\$?` - exit code of the last command
+//! - `\n` - newline
+//! - `\\` - a literal backslash
+//!
+//! Unknown escapes are kept literally.
+
+/// Environment variable holding the prompt template
+pub const PROMPT_ENV_VAR: &str = "OCEAN_PROMPT";
+
+/// Values available to a prompt template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptContext {
+    pub cwd: String,
+    pub user: String,
+    pub host: String,
+    pub last_exit_code: i32,
+}
+
+impl PromptContext {
+    /// Build a context for the current user and host
+    pub fn new(cwd: String, last_exit_code: i32) -> Self {
+        Self {
+            cwd,
+            user: current_user(),
+            host: current_host(),
+            last_exit_code,
+        }
+    }
+}
+
+/// Get the prompt template from `OCEAN_PROMPT`, if set and non-empty
+pub fn prompt_template() -> Option<String> {
+    std::env::var(PROMPT_ENV_VAR).ok().filter(|t| !t.is_empty())
+}
+
+/// Marker shown in the default prompt after a failed command, e.g. `✗2`
+///
+/// Returns `None` for a zero exit code so successful commands keep the
+/// prompt uncluttered.
+pub fn exit_status_marker(last_exit_code: i32) -> Option<String> {
+    (last_exit_code != 0).then(|| format!("✗{}", last_exit_code))
+}
+
+/// Render a prompt template, replacing escapes with context values
+pub fn render_prompt(template: &str, ctx: &PromptContext) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.peek().copied() {
+            Some('w') => output.push_str(&ctx.cwd),
+            Some('u') => output.push_str(&ctx.user),
+            Some('h') => output.push_str(&ctx.host),
+            Some('n') => output.push('\n'),
+            Some('\\') => output.push('\\'),
+            Some('$') => {
+                chars.next();
+                if chars.peek() == Some(&'?') {
+                    output.push_str(&ctx.last_exit_code.to_string());
+                } else {
+                    // Not `\$?` - keep what we consumed
+                    output.push_str("\\$");
+                    continue;
+                }
+            }
+            Some(other) => {
+                output.push('\\');
+                output.push(other);
+            }
+            None => {
+                output.push('\\');
+                continue;
+            }
+        }
+        chars.next();
+    }
+
+    output
+}
+
+/// Name of the current user
+fn current_user() -> String {
+    #[cfg(unix)]
+    {
+        if let Ok(Some(user)) = nix::unistd::User::from_uid(nix::unistd::getuid()) {
+            return user.name;
+        }
+    }
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "?".to_string())
+}
+
+/// Name of this machine
+fn current_host() -> String {
+    #[cfg(unix)]
+    {
+        if let Ok(host) = nix::unistd::gethostname() {
+            return host.to_string_lossy().to_string();
+        }
+    }
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "?".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> PromptContext {
+        PromptContext {
+            cwd: "~/مشروع".to_string(),
+            user: "osama".to_string(),
+            host: "box".to_string(),
+            last_exit_code: 2,
+        }
+    }
+
+    #[test]
+    fn test_render_prompt_placeholders() {
+        assert_eq!(render_prompt("\\u@\\h:\\w> ", &ctx()), "osama@box:~/مشروع> ");
+        assert_eq!(render_prompt("[\\$?]\\n$ ", &ctx()), "[2]\n$ ");
+    }
+
+    #[test]
+    fn test_render_prompt_unknown_escapes() {
+        assert_eq!(render_prompt("\\q \\$ \\\\", &ctx()), "\\q \\$ \\");
+        assert_eq!(render_prompt("end\\", &ctx()), "end\\");
+    }
+
+    #[test]
+    fn test_render_prompt_plain() {
+        assert_eq!(render_prompt("محيط> ", &ctx()), "محيط> ");
+    }
+
+    #[test]
+    fn test_exit_status_marker() {
+        assert_eq!(exit_status_marker(0), None);
+        assert_eq!(exit_status_marker(2).as_deref(), Some("✗2"));
+        assert_eq!(exit_status_marker(127).as_deref(), Some("✗127"));
+    }
+}
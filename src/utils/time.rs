@@ -0,0 +1,67 @@
+//! # Time Formatting (تنسيق الوقت)
+//!
+//! A minimal UTC timestamp formatter, so the shell doesn't need a date/time
+//! dependency just to stamp log lines.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Format a point in time as `YYYY-MM-DD HH:MM:SS` (UTC)
+///
+/// Times before the Unix epoch are clamped to the epoch.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// Format the current time with [`format_timestamp`]
+pub fn now_timestamp() -> String {
+    format_timestamp(SystemTime::now())
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) civil date
+///
+/// Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_epoch() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01 00:00:00");
+    }
+
+    #[test]
+    fn test_format_known_time() {
+        // 2024-02-29 13:45:30 UTC (leap day)
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_214_330);
+        assert_eq!(format_timestamp(time), "2024-02-29 13:45:30");
+    }
+}
@@ -0,0 +1,347 @@
+//! Integration tests for the `ocean` binary's command-line interface
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn ocean() -> Command {
+    Command::cargo_bin("ocean").unwrap()
+}
+
+#[test]
+fn test_command_flag_runs_once() {
+    ocean()
+        .args(["-c", "echo hello"])
+        .assert()
+        .success()
+        .stdout("hello\n");
+}
+
+#[test]
+fn test_command_flag_long_form() {
+    ocean()
+        .args(["--command", "echo a ; echo b"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("b"));
+}
+
+#[test]
+fn test_command_flag_exit_status() {
+    ocean().args(["-c", "exit"]).assert().code(0);
+    ocean()
+        .args(["-c", "cat /nonexistent/ocean-test-file"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Error"));
+}
+
+#[test]
+fn test_command_flag_parse_error() {
+    ocean().args(["-c", "echo a |"]).assert().code(2);
+}
+
+#[test]
+fn test_command_flag_missing_argument() {
+    ocean().arg("-c").assert().code(2);
+}
+
+#[test]
+fn test_script_file_runs_each_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("script.ocean");
+    std::fs::write(&script, "#!/usr/bin/env ocean\n# comment\n\necho one\necho two\n").unwrap();
+
+    ocean()
+        .arg(&script)
+        .assert()
+        .success()
+        .stdout("one\ntwo\n");
+}
+
+#[test]
+fn test_script_file_stops_on_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("script.ocean");
+    std::fs::write(&script, "echo before\ncat /nonexistent/ocean-test-file\necho after\n").unwrap();
+
+    ocean()
+        .arg(&script)
+        .assert()
+        .code(1)
+        .stdout("before\n");
+}
+
+#[test]
+fn test_script_file_or_continues() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("script.ocean");
+    std::fs::write(
+        &script,
+        "cat /nonexistent/ocean-test-file || cat /nonexistent/ocean-test-file\necho after\n",
+    )
+    .unwrap();
+
+    ocean()
+        .arg(&script)
+        .assert()
+        .success()
+        .stdout("after\n");
+}
+
+#[test]
+fn test_script_file_loop_spans_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("script.ocean");
+    std::fs::write(&script, "لكل x في a b\nنفذ\n  # each item\n  اطبع $x\nانتهى\necho after\n").unwrap();
+
+    ocean()
+        .arg(&script)
+        .assert()
+        .success()
+        .stdout("a\nb\nafter\n");
+}
+
+#[test]
+fn test_script_file_parse_error_names_the_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("script.ocean");
+    std::fs::write(&script, "echo before\n\necho a && && b\necho after\n").unwrap();
+
+    let prefix = format!("{}:3: ", script.display());
+    ocean()
+        .arg(&script)
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::starts_with(prefix));
+}
+
+#[test]
+fn test_script_file_missing() {
+    ocean().arg("/nonexistent/script.ocean").assert().code(127);
+}
+
+#[test]
+fn test_time_flag_stamps_output() {
+    ocean()
+        .args(["--time", "-c", "echo hello"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"^\[\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\] hello\n$").unwrap());
+}
+
+#[test]
+fn test_time_flag_skips_redirected_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out.txt");
+
+    ocean()
+        .args(["-t", "-c", &format!("echo hello > {}", out.display())])
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "hello\n");
+}
+
+#[test]
+fn test_version_flag() {
+    ocean()
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn test_help_flag() {
+    for flag in ["--help", "-h"] {
+        ocean()
+            .arg(flag)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("--command"));
+    }
+}
+
+#[test]
+fn test_piped_output_has_no_color() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    let ls = format!("ls {}", dir.path().display());
+
+    // Not a terminal: colors are off even when forced through the environment
+    ocean()
+        .env("CLICOLOR_FORCE", "1")
+        .args(["-c", &ls])
+        .assert()
+        .success()
+        .stdout("sub/\n");
+
+    ocean()
+        .args(["--no-color", "-c", &ls])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b").not());
+}
+
+#[test]
+fn test_eprintln_bypasses_stdout_redirect() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out.txt");
+
+    for name in ["صدى_خطأ", "eprintln"] {
+        ocean()
+            .args(["-c", &format!("{} warning > {}", name, out.display())])
+            .assert()
+            .success()
+            .stdout("")
+            .stderr("warning\n");
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "");
+    }
+}
+
+#[test]
+fn test_cd_applies_to_builtins_and_external_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("note.txt"), "note\n").unwrap();
+    let script = format!("انتقل {} ; اقرأ note.txt ; cat note.txt", dir.path().display());
+
+    ocean()
+        .args(["-c", &script])
+        .assert()
+        .success()
+        .stdout("note\nnote\n");
+}
+
+#[test]
+fn test_background_job_output_is_flushed_before_exit() {
+    ocean()
+        .args(["-c", "echo hello &"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("hello\n[1] "))
+        .stdout(predicate::str::ends_with("Done  echo hello\n"))
+        .stderr("[1]\n");
+}
+
+#[test]
+fn test_time_reports_to_stderr_and_keeps_exit_code() {
+    ocean()
+        .args(["-c", "time sh -c 'echo out; exit 3'"])
+        .assert()
+        .code(3)
+        .stdout("")
+        .stderr(predicate::str::contains("real"));
+    ocean()
+        .args(["-c", "time echo hello"])
+        .assert()
+        .success()
+        .stdout("hello\n")
+        .stderr(predicate::str::contains("real").and(predicate::str::contains("user")));
+}
+
+#[test]
+fn test_redirect_target_expands_tilde() {
+    let home = tempfile::tempdir().unwrap();
+    ocean()
+        .args(["-c", "echo hello > ~/out.txt ; echo again >> ~/out.txt"])
+        .env("HOME", home.path())
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(std::fs::read_to_string(home.path().join("out.txt")).unwrap(), "hello\nagain\n");
+}
+
+#[test]
+fn test_set_x_traces_and_set_e_stops() {
+    ocean()
+        .args(["-c", "set -x ; echo $HOME_OCEAN_UNSET hi"])
+        .assert()
+        .success()
+        .stdout("hi\n")
+        .stderr("+ echo hi\n");
+    ocean()
+        .args(["-c", "set -e ; cat /nonexistent/ocean-test-file ; echo after"])
+        .assert()
+        .code(1)
+        .stdout("");
+}
+
+#[test]
+fn test_echo_expands_variable_and_command_substitution() {
+    let dir = tempfile::tempdir().unwrap();
+    let cwd = std::fs::canonicalize(dir.path()).unwrap();
+    ocean()
+        .args(["-c", "echo $GREETING from $(pwd) \"[$(echo a   b)]\""])
+        .env("GREETING", "hello")
+        .current_dir(&cwd)
+        .assert()
+        .success()
+        .stdout(format!("hello from {} [a b]\n", cwd.display()));
+}
+
+#[test]
+fn test_ocean_lang_selects_message_language() {
+    // English only: no Arabic half and no separator
+    ocean()
+        .args(["-c", "cat /nonexistent/file"])
+        .env("OCEAN_LANG", "en")
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("Error: Cannot read '/nonexistent/file'"))
+        .stderr(predicate::str::contains(" / ").not());
+
+    ocean()
+        .args(["-c", "lang ar ; cat"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Error").not());
+
+    ocean()
+        .args(["-c", "cat"])
+        .env("OCEAN_LANG", "both")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\nError: Please specify a file\nUsage: cat"));
+}
+
+#[test]
+fn test_ocean_lang_applies_to_every_error() {
+    for (command, english) in [
+        ("false", "Command exited with code: 1\n"),
+        ("ocean-no-such-command", "Error: Command 'ocean-no-such-command' not found"),
+        ("echo a |", "Parse error [1:9]: Expected word, got: EOF"),
+        ("echo a > /nonexistent/dir/file", "Error: Cannot open '/nonexistent/dir/file'"),
+    ] {
+        ocean()
+            .args(["-c", command])
+            .env("OCEAN_LANG", "en")
+            .assert()
+            .failure()
+            .stderr(predicate::str::starts_with(english))
+            .stderr(predicate::str::contains(" / ").not());
+
+        ocean()
+            .args(["-c", command])
+            .env("OCEAN_LANG", "ar")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Error").not())
+            .stderr(predicate::str::contains("Command").not());
+    }
+}
+
+#[test]
+fn test_mkdir_parents_reports_only_missing_dirs_under_tilde() {
+    let home = tempfile::tempdir().unwrap();
+    std::fs::create_dir(home.path().join("a")).unwrap();
+
+    ocean()
+        .env("HOME", home.path())
+        .args(["-c", "انشئ -p -v ~/a/b/c"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("created '~/a/b'\n").and(predicate::str::contains("created '~/a/b/c'\n")))
+        .stdout(predicate::str::contains("'~'").not().and(predicate::str::contains("'~/a'").not()));
+    assert!(home.path().join("a/b/c").is_dir());
+}